@@ -0,0 +1,683 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Async API over [HdfsFs], enabled by ```async``` feature
+//!
+//! libhdfs3 calls block, so they are executed on a dedicated [BlockingPool]
+//! instead of the threads of an async runtime. Size of the pool caps the
+//! number of concurrent calls into libhdfs3, calls submitted while all
+//! threads are busy wait in a queue.
+//!
+//! [AsyncHdfsFile] implements tokio's ```AsyncRead```, ```AsyncWrite``` and
+//! ```AsyncSeek```. Like ```tokio::fs::File```, a write returns as soon as
+//! data is handed to the pool, its failure is reported by the next operation;
+//! ```flush``` and ```shutdown``` wait for all writes to finish.
+//!
+//! ```ignore
+//! let fs = AsyncHdfsFs::new(HdfsRegistry::new().get("hdfs://localhost:9000")?, 8);
+//! let mut file = fs.create("/data/file.txt").await?;
+//! file.write_all(b"data").await?;
+//! file.shutdown().await?;
+//! ```
+
+use crate::dfs::{BlockLocation, FileStatus, HdfsFile, HdfsFs};
+use crate::permission::FsPermission;
+use crate::status::FsStatus;
+use crate::summary::ContentSummary;
+use futures::channel::oneshot;
+use futures::stream::{self, BoxStream, StreamExt, TryStreamExt};
+use log::debug;
+use std::future::Future;
+use std::io::{Error, ErrorKind, SeekFrom};
+use std::panic::AssertUnwindSafe;
+use std::pin::Pin;
+use std::sync::{mpsc, Arc, Mutex};
+use std::task::{ready, Context, Poll};
+use std::time::SystemTime;
+use tokio::io::{AsyncRead, AsyncSeek, AsyncWrite, ReadBuf};
+
+/// Default number of threads of [BlockingPool]
+pub const DEFAULT_POOL_SIZE: usize = 16;
+
+/// Largest read submitted to the pool at once
+const MAX_READ: usize = 2 * 1024 * 1024;
+
+type Job = Box<dyn FnOnce() + Send>;
+
+/// Fixed size pool of threads executing blocking calls
+pub struct BlockingPool {
+    sender: Mutex<mpsc::Sender<Job>>,
+    size: usize,
+}
+
+impl BlockingPool {
+    /// Start pool with ```size``` threads, at least one.
+    /// Threads exit once the pool is dropped and queued calls are done.
+    pub fn new(size: usize) -> Result<BlockingPool, Error> {
+        let size = size.max(1);
+        let (sender, receiver) = mpsc::channel::<Job>();
+        let receiver = Arc::new(Mutex::new(receiver));
+        for idx in 0..size {
+            let receiver = receiver.clone();
+            std::thread::Builder::new()
+                .name(format!("hdfs-blocking-{}", idx))
+                .spawn(move || loop {
+                    // lock is released before the job runs
+                    let job = receiver.lock().unwrap().recv();
+                    match job {
+                        // a panic is reported to the caller as canceled call
+                        Ok(job) => {
+                            let _ = std::panic::catch_unwind(AssertUnwindSafe(job));
+                        }
+                        Err(_) => break,
+                    }
+                })?;
+        }
+        debug!("started blocking pool with [{}] threads", size);
+
+        Ok(BlockingPool {
+            sender: Mutex::new(sender),
+            size,
+        })
+    }
+
+    /// Number of threads
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Execute a blocking call on the pool
+    pub fn spawn<F, T>(&self, f: F) -> impl Future<Output = Result<T, Error>> + Send + 'static
+    where
+        F: FnOnce() -> Result<T, Error> + Send + 'static,
+        T: Send + 'static,
+    {
+        let (reply, result) = oneshot::channel();
+        let job: Job = Box::new(move || {
+            let _ = reply.send(f());
+        });
+        let sent = self.sender.lock().unwrap().send(job);
+
+        async move {
+            if sent.is_err() {
+                return Err(Error::other("blocking pool has been shut down"));
+            }
+            match result.await {
+                Ok(result) => result,
+                Err(_) => Err(Error::other("blocking call panicked")),
+            }
+        }
+    }
+}
+
+/// Async API over [HdfsFs], see [module documentation](self)
+#[derive(Clone)]
+pub struct AsyncHdfsFs {
+    fs: Arc<HdfsFs>,
+    pool: Arc<BlockingPool>,
+}
+
+impl AsyncHdfsFs {
+    /// Filesystem using its own pool of ```pool_size``` threads
+    pub fn new(fs: Arc<HdfsFs>, pool_size: usize) -> Result<AsyncHdfsFs, Error> {
+        Ok(Self::with_pool(fs, Arc::new(BlockingPool::new(pool_size)?)))
+    }
+
+    /// Filesystem sharing a pool, e.g. to cap calls to several filesystems
+    pub fn with_pool(fs: Arc<HdfsFs>, pool: Arc<BlockingPool>) -> AsyncHdfsFs {
+        AsyncHdfsFs { fs, pool }
+    }
+
+    /// Blocking filesystem
+    pub fn fs(&self) -> &Arc<HdfsFs> {
+        &self.fs
+    }
+
+    /// Pool executing blocking calls of the filesystem
+    pub fn pool(&self) -> &Arc<BlockingPool> {
+        &self.pool
+    }
+
+    pub fn url(&self) -> &str {
+        self.fs.url()
+    }
+
+    /// Execute any blocking operation of the filesystem on the pool
+    pub async fn run<F, T>(&self, f: F) -> Result<T, Error>
+    where
+        F: FnOnce(&HdfsFs) -> Result<T, Error> + Send + 'static,
+        T: Send + 'static,
+    {
+        let fs = self.fs.clone();
+        self.pool.spawn(move || f(&fs)).await
+    }
+
+    pub async fn exist(&self, path: &str) -> Result<bool, Error> {
+        let path = path.to_string();
+        self.run(move |fs| Ok(fs.exist(&path))).await
+    }
+
+    pub async fn get_file_status(&self, path: &str) -> Result<FileStatus, Error> {
+        let path = path.to_string();
+        self.run(move |fs| fs.get_file_status(&path)).await
+    }
+
+    pub async fn list_status(&self, path: &str) -> Result<Vec<FileStatus>, Error> {
+        let path = path.to_string();
+        self.run(move |fs| fs.list_status(&path)).await
+    }
+
+    /// Stream of ```path``` entries, async counterpart of [HdfsFs::list_status_iter].
+    ///
+    /// Each page of the listing is fetched on the pool once the previous one
+    /// has been consumed, entries can be grouped with ```TryStreamExt::try_chunks```.
+    pub fn list_status_stream(&self, path: &str) -> BoxStream<'static, Result<FileStatus, Error>> {
+        let fs = self.fs.clone();
+        let pool = self.pool.clone();
+        let path = Arc::new(path.to_string());
+
+        // state is the name to continue after, None once the last page is fetched
+        stream::try_unfold(Some(None), move |start_after: Option<Option<String>>| {
+            let (fs, pool, path) = (fs.clone(), pool.clone(), path.clone());
+            async move {
+                let start_after = match start_after {
+                    Some(start_after) => start_after,
+                    None => return Ok::<_, Error>(None),
+                };
+                let (entries, next) = pool
+                    .spawn(move || fs.list_status_batch(&path, start_after.as_deref()))
+                    .await?;
+                Ok(Some((
+                    stream::iter(entries.into_iter().map(Ok)),
+                    next.map(Some),
+                )))
+            }
+        })
+        .try_flatten()
+        .boxed()
+    }
+
+    pub async fn glob(&self, pattern: &str) -> Result<Vec<FileStatus>, Error> {
+        let pattern = pattern.to_string();
+        self.run(move |fs| fs.glob(&pattern)).await
+    }
+
+    pub async fn mkdir(&self, path: &str) -> Result<bool, Error> {
+        let path = path.to_string();
+        self.run(move |fs| fs.mkdir(&path)).await
+    }
+
+    pub async fn delete(&self, path: &str, recursive: bool) -> Result<bool, Error> {
+        let path = path.to_string();
+        self.run(move |fs| fs.delete(&path, recursive)).await
+    }
+
+    /// See [HdfsFs::delete_with_trash]
+    pub async fn delete_with_trash(
+        &self,
+        path: &str,
+        recursive: bool,
+        skip_trash: bool,
+    ) -> Result<bool, Error> {
+        let path = path.to_string();
+        self.run(move |fs| fs.delete_with_trash(&path, recursive, skip_trash))
+            .await
+    }
+
+    pub async fn rename(&self, old_path: &str, new_path: &str) -> Result<bool, Error> {
+        let (old_path, new_path) = (old_path.to_string(), new_path.to_string());
+        self.run(move |fs| fs.rename(&old_path, &new_path)).await
+    }
+
+    pub async fn chmod(&self, path: &str, mode: i16) -> Result<bool, Error> {
+        let path = path.to_string();
+        self.run(move |fs| fs.try_chmod(&path, mode).map(|_| true))
+            .await
+    }
+
+    pub async fn set_permission(
+        &self,
+        path: &str,
+        permission: FsPermission,
+    ) -> Result<bool, Error> {
+        let path = path.to_string();
+        self.run(move |fs| fs.try_set_permission(&path, permission).map(|_| true))
+            .await
+    }
+
+    pub async fn chown(&self, path: &str, owner: &str, group: &str) -> Result<bool, Error> {
+        let (path, owner, group) = (path.to_string(), owner.to_string(), group.to_string());
+        self.run(move |fs| fs.try_chown(&path, &owner, &group).map(|_| true))
+            .await
+    }
+
+    pub async fn set_replication(&self, path: &str, num: i16) -> Result<bool, Error> {
+        let path = path.to_string();
+        self.run(move |fs| fs.set_replication(&path, num)).await
+    }
+
+    /// See [HdfsFs::set_times]
+    pub async fn set_times(
+        &self,
+        path: &str,
+        modified: Option<SystemTime>,
+        accessed: Option<SystemTime>,
+    ) -> Result<bool, Error> {
+        let path = path.to_string();
+        self.run(move |fs| fs.set_times(&path, modified, accessed))
+            .await
+    }
+
+    pub async fn get_block_locations(
+        &self,
+        path: &str,
+        start: u64,
+        len: u64,
+    ) -> Result<Vec<BlockLocation>, Error> {
+        let path = path.to_string();
+        self.run(move |fs| fs.get_block_locations(&path, start, len))
+            .await
+    }
+
+    pub async fn content_summary(&self, path: &str) -> Result<ContentSummary, Error> {
+        let path = path.to_string();
+        self.run(move |fs| fs.content_summary(&path)).await
+    }
+
+    pub async fn du(&self, path: &str) -> Result<Vec<(String, ContentSummary)>, Error> {
+        let path = path.to_string();
+        self.run(move |fs| fs.du(&path)).await
+    }
+
+    pub async fn status(&self) -> Result<FsStatus, Error> {
+        self.run(|fs| fs.status()).await
+    }
+
+    pub async fn working_directory(&self) -> Result<String, Error> {
+        self.run(|fs| fs.working_directory()).await
+    }
+
+    pub async fn default_block_size(&self) -> Result<usize, Error> {
+        self.run(|fs| fs.default_block_size()).await
+    }
+
+    pub async fn capacity(&self) -> Result<usize, Error> {
+        self.run(|fs| fs.capacity()).await
+    }
+
+    pub async fn used(&self) -> Result<usize, Error> {
+        self.run(|fs| fs.used()).await
+    }
+
+    /// Open a file for reading
+    pub async fn open(&self, path: &str) -> Result<AsyncHdfsFile, Error> {
+        let path = path.to_string();
+        self.open_with(move |fs| fs.open(&path)).await
+    }
+
+    /// Create a file, fails if it exists
+    pub async fn create(&self, path: &str) -> Result<AsyncHdfsFile, Error> {
+        let path = path.to_string();
+        self.open_with(move |fs| fs.create(&path)).await
+    }
+
+    pub async fn create_with_overwrite(
+        &self,
+        path: &str,
+        overwrite: bool,
+    ) -> Result<AsyncHdfsFile, Error> {
+        let path = path.to_string();
+        self.open_with(move |fs| fs.create_with_overwrite(&path, overwrite))
+            .await
+    }
+
+    /// Open a file for appending
+    pub async fn append(&self, path: &str) -> Result<AsyncHdfsFile, Error> {
+        let path = path.to_string();
+        self.open_with(move |fs| fs.append(&path)).await
+    }
+
+    async fn open_with<F>(&self, open: F) -> Result<AsyncHdfsFile, Error>
+    where
+        F: for<'a> FnOnce(&'a HdfsFs) -> Result<HdfsFile<'a>, Error> + Send + 'static,
+    {
+        let fs = self.fs.clone();
+        let (file, pos) = self
+            .pool
+            .spawn(move || {
+                let file = OwnedFile::open(fs, open)?;
+                // appended files start at their end
+                let pos = file.file.pos()?;
+                Ok((file, pos))
+            })
+            .await?;
+        Ok(AsyncHdfsFile::new(self.pool.clone(), file, pos))
+    }
+}
+
+/// Open file which keeps its filesystem alive
+pub(crate) struct OwnedFile {
+    // declared before fs so it is closed before the filesystem is released
+    pub(crate) file: HdfsFile<'static>,
+    _fs: Arc<HdfsFs>,
+}
+
+// Files are not safe to be used concurrently, [AsyncHdfsFile] hands the
+// file to one pool thread at a time.
+unsafe impl Send for OwnedFile {}
+
+impl OwnedFile {
+    pub(crate) fn open<F>(fs: Arc<HdfsFs>, open: F) -> Result<OwnedFile, Error>
+    where
+        F: for<'a> FnOnce(&'a HdfsFs) -> Result<HdfsFile<'a>, Error>,
+    {
+        let file = open(&fs)?;
+        // filesystem lives behind Arc which is owned by the file and
+        // outlives it as fields are dropped in declaration order
+        let file = unsafe { std::mem::transmute::<HdfsFile<'_>, HdfsFile<'static>>(file) };
+        Ok(OwnedFile { file, _fs: fs })
+    }
+}
+
+/// Outcome of an operation executed on the pool
+enum Operation {
+    Read(Result<Vec<u8>, Error>),
+    Write(Result<(), Error>),
+    Seek(Result<u64, Error>),
+    Flush(Result<(), Error>),
+    Close(Result<(), Error>),
+}
+
+type Pending =
+    Pin<Box<dyn Future<Output = Result<(Option<Box<OwnedFile>>, Operation), Error>> + Send>>;
+
+enum State {
+    /// file is available, ```None``` once it has been closed
+    Idle(Option<Box<OwnedFile>>),
+    /// file has been handed to the pool
+    Busy(Pending),
+}
+
+/// Async file handle, created by [AsyncHdfsFs]
+pub struct AsyncHdfsFile {
+    pool: Arc<BlockingPool>,
+    state: State,
+    /// position of the underlying file
+    pos: u64,
+    /// data read ahead of the caller's buffer, ends at ```pos```
+    buf: Vec<u8>,
+    /// failure of an operation which has not been reported yet,
+    /// e.g. of a write which has been reported as successful
+    last_error: Option<Error>,
+}
+
+impl AsyncHdfsFile {
+    fn new(pool: Arc<BlockingPool>, file: OwnedFile, pos: u64) -> AsyncHdfsFile {
+        AsyncHdfsFile {
+            pool,
+            state: State::Idle(Some(Box::new(file))),
+            pos,
+            buf: Vec::new(),
+            last_error: None,
+        }
+    }
+
+    /// Hand the file to the pool
+    fn submit<F>(&mut self, mut file: Box<OwnedFile>, op: F)
+    where
+        F: FnOnce(&mut OwnedFile) -> Operation + Send + 'static,
+    {
+        let pending = self.pool.spawn(move || {
+            let result = op(&mut file);
+            Ok((Some(file), result))
+        });
+        self.state = State::Busy(Box::pin(pending));
+    }
+
+    /// Wait for the operation in progress, ```None``` if there is none
+    fn poll_busy(&mut self, cx: &mut Context<'_>) -> Poll<Option<Operation>> {
+        let pending = match &mut self.state {
+            State::Busy(pending) => pending,
+            State::Idle(_) => return Poll::Ready(None),
+        };
+        let (file, op) = match ready!(pending.as_mut().poll(cx)) {
+            Ok(done) => done,
+            // file is lost together with the failed call
+            Err(e) => (None, Operation::Close(Err(e))),
+        };
+        self.state = State::Idle(file);
+        if let Operation::Read(Ok(data)) = &op {
+            self.pos += data.len() as u64;
+        }
+        Poll::Ready(Some(op))
+    }
+
+    /// Record outcome of an operation the caller is not waiting for
+    fn complete(&mut self, op: Operation) {
+        match op {
+            Operation::Read(Ok(data)) => self.buf = data,
+            Operation::Seek(Ok(pos)) => self.pos = pos,
+            Operation::Write(Ok(())) | Operation::Flush(Ok(())) | Operation::Close(Ok(())) => (),
+            Operation::Read(Err(e))
+            | Operation::Write(Err(e))
+            | Operation::Seek(Err(e))
+            | Operation::Flush(Err(e))
+            | Operation::Close(Err(e)) => {
+                self.last_error.get_or_insert(e);
+            }
+        }
+    }
+
+    /// Idle file, fails with a pending error or if the file has been closed
+    fn take_idle(&mut self) -> Result<Box<OwnedFile>, Error> {
+        let file = match &mut self.state {
+            State::Idle(file) => file.take(),
+            State::Busy(_) => None,
+        };
+        let file = match (file, self.last_error.take()) {
+            (Some(file), None) => return Ok(file),
+            (file, Some(e)) => {
+                self.state = State::Idle(file);
+                return Err(e);
+            }
+            (None, None) => None,
+        };
+        self.state = State::Idle(file);
+        Err(Error::new(ErrorKind::BrokenPipe, "file has been closed"))
+    }
+}
+
+impl Drop for AsyncHdfsFile {
+    fn drop(&mut self) {
+        // closing blocks, a file in use is closed by the pool anyway
+        if let State::Idle(file) = &mut self.state {
+            if let Some(file) = file.take() {
+                // call is queued even if its result is not awaited
+                drop(self.pool.spawn(move || {
+                    drop(file);
+                    Ok(())
+                }));
+            }
+        }
+    }
+}
+
+impl AsyncRead for AsyncHdfsFile {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        dst: &mut ReadBuf<'_>,
+    ) -> Poll<std::io::Result<()>> {
+        let this = self.get_mut();
+        loop {
+            if !this.buf.is_empty() {
+                let len = this.buf.len().min(dst.remaining());
+                dst.put_slice(&this.buf[..len]);
+                this.buf.drain(..len);
+                return Poll::Ready(Ok(()));
+            }
+
+            match ready!(this.poll_busy(cx)) {
+                // end of file
+                Some(Operation::Read(Ok(data))) if data.is_empty() => return Poll::Ready(Ok(())),
+                Some(Operation::Read(Err(e))) => return Poll::Ready(Err(e)),
+                Some(op) => {
+                    this.complete(op);
+                    continue;
+                }
+                None => (),
+            }
+
+            let file = this.take_idle()?;
+            if dst.remaining() == 0 {
+                this.state = State::Idle(Some(file));
+                return Poll::Ready(Ok(()));
+            }
+            let len = dst.remaining().min(MAX_READ);
+            this.submit(file, move |file| {
+                let mut data = vec![0; len];
+                Operation::Read(file.file.read(&mut data).map(|read| {
+                    data.truncate(read);
+                    data
+                }))
+            });
+        }
+    }
+}
+
+impl AsyncWrite for AsyncHdfsFile {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        src: &[u8],
+    ) -> Poll<std::io::Result<usize>> {
+        let this = self.get_mut();
+        if let Some(op) = ready!(this.poll_busy(cx)) {
+            this.complete(op);
+        }
+
+        let file = this.take_idle()?;
+        let data = src.to_vec();
+        this.pos += data.len() as u64;
+        this.submit(file, move |file| {
+            Operation::Write(std::io::Write::write_all(&mut file.file, &data))
+        });
+        Poll::Ready(Ok(src.len()))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        let this = self.get_mut();
+        loop {
+            match ready!(this.poll_busy(cx)) {
+                Some(Operation::Flush(result)) => return Poll::Ready(result),
+                Some(op) => {
+                    this.complete(op);
+                    continue;
+                }
+                None => (),
+            }
+
+            let file = this.take_idle()?;
+            this.submit(file, |file| {
+                Operation::Flush(if file.file.is_writable() {
+                    file.file.try_flush()
+                } else {
+                    Ok(())
+                })
+            });
+        }
+    }
+
+    /// Close the file, a closed file can not be used any more
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        let this = self.get_mut();
+        loop {
+            match ready!(this.poll_busy(cx)) {
+                Some(Operation::Close(result)) => return Poll::Ready(result),
+                Some(op) => {
+                    this.complete(op);
+                    continue;
+                }
+                None => (),
+            }
+
+            let file = match this.take_idle() {
+                Ok(file) => file,
+                // already closed
+                Err(e) if e.kind() == ErrorKind::BrokenPipe && this.last_error.is_none() => {
+                    return Poll::Ready(Ok(()))
+                }
+                Err(e) => return Poll::Ready(Err(e)),
+            };
+            let pending = this.pool.spawn(move || {
+                let OwnedFile { file, _fs } = *file;
+                Ok((None, Operation::Close(file.close())))
+            });
+            this.state = State::Busy(Box::pin(pending));
+        }
+    }
+}
+
+impl AsyncSeek for AsyncHdfsFile {
+    fn start_seek(self: Pin<&mut Self>, position: SeekFrom) -> std::io::Result<()> {
+        let this = self.get_mut();
+        if let State::Busy(_) = this.state {
+            return Err(Error::other(
+                "other file operation is pending, call poll_complete before start_seek",
+            ));
+        }
+        let file = this.take_idle()?;
+
+        // data read ahead is dropped, current position is the one seen by the caller
+        let current = this.pos - this.buf.len() as u64;
+        this.buf.clear();
+        this.submit(file, move |file| {
+            Operation::Seek((|| {
+                let target = match position {
+                    SeekFrom::Start(offset) => Some(offset),
+                    SeekFrom::Current(delta) => current.checked_add_signed(delta),
+                    SeekFrom::End(delta) => {
+                        let len = file.file.get_file_status()?.len() as u64;
+                        len.checked_add_signed(delta)
+                    }
+                }
+                .ok_or_else(|| {
+                    Error::new(ErrorKind::InvalidInput, "seek to a negative position")
+                })?;
+
+                file.file.try_seek(target).map(|_| target)
+            })())
+        });
+        Ok(())
+    }
+
+    fn poll_complete(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<u64>> {
+        let this = self.get_mut();
+        loop {
+            match ready!(this.poll_busy(cx)) {
+                Some(Operation::Seek(result)) => {
+                    if let Ok(pos) = result {
+                        this.pos = pos;
+                    }
+                    return Poll::Ready(result);
+                }
+                Some(op) => this.complete(op),
+                None => return Poll::Ready(Ok(this.pos - this.buf.len() as u64)),
+            }
+        }
+    }
+}
@@ -0,0 +1,177 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Throughput benchmark similar to Hadoop's ```TestDFSIO```
+//!
+//! ```text
+//! hdfs-native-bench -fs hdfs://namenode:port -write -read -nrFiles 16 -fileSize 128MB -threads 8
+//! ```
+//!
+//! Workloads run in the order given, each one over the same set of files
+//! under ```-dir```; ```-read``` and ```-pread``` expect files written by
+//! an earlier ```-write```. Throughput, latency percentiles of single
+//! operations and errors are reported per workload, as text or ```-json```.
+
+mod report;
+mod workload;
+
+use crate::report::Report;
+use crate::workload::{Workload, WorkloadOptions};
+use hdfs_native::{HdfsRegistry, HdfsUtil};
+use std::io::Error;
+
+const PROGRAM: &str = "hdfs-native-bench";
+
+/// Exit code of invalid usage, same as ```hdfs-native```
+const EXIT_USAGE: i32 = -1;
+
+/// Client configuration key of the default filesystem
+const DEFAULT_URI_KEY: &str = "dfs.default.uri";
+
+const USAGE: &str = "Usage: hdfs-native-bench [-fs <url>] -write | -read | -pread | -list ...
+    [-nrFiles N] [-fileSize Size[B|KB|MB|GB|TB]] [-bufferSize Size[B|KB|MB|GB|TB]]
+    [-threads N] [-replication N] [-dir <path>] [-json] [-clean]";
+
+struct Options {
+    fs: Option<String>,
+    workloads: Vec<Workload>,
+    workload: WorkloadOptions,
+    json: bool,
+    clean: bool,
+}
+
+/// Parses size such as ```128MB```, plain numbers are bytes
+fn parse_size(value: &str) -> Result<u64, String> {
+    let upper = value.trim().to_ascii_uppercase();
+    let digits = upper.trim_end_matches(|c: char| c.is_ascii_alphabetic());
+    let multiplier: u64 = match &upper[digits.len()..] {
+        "" | "B" => 1,
+        "K" | "KB" => 1 << 10,
+        "M" | "MB" => 1 << 20,
+        "G" | "GB" => 1 << 30,
+        "T" | "TB" => 1 << 40,
+        _ => return Err(format!("invalid size {}", value)),
+    };
+    digits
+        .parse::<u64>()
+        .ok()
+        .and_then(|n| n.checked_mul(multiplier))
+        .ok_or_else(|| format!("invalid size {}", value))
+}
+
+fn parse_number<T: std::str::FromStr>(option: &str, value: &str) -> Result<T, String> {
+    value
+        .parse::<T>()
+        .map_err(|_| format!("invalid value of {}: {}", option, value))
+}
+
+fn parse_args(args: &[String]) -> Result<Options, String> {
+    let mut opts = Options {
+        fs: None,
+        workloads: Vec::new(),
+        workload: WorkloadOptions::default(),
+        json: false,
+        clean: false,
+    };
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .map(|v| v.as_str())
+                .ok_or_else(|| format!("{} requires a value", arg))
+        };
+        match arg.as_str() {
+            "-fs" => opts.fs = Some(value()?.to_string()),
+            "-write" => opts.workloads.push(Workload::Write),
+            "-read" => opts.workloads.push(Workload::Read),
+            "-pread" => opts.workloads.push(Workload::PositionalRead),
+            "-list" => opts.workloads.push(Workload::List),
+            "-nrFiles" => opts.workload.files = parse_number(arg, value()?)?,
+            "-fileSize" => opts.workload.file_size = parse_size(value()?)?,
+            "-bufferSize" => opts.workload.buffer_size = parse_size(value()?)? as usize,
+            "-threads" => opts.workload.threads = parse_number(arg, value()?)?,
+            "-replication" => opts.workload.replication = Some(parse_number(arg, value()?)?),
+            "-dir" => opts.workload.dir = value()?.to_string(),
+            "-json" => opts.json = true,
+            "-clean" => opts.clean = true,
+            _ => return Err(format!("unknown option {}", arg)),
+        }
+    }
+
+    if opts.workloads.is_empty() && !opts.clean {
+        return Err("no workload given".to_string());
+    }
+    if opts.workload.files == 0 || opts.workload.threads == 0 || opts.workload.buffer_size == 0 {
+        return Err("-nrFiles, -threads and -bufferSize have to be positive".to_string());
+    }
+    Ok(opts)
+}
+
+fn run(opts: &Options) -> Result<Vec<Report>, Error> {
+    let url = opts
+        .fs
+        .clone()
+        .or_else(|| HdfsUtil::get_conf_str(DEFAULT_URI_KEY))
+        .ok_or_else(|| {
+            Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "default filesystem is not configured, use -fs <uri>",
+            )
+        })?;
+    let fs = HdfsRegistry::new().get(&url)?;
+
+    let mut reports = Vec::new();
+    for workload in &opts.workloads {
+        reports.push(workload.run(&fs, &opts.workload)?);
+    }
+    if opts.clean && fs.exist(&opts.workload.dir) {
+        fs.delete(&opts.workload.dir, true)?;
+    }
+    Ok(reports)
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let opts = match parse_args(&args) {
+        Ok(opts) => opts,
+        Err(message) => {
+            eprintln!("{}: {}", PROGRAM, message);
+            eprintln!("{}", USAGE);
+            std::process::exit(EXIT_USAGE);
+        }
+    };
+
+    let reports = match run(&opts) {
+        Ok(reports) => reports,
+        Err(e) => {
+            eprintln!("{}: {}", PROGRAM, e);
+            std::process::exit(1);
+        }
+    };
+
+    if opts.json {
+        println!("{}", report::to_json(&reports));
+    } else {
+        for report in &reports {
+            print!("{}", report);
+        }
+    }
+
+    let failed = reports.iter().any(|r| r.errors > 0);
+    std::process::exit(if failed { 1 } else { 0 });
+}
@@ -0,0 +1,213 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Benchmark results
+
+use crate::workload::{Stats, WorkloadOptions};
+use std::fmt::{Display, Formatter};
+use std::time::Duration;
+
+const MEGABYTE: f64 = (1 << 20) as f64;
+
+/// Latency of single operations in milliseconds
+#[derive(Debug, Default)]
+pub struct Latency {
+    pub mean: f64,
+    pub p50: f64,
+    pub p90: f64,
+    pub p99: f64,
+    pub max: f64,
+}
+
+impl Latency {
+    fn new(mut latencies: Vec<Duration>) -> Latency {
+        if latencies.is_empty() {
+            return Latency::default();
+        }
+        latencies.sort();
+        let millis = |d: Duration| d.as_secs_f64() * 1000.0;
+        // nearest rank
+        let percentile = |p: f64| {
+            let rank = (p / 100.0 * latencies.len() as f64).ceil() as usize;
+            millis(latencies[rank.clamp(1, latencies.len()) - 1])
+        };
+        let total: Duration = latencies.iter().sum();
+
+        Latency {
+            mean: millis(total) / latencies.len() as f64,
+            p50: percentile(50.0),
+            p90: percentile(90.0),
+            p99: percentile(99.0),
+            max: millis(latencies[latencies.len() - 1]),
+        }
+    }
+}
+
+/// Result of a workload
+#[derive(Debug)]
+pub struct Report {
+    pub workload: &'static str,
+    pub files: usize,
+    pub file_size: u64,
+    pub buffer_size: usize,
+    pub threads: usize,
+    pub bytes: u64,
+    pub elapsed: Duration,
+    pub operations: usize,
+    pub latency: Latency,
+    pub errors: usize,
+    /// first few error messages
+    pub error_messages: Vec<String>,
+}
+
+impl Report {
+    pub fn new(
+        workload: &'static str,
+        opts: &WorkloadOptions,
+        elapsed: Duration,
+        stats: Stats,
+    ) -> Report {
+        Report {
+            workload,
+            files: opts.files,
+            file_size: opts.file_size,
+            buffer_size: opts.buffer_size,
+            threads: opts.threads,
+            bytes: stats.bytes,
+            elapsed,
+            operations: stats.latencies.len(),
+            latency: Latency::new(stats.latencies),
+            errors: stats.errors,
+            error_messages: stats.messages,
+        }
+    }
+
+    /// Throughput in MB (2^20 bytes) per second
+    pub fn throughput(&self) -> f64 {
+        per_second(self.bytes as f64 / MEGABYTE, self.elapsed)
+    }
+
+    pub fn operations_per_second(&self) -> f64 {
+        per_second(self.operations as f64, self.elapsed)
+    }
+}
+
+fn per_second(value: f64, elapsed: Duration) -> f64 {
+    let secs = elapsed.as_secs_f64();
+    if secs > 0.0 {
+        value / secs
+    } else {
+        0.0
+    }
+}
+
+impl Display for Report {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "----- hdfs-native-bench ----- : {}", self.workload)?;
+        writeln!(f, "           Number of files: {}", self.files)?;
+        writeln!(f, "                 File size: {} bytes", self.file_size)?;
+        writeln!(f, "               Buffer size: {} bytes", self.buffer_size)?;
+        writeln!(f, "                   Threads: {}", self.threads)?;
+        writeln!(
+            f,
+            "    Total MBytes processed: {:.2}",
+            self.bytes as f64 / MEGABYTE
+        )?;
+        writeln!(
+            f,
+            "            Test exec time: {:.3} s",
+            self.elapsed.as_secs_f64()
+        )?;
+        writeln!(f, "         Throughput MB/sec: {:.2}", self.throughput())?;
+        writeln!(
+            f,
+            "                Operations: {} ({:.2} ops/sec)",
+            self.operations,
+            self.operations_per_second()
+        )?;
+        writeln!(
+            f,
+            "   Latency mean/p50/p90/p99/max: {:.3}/{:.3}/{:.3}/{:.3}/{:.3} ms",
+            self.latency.mean,
+            self.latency.p50,
+            self.latency.p90,
+            self.latency.p99,
+            self.latency.max
+        )?;
+        writeln!(f, "                    Errors: {}", self.errors)?;
+        for message in &self.error_messages {
+            writeln!(f, "        {}", message)?;
+        }
+        writeln!(f)
+    }
+}
+
+/// Reports as JSON array, written by hand as the crate does not depend
+/// on a JSON library
+pub fn to_json(reports: &[Report]) -> String {
+    let reports: Vec<String> = reports
+        .iter()
+        .map(|r| {
+            let messages: Vec<String> = r.error_messages.iter().map(|m| json_string(m)).collect();
+            format!(
+                concat!(
+                    "{{\"workload\":{},\"files\":{},\"file_size\":{},\"buffer_size\":{},",
+                    "\"threads\":{},\"bytes\":{},\"elapsed_secs\":{:.6},\"throughput_mb_per_sec\":{:.3},",
+                    "\"operations\":{},\"operations_per_sec\":{:.3},",
+                    "\"latency_ms\":{{\"mean\":{:.3},\"p50\":{:.3},\"p90\":{:.3},\"p99\":{:.3},\"max\":{:.3}}},",
+                    "\"errors\":{},\"error_messages\":[{}]}}"
+                ),
+                json_string(r.workload),
+                r.files,
+                r.file_size,
+                r.buffer_size,
+                r.threads,
+                r.bytes,
+                r.elapsed.as_secs_f64(),
+                r.throughput(),
+                r.operations,
+                r.operations_per_second(),
+                r.latency.mean,
+                r.latency.p50,
+                r.latency.p90,
+                r.latency.p99,
+                r.latency.max,
+                r.errors,
+                messages.join(",")
+            )
+        })
+        .collect();
+    format!("[{}]", reports.join(","))
+}
+
+fn json_string(value: &str) -> String {
+    let mut out = String::with_capacity(value.len() + 2);
+    out.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
@@ -0,0 +1,256 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Benchmark workloads
+
+use crate::report::Report;
+use hdfs_native::HdfsFs;
+use std::io::{Error, Write};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// Directory under benchmark directory holding data files
+const DATA_DIR: &str = "io_data";
+
+/// Number of error messages kept per thread, all errors are counted
+const MAX_ERROR_MESSAGES: usize = 10;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Workload {
+    /// Sequential write of all files
+    Write,
+    /// Sequential read of all files
+    Read,
+    /// Reads of ```buffer_size``` at random offsets, as many as
+    /// sequential read would do
+    PositionalRead,
+    /// Listing of the data directory, once per file
+    List,
+}
+
+pub struct WorkloadOptions {
+    pub files: usize,
+    pub file_size: u64,
+    pub buffer_size: usize,
+    pub threads: usize,
+    /// replication of written files, filesystem default if not set
+    pub replication: Option<i16>,
+    /// benchmark directory
+    pub dir: String,
+}
+
+impl Default for WorkloadOptions {
+    fn default() -> Self {
+        Self {
+            files: 4,
+            file_size: 8 << 20,
+            buffer_size: 1 << 20,
+            threads: 4,
+            replication: None,
+            dir: "/benchmarks/hdfs-native-bench".to_string(),
+        }
+    }
+}
+
+impl WorkloadOptions {
+    fn data_dir(&self) -> String {
+        format!("{}/{}", self.dir.trim_end_matches('/'), DATA_DIR)
+    }
+
+    fn file(&self, idx: usize) -> String {
+        format!("{}/file_{}", self.data_dir(), idx)
+    }
+}
+
+/// Results of a single thread
+#[derive(Default)]
+pub struct Stats {
+    pub bytes: u64,
+    pub latencies: Vec<Duration>,
+    pub errors: usize,
+    pub messages: Vec<String>,
+}
+
+impl Stats {
+    fn error(&mut self, path: &str, e: Error) {
+        self.errors += 1;
+        if self.messages.len() < MAX_ERROR_MESSAGES {
+            self.messages.push(format!("{}: {}", path, e));
+        }
+    }
+
+    fn merge(&mut self, other: Stats) {
+        self.bytes += other.bytes;
+        self.latencies.extend(other.latencies);
+        self.errors += other.errors;
+        let free = MAX_ERROR_MESSAGES.saturating_sub(self.messages.len());
+        self.messages.extend(other.messages.into_iter().take(free));
+    }
+
+    /// Time a single operation returning number of bytes transferred
+    fn time<F>(&mut self, op: F) -> Result<usize, Error>
+    where
+        F: FnOnce() -> Result<usize, Error>,
+    {
+        let start = Instant::now();
+        let bytes = op()?;
+        self.latencies.push(start.elapsed());
+        self.bytes += bytes as u64;
+        Ok(bytes)
+    }
+}
+
+impl Workload {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Workload::Write => "write",
+            Workload::Read => "read",
+            Workload::PositionalRead => "pread",
+            Workload::List => "list",
+        }
+    }
+
+    /// Run workload over all files, distributed between threads
+    pub fn run(&self, fs: &HdfsFs, opts: &WorkloadOptions) -> Result<Report, Error> {
+        if *self == Workload::Write {
+            fs.mkdir(&opts.data_dir())?;
+        }
+
+        let next = AtomicUsize::new(0);
+        let start = Instant::now();
+        let stats = std::thread::scope(|scope| {
+            let handles: Vec<_> = (0..opts.threads)
+                .map(|_| {
+                    scope.spawn(|| {
+                        let mut stats = Stats::default();
+                        let mut buf = vec![0u8; opts.buffer_size];
+                        loop {
+                            let idx = next.fetch_add(1, Ordering::Relaxed);
+                            if idx >= opts.files {
+                                break;
+                            }
+                            let path = opts.file(idx);
+                            let result = match self {
+                                Workload::Write => write(fs, &path, opts, &mut buf, &mut stats),
+                                Workload::Read => read(fs, &path, &mut buf, &mut stats),
+                                Workload::PositionalRead => {
+                                    positional_read(fs, &path, idx, &mut buf, &mut stats)
+                                }
+                                Workload::List => list(fs, opts, &mut stats),
+                            };
+                            if let Err(e) = result {
+                                stats.error(&path, e);
+                            }
+                        }
+                        stats
+                    })
+                })
+                .collect();
+
+            let mut stats = Stats::default();
+            for handle in handles {
+                match handle.join() {
+                    Ok(thread_stats) => stats.merge(thread_stats),
+                    Err(_) => stats.error(self.name(), Error::other("benchmark thread panicked")),
+                }
+            }
+            stats
+        });
+
+        Ok(Report::new(self.name(), opts, start.elapsed(), stats))
+    }
+}
+
+fn write(
+    fs: &HdfsFs,
+    path: &str,
+    opts: &WorkloadOptions,
+    buf: &mut [u8],
+    stats: &mut Stats,
+) -> Result<(), Error> {
+    // content does not matter, but should not be all zeros
+    for (idx, b) in buf.iter_mut().enumerate() {
+        *b = idx as u8;
+    }
+
+    let mut file = fs.create_with_overwrite(path, true)?;
+    if let Some(replication) = opts.replication {
+        fs.set_replication(path, replication)?;
+    }
+
+    let mut remaining = opts.file_size;
+    while remaining > 0 {
+        let len = remaining.min(buf.len() as u64) as usize;
+        stats.time(|| file.write_all(&buf[..len]).map(|_| len))?;
+        remaining -= len as u64;
+    }
+    // data is not durable until the file is closed
+    stats.time(|| file.close().map(|_| 0))?;
+    Ok(())
+}
+
+fn read(fs: &HdfsFs, path: &str, buf: &mut [u8], stats: &mut Stats) -> Result<(), Error> {
+    let file = fs.open(path)?;
+    while stats.time(|| file.read(buf))? > 0 {}
+    Ok(())
+}
+
+fn positional_read(
+    fs: &HdfsFs,
+    path: &str,
+    seed: usize,
+    buf: &mut [u8],
+    stats: &mut Stats,
+) -> Result<(), Error> {
+    let len = fs.get_file_status(path)?.len() as u64;
+    let file = fs.open(path)?;
+    let reads = (len / buf.len() as u64).max(1);
+    let max_offset = len.saturating_sub(buf.len() as u64);
+
+    let mut random = Random::new(seed as u64);
+    for _ in 0..reads {
+        let offset = random.next() % (max_offset + 1);
+        let length = buf.len();
+        stats.time(|| file.read_with_pos_length(offset as i64, buf, length))?;
+    }
+    Ok(())
+}
+
+fn list(fs: &HdfsFs, opts: &WorkloadOptions, stats: &mut Stats) -> Result<(), Error> {
+    let dir = opts.data_dir();
+    stats.time(|| fs.list_status(&dir).map(|_| 0))?;
+    Ok(())
+}
+
+/// xorshift generator, offsets do not need to be of high quality
+struct Random(u64);
+
+impl Random {
+    fn new(seed: u64) -> Random {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.subsec_nanos() as u64);
+        Random((seed << 32) ^ nanos | 1)
+    }
+
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+}
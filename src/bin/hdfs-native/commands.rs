@@ -0,0 +1,1264 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Commands compatible with ```hadoop fs```
+//!
+//! Errors are reported as ```<cmd>: `<path>': <reason>``` on stderr and
+//! processing continues with the next path. Exit code is ```0``` on success,
+//! ```1``` if any path failed and ```-1``` on usage errors.
+
+use crate::context::{parent, Context, Target};
+use crate::format::{self, Align};
+use hdfs_native::permission::SymbolicMode;
+use hdfs_native::recursive::{RecursiveOptions, RecursiveReport};
+use hdfs_native::trash::Trash;
+use hdfs_native::util::{base_name, CopyOptions, ExistsPolicy, NoProgress, TransferOptions};
+use hdfs_native::{
+    FileKind, FileStatus, FollowEvent, FollowFrom, FollowOptions, FsPermission, HdfsUtil,
+};
+use std::io::{Error, ErrorKind, Read, Write};
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Exit code of failed commands
+pub const EXIT_FAILURE: i32 = 1;
+
+/// Exit code of unknown commands and invalid usage
+pub const EXIT_USAGE: i32 = -1;
+
+/// Bytes shown by ```head``` and ```tail```
+const KILOBYTE: u64 = 1024;
+
+/// Interval between checks of a followed file, same as ```hadoop fs -tail -f```
+const DEFAULT_FOLLOW_INTERVAL_MS: u64 = 5000;
+
+/// Invalid usage of a command, reported together with command usage
+pub struct UsageError(pub String);
+
+type CommandResult = Result<i32, UsageError>;
+
+type Run = fn(&Context, &Args, &mut dyn Write) -> CommandResult;
+
+pub struct Command {
+    pub name: &'static str,
+    pub usage: &'static str,
+    pub description: &'static str,
+    /// options ending with ```:``` take a value, e.g. ```s:```
+    options: &'static [&'static str],
+    /// unknown options are treated as arguments, e.g. ```chmod -w```
+    lenient: bool,
+    run: Run,
+}
+
+/// Parsed options and arguments of a command
+pub struct Args {
+    flags: Vec<String>,
+    values: Vec<(String, String)>,
+    pub args: Vec<String>,
+}
+
+impl Args {
+    pub fn has(&self, flag: &str) -> bool {
+        self.flags.iter().any(|f| f == flag)
+    }
+
+    /// Value of an option taking a value, last one wins
+    pub fn value(&self, flag: &str) -> Option<&str> {
+        self.values
+            .iter()
+            .rev()
+            .find(|(f, _)| f == flag)
+            .map(|(_, v)| v.as_str())
+    }
+
+    fn expect(&self, min: usize, max: usize) -> Result<(), UsageError> {
+        if self.args.len() < min {
+            Err(UsageError(format!(
+                "Not enough arguments: expected {} but got {}",
+                min,
+                self.args.len()
+            )))
+        } else if self.args.len() > max {
+            Err(UsageError(format!(
+                "Too many arguments: expected {} but got {}",
+                max,
+                self.args.len()
+            )))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Arguments or ```default``` if there are none
+    fn paths_or(&self, default: &str) -> Vec<String> {
+        if self.args.is_empty() {
+            vec![default.to_string()]
+        } else {
+            self.args.clone()
+        }
+    }
+}
+
+pub static COMMANDS: &[Command] = &[
+    Command {
+        name: "cat",
+        usage: "<src> ...",
+        description: "Fetch all files that match the file pattern <src> and display their content on stdout.",
+        options: &[],
+        lenient: false,
+        run: cat,
+    },
+    Command {
+        name: "chmod",
+        usage: "[-R] <MODE[,MODE]... | OCTALMODE> PATH...",
+        description: "Changes permissions of a file. -R modifies the files recursively.",
+        options: &["R"],
+        lenient: true,
+        run: chmod,
+    },
+    Command {
+        name: "chown",
+        usage: "[-R] [OWNER][:[GROUP]] PATH...",
+        description: "Changes owner and group of a file. -R modifies the files recursively.",
+        options: &["R"],
+        lenient: false,
+        run: chown,
+    },
+    Command {
+        name: "cp",
+        usage: "[-f] [-p] <src> ... <dst>",
+        description: "Copy files that match the file pattern <src> to a destination. -f overwrites the destination if it already exists, -p preserves permission and timestamps.",
+        options: &["f", "p", "d"],
+        lenient: false,
+        run: cp,
+    },
+    Command {
+        name: "df",
+        usage: "[-h] [<path> ...]",
+        description: "Shows the capacity, free and used space of the filesystem. -h formats sizes in a human-readable fashion.",
+        options: &["h"],
+        lenient: false,
+        run: df,
+    },
+    Command {
+        name: "du",
+        usage: "[-s] [-h] [-v] [-x] <path> ...",
+        description: "Show the amount of space, in bytes, used by the files that match the specified file pattern. -s shows a summary, -h formats sizes in a human-readable fashion, -v displays a header line.",
+        options: &["s", "h", "v", "x"],
+        lenient: false,
+        run: du,
+    },
+    Command {
+        name: "get",
+        usage: "[-f] [-p] [-ignoreCrc] [-crc] <src> ... <localdst>",
+        description: "Copy files that match the file pattern <src> to the local name. -f overwrites the destination if it already exists, -p preserves permission and timestamps.",
+        options: &["f", "p", "ignoreCrc", "crc"],
+        lenient: false,
+        run: get,
+    },
+    Command {
+        name: "head",
+        usage: "<file>",
+        description: "Show the first 1KB of the file.",
+        options: &[],
+        lenient: false,
+        run: head,
+    },
+    Command {
+        name: "help",
+        usage: "[cmd ...]",
+        description: "Displays help for given command or all commands if none is specified.",
+        options: &[],
+        lenient: false,
+        run: help,
+    },
+    Command {
+        name: "ls",
+        usage: "[-C] [-d] [-h] [-R] [-t] [-S] [-r] [-u] [<path> ...]",
+        description: "List the contents that match the specified file pattern. -C displays paths only, -d lists directories as plain files, -h formats sizes in a human-readable fashion, -R lists recursively, -t sorts by modification time, -S sorts by size, -r reverses the order, -u uses access time.",
+        options: &["C", "d", "h", "R", "t", "S", "r", "u"],
+        lenient: false,
+        run: ls,
+    },
+    Command {
+        name: "mkdir",
+        usage: "[-p] <path> ...",
+        description: "Create a directory in specified location. -p does not fail if the directory already exists and creates parent directories.",
+        options: &["p"],
+        lenient: false,
+        run: mkdir,
+    },
+    Command {
+        name: "mv",
+        usage: "<src> ... <dst>",
+        description: "Move files that match the specified file pattern <src> to a destination <dst>.",
+        options: &[],
+        lenient: false,
+        run: mv,
+    },
+    Command {
+        name: "put",
+        usage: "[-f] [-p] [-d] <localsrc> ... <dst>",
+        description: "Copy files from the local file system into fs. -f overwrites the destination if it already exists, -p preserves permission and timestamps. <localsrc> - reads from stdin.",
+        options: &["f", "p", "d", "l"],
+        lenient: false,
+        run: put,
+    },
+    Command {
+        name: "rm",
+        usage: "[-f] [-r|-R] [-skipTrash] [-safely] <src> ...",
+        description: "Delete all files that match the specified file pattern. -f does not report missing files, -r and -R delete directories recursively, -skipTrash bypasses trash.",
+        options: &["f", "r", "R", "skipTrash", "safely"],
+        lenient: false,
+        run: rm,
+    },
+    Command {
+        name: "setrep",
+        usage: "[-R] [-w] <rep> <path> ...",
+        description: "Set the replication level of a file. If <path> is a directory the replication level of all files under it is changed. -w waits for the replication to complete.",
+        options: &["R", "w"],
+        lenient: false,
+        run: setrep,
+    },
+    Command {
+        name: "stat",
+        usage: "[format] <path> ...",
+        description: "Print statistics about the file/directory at <path> in the specified format: %a octal permission, %A symbolic permission, %b length, %F type, %g group, %n name, %o block size, %r replication, %u owner, %x access date, %X access time in ms, %y modification date, %Y modification time in ms. Default format is %y.",
+        options: &[],
+        lenient: false,
+        run: stat,
+    },
+    Command {
+        name: "tail",
+        usage: "[-f] [-s <sleep interval>] <file>",
+        description: "Show the last 1KB of the file. -f shows appended data as the file grows, -s is the interval between checks in milliseconds (default 5000).",
+        options: &["f", "s:"],
+        lenient: false,
+        run: tail,
+    },
+    Command {
+        name: "test",
+        usage: "-[defsz] <path>",
+        description: "Answer various questions about <path>, with result via exit status. -d is a directory, -e exists, -f is a file, -s is not empty, -z is zero length.",
+        options: &["d", "e", "f", "s", "z"],
+        lenient: false,
+        run: test,
+    },
+    Command {
+        name: "touchz",
+        usage: "<path> ...",
+        description: "Creates a file of zero length at <path>. An error is returned if the file exists with non-zero length.",
+        options: &[],
+        lenient: false,
+        run: touchz,
+    },
+];
+
+fn find(name: &str) -> Option<&'static Command> {
+    COMMANDS.iter().find(|c| c.name == name)
+}
+
+fn parse(command: &Command, argv: &[String]) -> Result<Args, UsageError> {
+    let mut flags = Vec::new();
+    let mut values = Vec::new();
+    let mut idx = 0;
+    while idx < argv.len() {
+        let arg = &argv[idx];
+        if arg == "-" || !arg.starts_with('-') {
+            break;
+        }
+        if arg == "--" {
+            idx += 1;
+            break;
+        }
+        let flag = &arg[1..];
+        if command.options.contains(&flag) {
+            flags.push(flag.to_string());
+        } else if command
+            .options
+            .iter()
+            .any(|o| o.strip_suffix(':') == Some(flag))
+        {
+            idx += 1;
+            match argv.get(idx) {
+                Some(value) => values.push((flag.to_string(), value.clone())),
+                None => return Err(UsageError(format!("Missing value for option {}", arg))),
+            }
+        } else if command.lenient {
+            break;
+        } else {
+            return Err(UsageError(format!("Illegal option {}", arg)));
+        }
+        idx += 1;
+    }
+
+    Ok(Args {
+        flags,
+        values,
+        args: argv[idx..].to_vec(),
+    })
+}
+
+/// Print usage of all commands
+pub fn usage(out: &mut dyn Write, program: &str) {
+    let _ = writeln!(out, "Usage: {} [generic options]", program);
+    for command in COMMANDS {
+        let _ = writeln!(out, "\t[-{} {}]", command.name, command.usage);
+    }
+    let _ = writeln!(out);
+    let _ = writeln!(out, "Generic options supported are:");
+    let _ = writeln!(
+        out,
+        "-fs <file:///|hdfs://namenode:port>\tspecify default filesystem URL to use"
+    );
+    let _ = writeln!(
+        out,
+        "-D <property=value>\tdefine a value for a given property"
+    );
+}
+
+/// Run a command, ```argv[0]``` is name of the command with or
+/// without leading ```-```. Returns exit code.
+pub fn run(ctx: &Context, argv: &[String], out: &mut dyn Write) -> i32 {
+    let name = argv[0].strip_prefix('-').unwrap_or(&argv[0]);
+    let command = match find(name) {
+        Some(command) => command,
+        None => {
+            eprintln!("{}: Unknown command", argv[0]);
+            return EXIT_USAGE;
+        }
+    };
+
+    let result = parse(command, &argv[1..]).and_then(|args| (command.run)(ctx, &args, out));
+    let _ = out.flush();
+    match result {
+        Ok(code) => code,
+        Err(UsageError(message)) => {
+            eprintln!("-{}: {}", command.name, message);
+            eprintln!(
+                "Usage: hdfs-native [generic options] -{} {}",
+                command.name, command.usage
+            );
+            EXIT_USAGE
+        }
+    }
+}
+
+/// Reports failure of ```path``` on stderr
+pub fn report(cmd: &str, path: &str, e: &Error) {
+    let reason = match e.kind() {
+        ErrorKind::NotFound => "No such file or directory".to_string(),
+        ErrorKind::AlreadyExists => "File exists".to_string(),
+        ErrorKind::PermissionDenied => "Permission denied".to_string(),
+        // output closed by the reader, e.g. `hdfs-native -cat f | head`
+        ErrorKind::BrokenPipe => return,
+        _ => {
+            let message = e.to_string();
+            match message.find(" (os error") {
+                Some(idx) => message[..idx].to_string(),
+                None => message,
+            }
+        }
+    };
+    eprintln!("{}: `{}': {}", cmd, path, reason);
+}
+
+fn is_a_directory() -> Error {
+    Error::other("Is a directory")
+}
+
+fn is_not_a_directory() -> Error {
+    Error::other("Is not a directory")
+}
+
+/// Applies ```op``` to every target matched by ```args```, reporting failures
+fn for_each<F>(ctx: &Context, cmd: &str, args: &[String], mut op: F) -> i32
+where
+    F: FnMut(&Target) -> Result<(), Error>,
+{
+    let mut code = 0;
+    for arg in args {
+        let targets = match ctx.expand(arg) {
+            Ok(targets) => targets,
+            Err(e) => {
+                report(cmd, arg, &e);
+                code = EXIT_FAILURE;
+                continue;
+            }
+        };
+        for target in targets {
+            if let Err(e) = op(&target) {
+                report(cmd, &target.display, &e);
+                code = EXIT_FAILURE;
+            }
+        }
+    }
+    code
+}
+
+fn kind(status: &FileStatus) -> FileKind {
+    if status.is_directory() {
+        FileKind::Directory
+    } else {
+        FileKind::File
+    }
+}
+
+fn cat(ctx: &Context, args: &Args, out: &mut dyn Write) -> CommandResult {
+    args.expect(1, usize::MAX)?;
+    Ok(for_each(ctx, "cat", &args.args, |target| {
+        if target.status()?.is_directory() {
+            return Err(is_a_directory());
+        }
+        let mut file = target.fs.open(&target.path)?;
+        std::io::copy(&mut file, out)?;
+        Ok(())
+    }))
+}
+
+fn head(ctx: &Context, args: &Args, out: &mut dyn Write) -> CommandResult {
+    args.expect(1, 1)?;
+    Ok(for_each(ctx, "head", &args.args, |target| {
+        if target.status()?.is_directory() {
+            return Err(is_a_directory());
+        }
+        let file = target.fs.open(&target.path)?;
+        let mut file = file.take(KILOBYTE);
+        std::io::copy(&mut file, out)?;
+        Ok(())
+    }))
+}
+
+fn tail(ctx: &Context, args: &Args, out: &mut dyn Write) -> CommandResult {
+    args.expect(1, 1)?;
+    let interval = match args.value("s") {
+        Some(value) => value
+            .parse::<u64>()
+            .map_err(|_| UsageError(format!("Invalid sleep interval: {}", value)))?,
+        None => DEFAULT_FOLLOW_INTERVAL_MS,
+    };
+    if args.has("f") {
+        return Ok(for_each(ctx, "tail", &args.args, |target| {
+            follow(target, Duration::from_millis(interval), out)
+        }));
+    }
+
+    Ok(for_each(ctx, "tail", &args.args, |target| {
+        let status = target.status()?;
+        if status.is_directory() {
+            return Err(is_a_directory());
+        }
+        let len = status.len() as u64;
+        let offset = len.saturating_sub(KILOBYTE);
+        let file = target.fs.open(&target.path)?;
+        let mut buf = vec![0; (len - offset) as usize];
+        let mut read = 0;
+        while read < buf.len() {
+            let remaining = buf.len() - read;
+            let n = file.read_with_pos_length(
+                (offset as usize + read) as i64,
+                &mut buf[read..],
+                remaining,
+            )?;
+            if n == 0 {
+                break;
+            }
+            read += n;
+        }
+        out.write_all(&buf[..read])?;
+        Ok(())
+    }))
+}
+
+/// Print the last 1KB and then data appended to the file, until interrupted
+fn follow(target: &Target, interval: Duration, out: &mut dyn Write) -> Result<(), Error> {
+    let opts = FollowOptions {
+        from: FollowFrom::LastBytes(KILOBYTE),
+        poll_interval: interval,
+        ..Default::default()
+    };
+    for event in target.fs.follow(&target.path, &opts)? {
+        match event? {
+            FollowEvent::Data(data) => {
+                out.write_all(&data)?;
+                out.flush()?;
+            }
+            FollowEvent::Truncated => {
+                eprintln!("tail: {}: file truncated", target.display)
+            }
+            FollowEvent::Deleted => {
+                eprintln!(
+                    "tail: `{}': No such file or directory, waiting for it to appear",
+                    target.display
+                )
+            }
+            FollowEvent::Recreated => {
+                eprintln!(
+                    "tail: `{}' has appeared, following new file",
+                    target.display
+                )
+            }
+        }
+    }
+    Ok(())
+}
+
+struct LsOptions {
+    paths_only: bool,
+    human: bool,
+    recursive: bool,
+    by_time: bool,
+    by_size: bool,
+    reverse: bool,
+    access_time: bool,
+}
+
+fn ls(ctx: &Context, args: &Args, out: &mut dyn Write) -> CommandResult {
+    let opts = LsOptions {
+        paths_only: args.has("C"),
+        human: args.has("h"),
+        recursive: args.has("R"),
+        by_time: args.has("t"),
+        by_size: args.has("S"),
+        reverse: args.has("r"),
+        access_time: args.has("u"),
+    };
+    let dirs_as_files = args.has("d");
+
+    Ok(for_each(ctx, "ls", &args.paths_or("."), |target| {
+        let status = target.status()?;
+        if status.is_directory() && !dirs_as_files {
+            ls_directory(target, &opts, out, true)
+        } else {
+            ls_entries(&[(target.clone(), status)], &opts, out)
+        }
+    }))
+}
+
+fn ls_directory(
+    target: &Target,
+    opts: &LsOptions,
+    out: &mut dyn Write,
+    top: bool,
+) -> Result<(), Error> {
+    let mut items: Vec<(Target, FileStatus)> = target
+        .fs
+        .list_status(&target.path)?
+        .into_iter()
+        .map(|status| (target.child(base_name(status.name())), status))
+        .collect();
+
+    let time = |s: &FileStatus| {
+        if opts.access_time {
+            s.last_accced()
+        } else {
+            s.last_modified()
+        }
+    };
+    items.sort_by(|(a, a_status), (b, b_status)| {
+        if opts.by_time {
+            time(b_status)
+                .cmp(&time(a_status))
+                .then(a.path.cmp(&b.path))
+        } else if opts.by_size {
+            b_status
+                .len()
+                .cmp(&a_status.len())
+                .then(a.path.cmp(&b.path))
+        } else {
+            a.path.cmp(&b.path)
+        }
+    });
+    if opts.reverse {
+        items.reverse();
+    }
+
+    if top && !opts.recursive && !items.is_empty() && !opts.paths_only {
+        writeln!(out, "Found {} items", items.len())?;
+    }
+
+    if !opts.recursive {
+        return ls_entries(&items, opts, out);
+    }
+
+    // children of a directory are listed right after it
+    let lines = ls_lines(&items, opts);
+    for ((child, status), line) in items.iter().zip(lines) {
+        writeln!(out, "{}", line)?;
+        if status.is_directory() {
+            ls_directory(child, opts, out, false)?;
+        }
+    }
+    Ok(())
+}
+
+fn ls_entries(
+    items: &[(Target, FileStatus)],
+    opts: &LsOptions,
+    out: &mut dyn Write,
+) -> Result<(), Error> {
+    for line in ls_lines(items, opts) {
+        writeln!(out, "{}", line)?;
+    }
+    Ok(())
+}
+
+/// Lines of ```ls```, widths of columns are shared by all items
+fn ls_lines(items: &[(Target, FileStatus)], opts: &LsOptions) -> Vec<String> {
+    if opts.paths_only {
+        return items.iter().map(|(t, _)| t.display.clone()).collect();
+    }
+
+    let rows: Vec<[String; 7]> = items
+        .iter()
+        .map(|(target, status)| {
+            let time = if opts.access_time {
+                status.last_accced()
+            } else {
+                status.last_modified()
+            };
+            [
+                status.fs_permission().to_ls_string(kind(status)),
+                if status.is_file() {
+                    status.replica_count().to_string()
+                } else {
+                    "-".to_string()
+                },
+                status.owner().to_string(),
+                status.group().to_string(),
+                format::size(status.len() as u64, opts.human),
+                format::date(time, false),
+                target.display.clone(),
+            ]
+        })
+        .collect();
+
+    let width = |c: usize, min: usize| {
+        rows.iter()
+            .map(|r| r[c].chars().count())
+            .max()
+            .unwrap_or(0)
+            .max(min)
+    };
+    let (repl, owner, group, size) = (width(1, 3), width(2, 0), width(3, 0), width(4, 10));
+
+    rows.iter()
+        .map(|r| {
+            format!(
+                "{} {:>repl$} {:<owner$} {:<group$} {:>size$} {} {}",
+                r[0], r[1], r[2], r[3], r[4], r[5], r[6]
+            )
+        })
+        .collect()
+}
+
+fn mkdir(ctx: &Context, args: &Args, _out: &mut dyn Write) -> CommandResult {
+    args.expect(1, usize::MAX)?;
+    let parents = args.has("p");
+    let mut code = 0;
+    for arg in &args.args {
+        let result = ctx.resolve(arg).and_then(|target| {
+            match target.status() {
+                Ok(status) if status.is_directory() && parents => return Ok(()),
+                Ok(_) => return Err(ErrorKind::AlreadyExists.into()),
+                Err(e) if e.kind() == ErrorKind::NotFound => (),
+                Err(e) => return Err(e),
+            }
+            if !parents && !target.fs.exist(parent(&target.path)) {
+                return Err(ErrorKind::NotFound.into());
+            }
+            target.fs.mkdir(&target.path).map(|_| ())
+        });
+        if let Err(e) = result {
+            report("mkdir", arg, &e);
+            code = EXIT_FAILURE;
+        }
+    }
+    Ok(code)
+}
+
+fn rm(ctx: &Context, args: &Args, out: &mut dyn Write) -> CommandResult {
+    let force = args.has("f");
+    if !force {
+        args.expect(1, usize::MAX)?;
+    }
+    let recursive = args.has("r") || args.has("R");
+    let skip_trash = args.has("skipTrash");
+
+    let mut code = 0;
+    for arg in &args.args {
+        let targets = match ctx.expand(arg) {
+            Ok(targets) => targets,
+            Err(e) if force && e.kind() == ErrorKind::NotFound => continue,
+            Err(e) => {
+                report("rm", arg, &e);
+                code = EXIT_FAILURE;
+                continue;
+            }
+        };
+
+        for target in targets {
+            let result = match target.status() {
+                Err(e) if force && e.kind() == ErrorKind::NotFound => continue,
+                Err(e) => Err(e),
+                Ok(status) if status.is_directory() && !recursive => Err(is_a_directory()),
+                Ok(_) => rm_target(&target, skip_trash, out),
+            };
+            if let Err(e) = result {
+                report("rm", &target.display, &e);
+                code = EXIT_FAILURE;
+            }
+        }
+    }
+    Ok(code)
+}
+
+fn rm_target(target: &Target, skip_trash: bool, out: &mut dyn Write) -> Result<(), Error> {
+    if !skip_trash {
+        let trash = Trash::new(&target.fs)?;
+        if trash.is_enabled() && !trash.is_in_trash(&target.path) {
+            trash.move_to_trash(&target.path)?;
+            let url = target.fs.url();
+            writeln!(
+                out,
+                "Moved: '{}{}' to trash at: {}{}",
+                url,
+                target.path,
+                url,
+                trash.current()
+            )?;
+            return Ok(());
+        }
+    }
+
+    target.fs.delete(&target.path, true)?;
+    writeln!(out, "Deleted {}", target.display)?;
+    Ok(())
+}
+
+/// Splits arguments into sources and destination, destination defaults to ```.```
+fn sources_and_destination(args: &Args) -> (&[String], String) {
+    match args.args.len() {
+        1 => (&args.args[..], ".".to_string()),
+        _ => {
+            let (dst, srcs) = args.args.split_last().unwrap();
+            (srcs, dst.clone())
+        }
+    }
+}
+
+/// Checks destination is a directory if there are multiple sources
+fn check_multiple_sources(cmd: &str, sources: usize, dst: &Target) -> bool {
+    if sources > 1 {
+        match dst.status() {
+            Ok(status) if status.is_directory() => (),
+            Ok(_) => {
+                report(cmd, &dst.display, &is_not_a_directory());
+                return false;
+            }
+            Err(e) => {
+                report(cmd, &dst.display, &e);
+                return false;
+            }
+        }
+    }
+    true
+}
+
+fn mv(ctx: &Context, args: &Args, _out: &mut dyn Write) -> CommandResult {
+    args.expect(2, usize::MAX)?;
+    let (srcs, dst) = sources_and_destination(args);
+    let dst = match ctx.resolve(&dst) {
+        Ok(dst) => dst,
+        Err(e) => {
+            report("mv", &dst, &e);
+            return Ok(EXIT_FAILURE);
+        }
+    };
+    if !check_multiple_sources("mv", srcs.len(), &dst) {
+        return Ok(EXIT_FAILURE);
+    }
+
+    Ok(for_each(ctx, "mv", srcs, |src| {
+        HdfsUtil::mv(&src.fs, &src.path, &dst.fs, &dst.path).map(|_| ())
+    }))
+}
+
+fn cp(ctx: &Context, args: &Args, _out: &mut dyn Write) -> CommandResult {
+    args.expect(2, usize::MAX)?;
+    let (srcs, dst) = sources_and_destination(args);
+    let dst = match ctx.resolve(&dst) {
+        Ok(dst) => dst,
+        Err(e) => {
+            report("cp", &dst, &e);
+            return Ok(EXIT_FAILURE);
+        }
+    };
+    if !check_multiple_sources("cp", srcs.len(), &dst) {
+        return Ok(EXIT_FAILURE);
+    }
+
+    let opts = CopyOptions {
+        overwrite: args.has("f"),
+        recursive: true,
+        preserve_permission: args.has("p"),
+        preserve_times: args.has("p"),
+        ..Default::default()
+    };
+    Ok(for_each(ctx, "cp", srcs, |src| {
+        HdfsUtil::cp_with_options(&src.fs, &src.path, &dst.fs, &dst.path, &opts).map(|_| ())
+    }))
+}
+
+fn transfer_options(args: &Args) -> TransferOptions {
+    TransferOptions {
+        exists: if args.has("f") {
+            ExistsPolicy::Overwrite
+        } else {
+            ExistsPolicy::Fail
+        },
+        preserve_permission: args.has("p"),
+        preserve_times: args.has("p"),
+        ..Default::default()
+    }
+}
+
+fn put(ctx: &Context, args: &Args, _out: &mut dyn Write) -> CommandResult {
+    args.expect(1, usize::MAX)?;
+    let (srcs, dst) = sources_and_destination(args);
+    let dst = match ctx.resolve(&dst) {
+        Ok(dst) => dst,
+        Err(e) => {
+            report("put", &dst, &e);
+            return Ok(EXIT_FAILURE);
+        }
+    };
+    if !check_multiple_sources("put", srcs.len(), &dst) {
+        return Ok(EXIT_FAILURE);
+    }
+
+    let opts = transfer_options(args);
+    let mut code = 0;
+    for src in srcs {
+        let result = if src == "-" {
+            put_stdin(&dst, &opts)
+        } else {
+            HdfsUtil::copy_from_local(src, &dst.fs, &dst.path, &opts, &NoProgress).map(|_| ())
+        };
+        if let Err(e) = result {
+            let path = if e.kind() == ErrorKind::AlreadyExists {
+                &dst.display
+            } else {
+                src
+            };
+            report("put", path, &e);
+            code = EXIT_FAILURE;
+        }
+    }
+    Ok(code)
+}
+
+fn put_stdin(dst: &Target, opts: &TransferOptions) -> Result<(), Error> {
+    match dst.status() {
+        Ok(status) if status.is_directory() => return Err(is_a_directory()),
+        Ok(_) if opts.exists != ExistsPolicy::Overwrite => {
+            return Err(ErrorKind::AlreadyExists.into())
+        }
+        _ => (),
+    }
+    let mut file = dst.fs.create_with_overwrite(&dst.path, true)?;
+    std::io::copy(&mut std::io::stdin().lock(), &mut file)?;
+    file.close()
+}
+
+fn get(ctx: &Context, args: &Args, _out: &mut dyn Write) -> CommandResult {
+    args.expect(1, usize::MAX)?;
+    let (srcs, dst) = sources_and_destination(args);
+    if srcs.len() > 1 && !Path::new(&dst).is_dir() {
+        report("get", &dst, &is_not_a_directory());
+        return Ok(EXIT_FAILURE);
+    }
+
+    let opts = transfer_options(args);
+    Ok(for_each(ctx, "get", srcs, |src| {
+        HdfsUtil::copy_to_local(&src.fs, &src.path, &dst, &opts, &NoProgress).map(|_| ())
+    }))
+}
+
+fn stat(ctx: &Context, args: &Args, out: &mut dyn Write) -> CommandResult {
+    args.expect(1, usize::MAX)?;
+    let (format, paths) = if args.args.len() > 1 && args.args[0].contains('%') {
+        (args.args[0].as_str(), &args.args[1..])
+    } else {
+        ("%y", &args.args[..])
+    };
+
+    Ok(for_each(ctx, "stat", paths, |target| {
+        let status = target.status()?;
+        writeln!(out, "{}", format_stat(format, target, &status))?;
+        Ok(())
+    }))
+}
+
+fn format_stat(format: &str, target: &Target, status: &FileStatus) -> String {
+    let mut result = String::new();
+    let mut chars = format.chars();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            result.push(c);
+            continue;
+        }
+        let spec = match chars.next() {
+            Some(spec) => spec,
+            None => {
+                result.push(c);
+                break;
+            }
+        };
+        match spec {
+            'a' => result.push_str(&format!("{:o}", status.fs_permission().mode())),
+            'A' => result.push_str(&status.fs_permission().to_string()),
+            'b' => result.push_str(&status.len().to_string()),
+            'F' => result.push_str(if status.is_directory() {
+                "directory"
+            } else {
+                "regular file"
+            }),
+            'g' => result.push_str(status.group()),
+            'n' => result.push_str(base_name(&target.path)),
+            'o' => result.push_str(&status.block_size().to_string()),
+            'r' => result.push_str(&status.replica_count().to_string()),
+            'u' => result.push_str(status.owner()),
+            'x' => result.push_str(&format::date(status.last_accced(), true)),
+            'X' => result.push_str(&(status.last_accced() * 1000).to_string()),
+            'y' => result.push_str(&format::date(status.last_modified(), true)),
+            'Y' => result.push_str(&(status.last_modified() * 1000).to_string()),
+            // unknown specifiers are printed without %, as hadoop does
+            spec => result.push(spec),
+        }
+    }
+    result
+}
+
+fn du(ctx: &Context, args: &Args, out: &mut dyn Write) -> CommandResult {
+    let human = args.has("h");
+    let summary = args.has("s");
+
+    let mut rows: Vec<Vec<String>> = Vec::new();
+    if args.has("v") {
+        rows.push(vec![
+            "SIZE".to_string(),
+            "DISK_SPACE_CONSUMED_WITH_ALL_REPLICAS".to_string(),
+            "FULL_PATH_NAME".to_string(),
+        ]);
+    }
+
+    let code = for_each(ctx, "du", &args.paths_or("."), |target| {
+        if summary {
+            let s = target.fs.content_summary(&target.path)?;
+            rows.push(vec![
+                format::size(s.length, human),
+                format::size(s.space_consumed, human),
+                target.display.clone(),
+            ]);
+        } else {
+            let status = target.status()?;
+            for (path, s) in target.fs.du(&target.path)? {
+                let display = if status.is_directory() {
+                    target.child(base_name(&path)).display
+                } else {
+                    target.display.clone()
+                };
+                rows.push(vec![
+                    format::size(s.length, human),
+                    format::size(s.space_consumed, human),
+                    display,
+                ]);
+            }
+        }
+        Ok(())
+    });
+
+    for line in format::table(&rows, &[Align::Left, Align::Left, Align::Left]) {
+        if let Err(e) = writeln!(out, "{}", line) {
+            report("du", "-", &e);
+            return Ok(EXIT_FAILURE);
+        }
+    }
+    Ok(code)
+}
+
+fn df(ctx: &Context, args: &Args, out: &mut dyn Write) -> CommandResult {
+    let human = args.has("h");
+    let mut rows = vec![vec![
+        "Filesystem".to_string(),
+        "Size".to_string(),
+        "Used".to_string(),
+        "Available".to_string(),
+        "Use%".to_string(),
+    ]];
+    let mut seen: Vec<String> = Vec::new();
+
+    let code = for_each(ctx, "df", &args.paths_or("/"), |target| {
+        if seen.iter().any(|url| url == target.fs.url()) {
+            return Ok(());
+        }
+        seen.push(target.fs.url().to_string());
+
+        let status = target.fs.status()?;
+        rows.push(vec![
+            target.fs.url().to_string(),
+            format::size(status.capacity, human),
+            format::size(status.used, human),
+            format::size(status.remaining, human),
+            format!("{:.0}%", status.used_ratio() * 100.0),
+        ]);
+        Ok(())
+    });
+
+    let align = [
+        Align::Left,
+        Align::Right,
+        Align::Right,
+        Align::Right,
+        Align::Right,
+    ];
+    for line in format::table(&rows, &align) {
+        if let Err(e) = writeln!(out, "{}", line) {
+            report("df", "-", &e);
+            return Ok(EXIT_FAILURE);
+        }
+    }
+    Ok(code)
+}
+
+fn chmod(ctx: &Context, args: &Args, _out: &mut dyn Write) -> CommandResult {
+    args.expect(2, usize::MAX)?;
+    let mode = &args.args[0];
+    if FsPermission::from_octal(mode).is_err() && SymbolicMode::parse(mode).is_err() {
+        return Err(UsageError(format!(
+            "chmod : mode '{}' does not match the expected pattern.",
+            mode
+        )));
+    }
+
+    let recursive = args.has("R");
+    Ok(for_each(ctx, "chmod", &args.args[1..], |target| {
+        if recursive {
+            let opts = RecursiveOptions::default();
+            return recursive_result(
+                "chmod",
+                target.fs.chmod_mode_recursive(&target.path, mode, &opts)?,
+            );
+        }
+        let status = target.status()?;
+        let permission = status
+            .fs_permission()
+            .apply_mode(mode, status.is_directory())?;
+        target.fs.try_set_permission(&target.path, permission)
+    }))
+}
+
+fn chown(ctx: &Context, args: &Args, _out: &mut dyn Write) -> CommandResult {
+    args.expect(2, usize::MAX)?;
+    let spec = &args.args[0];
+    let (owner, group) = match spec.split_once(':') {
+        Some((owner, group)) => (owner, group),
+        None => (spec.as_str(), ""),
+    };
+    if owner.is_empty() && group.is_empty() {
+        return Err(UsageError(format!(
+            "'{}' does not match expected pattern for [owner][:group].",
+            spec
+        )));
+    }
+
+    let recursive = args.has("R");
+    Ok(for_each(ctx, "chown", &args.args[1..], |target| {
+        if recursive {
+            let opts = RecursiveOptions::default();
+            return recursive_result(
+                "chown",
+                target.fs.chown_recursive(&target.path, owner, group, &opts),
+            );
+        }
+        target.fs.try_chown(&target.path, owner, group)
+    }))
+}
+
+/// Reports failures of a recursive operation, the last one is returned
+/// to be reported with the target
+fn recursive_result(cmd: &str, outcome: RecursiveReport) -> Result<(), Error> {
+    let mut failures = outcome.failures;
+    let last = failures.pop();
+    for (path, e) in failures {
+        report(cmd, &path, &e);
+    }
+    last.map_or(Ok(()), |(_, e)| Err(e))
+}
+
+fn setrep(ctx: &Context, args: &Args, out: &mut dyn Write) -> CommandResult {
+    args.expect(2, usize::MAX)?;
+    let replication: i16 = match args.args[0].parse() {
+        Ok(replication) if replication > 0 => replication,
+        _ => {
+            return Err(UsageError(format!(
+                "Invalid replication factor '{}'",
+                args.args[0]
+            )))
+        }
+    };
+
+    let mut files: Vec<Target> = Vec::new();
+    let code = for_each(ctx, "setrep", &args.args[1..], |target| {
+        // replication of directories is set on all files under them,
+        // the filter only records which files are modified
+        let selected = Mutex::new(Vec::new());
+        let record = |path: &str, _: &FileStatus| {
+            selected.lock().unwrap().push(path.to_string());
+            true
+        };
+        let opts = RecursiveOptions {
+            filter: Some(&record),
+            ..Default::default()
+        };
+        let report = target
+            .fs
+            .set_replication_recursive(&target.path, replication, &opts);
+
+        for path in selected.into_inner().unwrap() {
+            if report.failures.iter().any(|(failed, _)| failed == &path) {
+                continue;
+            }
+            let display = if path == target.path {
+                target.display.clone()
+            } else {
+                format!("{}{}", target.display, &path[target.path.len()..])
+            };
+            writeln!(out, "Replication {} set: {}", replication, display)?;
+            files.push(Target {
+                fs: target.fs.clone(),
+                path,
+                display,
+            });
+        }
+        recursive_result("setrep", report)
+    });
+
+    if !args.has("w") {
+        return Ok(code);
+    }
+
+    let mut code = code;
+    for file in files {
+        if let Err(e) = wait_for_replication(&file, replication, out) {
+            report("setrep", &file.display, &e);
+            code = EXIT_FAILURE;
+        }
+    }
+    Ok(code)
+}
+
+/// Polls block locations until all blocks have ```replication``` replicas
+fn wait_for_replication(file: &Target, replication: i16, out: &mut dyn Write) -> Result<(), Error> {
+    write!(out, "Waiting for {} ...", file.display)?;
+    out.flush()?;
+    loop {
+        let len = file.status()?.len() as u64;
+        let blocks = file.fs.get_block_locations(&file.path, 0, len)?;
+        if blocks.iter().all(|b| b.hosts.len() >= replication as usize) {
+            break;
+        }
+        write!(out, ".")?;
+        out.flush()?;
+        std::thread::sleep(Duration::from_secs(10));
+    }
+    writeln!(out, " done")?;
+    Ok(())
+}
+
+fn touchz(ctx: &Context, args: &Args, _out: &mut dyn Write) -> CommandResult {
+    args.expect(1, usize::MAX)?;
+    let mut code = 0;
+    for arg in &args.args {
+        let result = ctx.resolve(arg).and_then(|target| match target.status() {
+            Ok(status) if status.is_directory() => Err(is_a_directory()),
+            Ok(status) if status.len() != 0 => Err(Error::other("Not a zero-length file")),
+            Ok(_) => Ok(()),
+            Err(e) if e.kind() == ErrorKind::NotFound => {
+                if !target.fs.exist(parent(&target.path)) {
+                    return Err(ErrorKind::NotFound.into());
+                }
+                target.fs.create(&target.path).map(|_| ())
+            }
+            Err(e) => Err(e),
+        });
+        if let Err(e) = result {
+            report("touchz", arg, &e);
+            code = EXIT_FAILURE;
+        }
+    }
+    Ok(code)
+}
+
+fn test(ctx: &Context, args: &Args, _out: &mut dyn Write) -> CommandResult {
+    args.expect(1, 1)?;
+    if args.flags.len() != 1 {
+        return Err(UsageError("No test flag given".to_string()));
+    }
+
+    let status = ctx
+        .resolve(&args.args[0])
+        .and_then(|target| target.status());
+    let result = match (args.flags[0].as_str(), status) {
+        ("e", status) => status.is_ok(),
+        ("d", Ok(status)) => status.is_directory(),
+        ("f", Ok(status)) => status.is_file(),
+        ("s", Ok(status)) => status.len() > 0,
+        ("z", Ok(status)) => status.len() == 0,
+        _ => false,
+    };
+    Ok(if result { 0 } else { EXIT_FAILURE })
+}
+
+fn help(_ctx: &Context, args: &Args, out: &mut dyn Write) -> CommandResult {
+    let names: Vec<&str> = if args.args.is_empty() {
+        COMMANDS.iter().map(|c| c.name).collect()
+    } else {
+        args.args
+            .iter()
+            .map(|a| a.strip_prefix('-').unwrap_or(a))
+            .collect()
+    };
+
+    let mut code = 0;
+    for name in names {
+        match find(name) {
+            Some(command) => {
+                let _ = writeln!(out, "-{} {} :", command.name, command.usage);
+                let _ = writeln!(out, "  {}", command.description);
+                let _ = writeln!(out);
+            }
+            None => {
+                eprintln!("help: Unknown command -{}", name);
+                code = EXIT_FAILURE;
+            }
+        }
+    }
+    Ok(code)
+}
@@ -0,0 +1,204 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Resolution of command line paths to filesystems
+
+use hdfs_native::glob::GlobPattern;
+use hdfs_native::util::{join_path, strip_authority};
+use hdfs_native::{FileStatus, HdfsFs, HdfsRegistry, HdfsUtil};
+use std::io::{Error, ErrorKind};
+use std::sync::Arc;
+use url::Url;
+
+/// Client configuration key of the default filesystem
+const DEFAULT_URI_KEY: &str = "dfs.default.uri";
+
+/// Path given on the command line, resolved to its filesystem
+#[derive(Clone)]
+pub struct Target {
+    pub fs: Arc<HdfsFs>,
+    /// absolute path on the filesystem
+    pub path: String,
+    /// path as it should be printed
+    pub display: String,
+}
+
+impl Target {
+    pub fn status(&self) -> Result<FileStatus, Error> {
+        self.fs.get_file_status(&self.path)
+    }
+
+    /// Target of a child of this directory
+    pub fn child(&self, name: &str) -> Target {
+        Target {
+            fs: self.fs.clone(),
+            path: join_path(&self.path, name),
+            display: match self.display.as_str() {
+                "." => name.to_string(),
+                display => join_path(display, name),
+            },
+        }
+    }
+}
+
+/// State shared by commands
+pub struct Context {
+    registry: HdfsRegistry,
+    default_fs: Option<String>,
+    /// working directory on default filesystem, if not set working
+    /// directory of the filesystem is used
+    working_dir: Option<String>,
+}
+
+impl Context {
+    /// ```default_fs``` is used for paths without scheme, if not set
+    /// ```dfs.default.uri``` of client configuration is used.
+    pub fn new(default_fs: Option<String>) -> Context {
+        Context {
+            registry: HdfsRegistry::new(),
+            default_fs: default_fs.or_else(|| HdfsUtil::get_conf_str(DEFAULT_URI_KEY)),
+            working_dir: None,
+        }
+    }
+
+    /// URL of default filesystem, if configured
+    #[cfg(feature = "shell")]
+    pub fn default_fs_url(&self) -> Option<&str> {
+        self.default_fs.as_deref()
+    }
+
+    /// Connect to a filesystem and make it default, working directory
+    /// is reset to the one of the filesystem.
+    #[cfg(feature = "shell")]
+    pub fn set_default_fs(&mut self, url: &str) -> Result<Arc<HdfsFs>, Error> {
+        let fs = self.registry.get(url)?;
+        self.default_fs = Some(url.to_string());
+        self.working_dir = None;
+        Ok(fs)
+    }
+
+    /// Absolute working directory on default filesystem
+    pub fn working_directory(&self) -> Result<String, Error> {
+        match &self.working_dir {
+            Some(path) => Ok(path.clone()),
+            None => Ok(strip_authority(&self.default_fs()?.working_directory()?)),
+        }
+    }
+
+    /// Change working directory on default filesystem, ```None``` restores
+    /// working directory of the filesystem
+    #[cfg(feature = "shell")]
+    pub fn set_working_directory(&mut self, path: Option<String>) {
+        self.working_dir = path;
+    }
+
+    pub fn default_fs(&self) -> Result<Arc<HdfsFs>, Error> {
+        match &self.default_fs {
+            Some(url) => self.registry.get(url),
+            None => Err(Error::new(
+                ErrorKind::InvalidInput,
+                "default filesystem is not configured, use -fs <uri>",
+            )),
+        }
+    }
+
+    /// Resolve a path, relative paths are resolved against working directory
+    pub fn resolve(&self, arg: &str) -> Result<Target, Error> {
+        if arg.contains("://") {
+            let url = Url::parse(arg)
+                .map_err(|e| Error::new(ErrorKind::InvalidInput, format!("{}: {}", arg, e)))?;
+            let fs = self.registry.get(arg)?;
+            let path = normalize(match url.path() {
+                "" => "/",
+                path => path,
+            });
+            return Ok(Target {
+                fs,
+                path,
+                display: arg.to_string(),
+            });
+        }
+
+        let fs = self.default_fs()?;
+        let path = if arg.starts_with('/') {
+            normalize(arg)
+        } else {
+            normalize(&join_path(&self.working_directory()?, arg))
+        };
+
+        Ok(Target {
+            fs,
+            path,
+            display: arg.to_string(),
+        })
+    }
+
+    /// Resolve a path which may contain glob pattern. Returns
+    /// [ErrorKind::NotFound] if a pattern does not match anything.
+    pub fn expand(&self, arg: &str) -> Result<Vec<Target>, Error> {
+        let target = self.resolve(arg)?;
+        if !GlobPattern::new(&target.path)?.has_wildcard() {
+            return Ok(vec![target]);
+        }
+
+        let matched = target.fs.glob(&target.path)?;
+        if matched.is_empty() {
+            return Err(ErrorKind::NotFound.into());
+        }
+
+        let prefix = if arg.contains("://") {
+            target.fs.url().to_string()
+        } else {
+            String::new()
+        };
+        Ok(matched
+            .iter()
+            .map(|status| {
+                let path = strip_authority(status.name());
+                Target {
+                    fs: target.fs.clone(),
+                    display: format!("{}{}", prefix, path),
+                    path,
+                }
+            })
+            .collect())
+    }
+}
+
+/// Returns parent of an absolute path
+pub fn parent(path: &str) -> &str {
+    let path = path.trim_end_matches('/');
+    match path.rfind('/') {
+        Some(0) | None => "/",
+        Some(idx) => &path[..idx],
+    }
+}
+
+/// Resolves ```.``` and ```..``` components of an absolute path
+fn normalize(path: &str) -> String {
+    let mut components: Vec<&str> = Vec::new();
+    for component in path.split('/') {
+        match component {
+            "" | "." => (),
+            ".." => {
+                components.pop();
+            }
+            component => components.push(component),
+        }
+    }
+    format!("/{}", components.join("/"))
+}
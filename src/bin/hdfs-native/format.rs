@@ -0,0 +1,121 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Formatting of sizes, dates and tables compatible with ```hadoop fs```
+
+use libc::time_t;
+
+const PREFIXES: [char; 6] = ['K', 'M', 'G', 'T', 'P', 'E'];
+
+/// Formats size using binary prefixes with one decimal place,
+/// e.g. ```1.5 K```, the way Hadoop's ```TraditionalBinaryPrefix``` does.
+pub fn human_size(size: u64) -> String {
+    if size < 1024 {
+        return size.to_string();
+    }
+
+    let mut idx = 0;
+    while idx + 1 < PREFIXES.len() && size >= 1 << (10 * (idx + 2)) {
+        idx += 1;
+    }
+    let shift = 10 * (idx + 1);
+    if size & ((1 << shift) - 1) == 0 {
+        return format!("{} {}", size >> shift, PREFIXES[idx]);
+    }
+
+    let value = format!("{:.1}", size as f64 / (1u64 << shift) as f64);
+    if value.starts_with("1024") && idx + 1 < PREFIXES.len() {
+        let value = size as f64 / (1u64 << (shift + 10)) as f64;
+        return format!("{:.1} {}", value, PREFIXES[idx + 1]);
+    }
+    format!("{} {}", value, PREFIXES[idx])
+}
+
+/// Formats size as is or in human readable form
+pub fn size(size: u64, human: bool) -> String {
+    if human {
+        human_size(size)
+    } else {
+        size.to_string()
+    }
+}
+
+/// Formats seconds since epoch as ```yyyy-MM-dd HH:mm```, using
+/// local time zone, or as ```yyyy-MM-dd HH:mm:ss``` in UTC.
+pub fn date(secs: time_t, utc: bool) -> String {
+    let mut tm: libc::tm = unsafe { std::mem::zeroed() };
+    unsafe {
+        if utc {
+            libc::gmtime_r(&secs, &mut tm);
+        } else {
+            libc::localtime_r(&secs, &mut tm);
+        }
+    }
+
+    let date = format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}",
+        tm.tm_year + 1900,
+        tm.tm_mon + 1,
+        tm.tm_mday,
+        tm.tm_hour,
+        tm.tm_min
+    );
+    if utc {
+        format!("{}:{:02}", date, tm.tm_sec)
+    } else {
+        date
+    }
+}
+
+/// Column alignment of [table]
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Align {
+    Left,
+    Right,
+}
+
+/// Renders rows as columns separated by two spaces
+pub fn table(rows: &[Vec<String>], align: &[Align]) -> Vec<String> {
+    let columns = rows.iter().map(|r| r.len()).max().unwrap_or(0);
+    let widths: Vec<usize> = (0..columns)
+        .map(|c| {
+            rows.iter()
+                .filter_map(|r| r.get(c))
+                .map(|v| v.chars().count())
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+
+    rows.iter()
+        .map(|row| {
+            let cells: Vec<String> = row
+                .iter()
+                .enumerate()
+                .map(|(c, value)| {
+                    let last = c + 1 == row.len();
+                    match align.get(c).copied().unwrap_or(Align::Left) {
+                        Align::Right => format!("{:>width$}", value, width = widths[c]),
+                        Align::Left if last => value.clone(),
+                        Align::Left => format!("{:<width$}", value, width = widths[c]),
+                    }
+                })
+                .collect();
+            cells.join("  ")
+        })
+        .collect()
+}
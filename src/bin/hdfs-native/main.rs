@@ -0,0 +1,105 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Command line client mirroring ```hadoop fs```
+//!
+//! ```text
+//! hdfs-native [-fs hdfs://namenode:port] -ls -R /data
+//! ```
+//!
+//! Paths without scheme are resolved against filesystem given by ```-fs```
+//! (or ```-D fs.defaultFS=```), or ```dfs.default.uri``` of client
+//! configuration. Output and exit codes follow ```hadoop fs```.
+//!
+//! ```-shell``` starts an interactive shell, available with ```shell``` feature.
+
+mod commands;
+mod context;
+mod format;
+#[cfg(feature = "shell")]
+mod shell;
+
+use crate::commands::EXIT_USAGE;
+use crate::context::Context;
+
+const PROGRAM: &str = "hdfs-native";
+
+/// Parses generic options, returns default filesystem and remaining arguments
+fn parse_generic_options(mut args: &[String]) -> Result<(Option<String>, &[String]), String> {
+    let mut default_fs = None;
+    loop {
+        match args.first().map(|a| a.as_str()) {
+            Some("-fs") => {
+                let url = args.get(1).ok_or("-fs requires a filesystem URL")?;
+                default_fs = Some(url.clone());
+                args = &args[2..];
+            }
+            Some("-D") => {
+                let property = args.get(1).ok_or("-D requires a property=value")?;
+                match property.split_once('=') {
+                    Some(("fs.defaultFS", url)) | Some(("fs.default.name", url)) => {
+                        default_fs = Some(url.to_string())
+                    }
+                    Some(_) => eprintln!("{}: ignoring unsupported property {}", PROGRAM, property),
+                    None => return Err(format!("invalid property {}", property)),
+                }
+                args = &args[2..];
+            }
+            _ => return Ok((default_fs, args)),
+        }
+    }
+}
+
+#[cfg(feature = "shell")]
+fn run_shell(ctx: Context) -> i32 {
+    shell::run(ctx)
+}
+
+#[cfg(not(feature = "shell"))]
+fn run_shell(_ctx: Context) -> i32 {
+    eprintln!(
+        "{}: shell is not available, install with --features shell",
+        PROGRAM
+    );
+    EXIT_USAGE
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+
+    let (default_fs, args) = match parse_generic_options(&args) {
+        Ok(parsed) => parsed,
+        Err(message) => {
+            eprintln!("{}: {}", PROGRAM, message);
+            std::process::exit(EXIT_USAGE);
+        }
+    };
+
+    if args.is_empty() {
+        commands::usage(&mut std::io::stderr(), PROGRAM);
+        std::process::exit(EXIT_USAGE);
+    }
+
+    let ctx = Context::new(default_fs);
+    if args[0] == "-shell" {
+        std::process::exit(run_shell(ctx));
+    }
+
+    let mut out = std::io::stdout().lock();
+    let code = commands::run(&ctx, args, &mut out);
+    std::process::exit(code);
+}
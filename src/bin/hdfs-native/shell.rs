@@ -0,0 +1,484 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Interactive shell
+//!
+//! ```text
+//! hdfs-native -fs hdfs://namenode:port -shell
+//! hdfs:default:/user/hdfs> cd /data
+//! hdfs:default:/data> cat part-0000.csv | grep error
+//! hdfs:default:/data> connect backup hdfs://backup:8020
+//! ```
+//!
+//! All commands of ```hadoop fs``` are available, with or without leading
+//! dash, relative paths are resolved against the working directory of the
+//! current connection. Output of a command can be piped to a local command
+//! which is executed by ```sh -c```.
+
+use crate::commands::{self, report, COMMANDS};
+use crate::context::Context;
+use hdfs_native::util::base_name;
+use rustyline::completion::Completer;
+use rustyline::error::ReadlineError;
+use rustyline::highlight::Highlighter;
+use rustyline::hint::Hinter;
+use rustyline::history::DefaultHistory;
+use rustyline::validate::Validator;
+use rustyline::{Editor, Helper};
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::io::{Error, ErrorKind};
+use std::path::PathBuf;
+use std::process::Stdio;
+use std::rc::Rc;
+
+/// Name of the connection given by ```-fs```
+const DEFAULT_CONNECTION: &str = "default";
+
+/// History file, relative to home directory
+const HISTORY_FILE: &str = ".hdfs_native_history";
+
+/// Commands handled by the shell itself
+const BUILTINS: &[(&str, &str, &str)] = &[
+    (
+        "cd",
+        "[<path>]",
+        "Change working directory, without path restores the initial one.",
+    ),
+    ("pwd", "", "Print working directory."),
+    (
+        "connect",
+        "<name> <url>",
+        "Connect to a filesystem and make it current.",
+    ),
+    ("use", "<name>", "Make a connection current."),
+    (
+        "connections",
+        "",
+        "List connections, current one is marked with *.",
+    ),
+    ("history", "", "Show command history."),
+    ("help", "[<command>]", "Show shell and command usage."),
+    ("exit", "", "Leave the shell, as does quit or Ctrl-D."),
+];
+
+/// Named connection and its working directory
+struct Connection {
+    url: String,
+    working_dir: Option<String>,
+}
+
+struct Shell {
+    ctx: Rc<RefCell<Context>>,
+    connections: BTreeMap<String, Connection>,
+    current: Option<String>,
+    /// exit code of the last command
+    status: i32,
+}
+
+/// Run the shell until end of input, returns exit code of the last command
+pub fn run(ctx: Context) -> i32 {
+    let default_fs = ctx.default_fs_url().map(|url| url.to_string());
+    let ctx = Rc::new(RefCell::new(ctx));
+    let mut shell = Shell {
+        ctx: ctx.clone(),
+        connections: BTreeMap::new(),
+        current: None,
+        status: 0,
+    };
+    if let Some(url) = default_fs {
+        if let Err(e) = shell.connect(DEFAULT_CONNECTION, &url) {
+            report("connect", &url, &e);
+        }
+    }
+
+    let mut editor: Editor<ShellHelper, DefaultHistory> = match Editor::new() {
+        Ok(editor) => editor,
+        Err(e) => {
+            eprintln!("shell: {}", e);
+            return commands::EXIT_FAILURE;
+        }
+    };
+    editor.set_helper(Some(ShellHelper { ctx }));
+    let history = history_file();
+    if let Some(history) = &history {
+        // missing on the first run
+        let _ = editor.load_history(history);
+    }
+
+    loop {
+        let line = match editor.readline(&shell.prompt()) {
+            Ok(line) => line,
+            Err(ReadlineError::Interrupted) => continue,
+            Err(ReadlineError::Eof) => break,
+            Err(e) => {
+                eprintln!("shell: {}", e);
+                break;
+            }
+        };
+        if line.trim().is_empty() {
+            continue;
+        }
+        let _ = editor.add_history_entry(line.as_str());
+
+        let (args, pipe) = match split(&line) {
+            Ok(split) => split,
+            Err(message) => {
+                eprintln!("shell: {}", message);
+                continue;
+            }
+        };
+        if args.is_empty() {
+            eprintln!("shell: missing command before |");
+            continue;
+        }
+
+        match args[0].as_str() {
+            "exit" | "quit" => break,
+            "history" => {
+                for (idx, entry) in editor.history().iter().enumerate() {
+                    println!("{:5}  {}", idx + 1, entry);
+                }
+            }
+            _ => shell.execute(&args, pipe.as_deref()),
+        }
+    }
+
+    if let Some(history) = &history {
+        if let Err(e) = editor.save_history(history) {
+            eprintln!("shell: history not saved: {}", e);
+        }
+    }
+    shell.status
+}
+
+impl Shell {
+    fn prompt(&self) -> String {
+        let ctx = self.ctx.borrow();
+        match (&self.current, ctx.working_directory()) {
+            (Some(name), Ok(path)) => format!("hdfs:{}:{}> ", name, path),
+            (Some(name), Err(_)) => format!("hdfs:{}> ", name),
+            (None, _) => "hdfs> ".to_string(),
+        }
+    }
+
+    fn execute(&mut self, args: &[String], pipe: Option<&str>) {
+        let mut code = 0;
+        let result = match args[0].as_str() {
+            "cd" => self.cd(args.get(1).map(|a| a.as_str())),
+            "pwd" => self.pwd(),
+            "connect" => match args {
+                [_, name, url] => self.connect(name, url),
+                _ => print_usage("connect <name> <url>"),
+            },
+            "use" => match args {
+                [_, name] => self.switch(name),
+                _ => print_usage("use <name>"),
+            },
+            "connections" => {
+                self.list_connections();
+                Ok(())
+            }
+            "help" if args.len() == 1 => {
+                help();
+                Ok(())
+            }
+            "help" if BUILTINS.iter().any(|b| b.0 == args[1]) => {
+                for (name, usage, description) in BUILTINS.iter().filter(|b| b.0 == args[1]) {
+                    println!("{} {}:\t{}", name, usage, description);
+                }
+                Ok(())
+            }
+            _ => {
+                let ctx = self.ctx.borrow();
+                match pipe {
+                    Some(pipe) => run_piped(&ctx, args, pipe).map(|c| code = c),
+                    None => {
+                        code = commands::run(&ctx, args, &mut std::io::stdout().lock());
+                        Ok(())
+                    }
+                }
+            }
+        };
+        self.status = match result {
+            Ok(()) => code,
+            Err(e) => {
+                report(&args[0], args.get(1).map_or("", |a| a.as_str()), &e);
+                commands::EXIT_FAILURE
+            }
+        };
+    }
+
+    fn cd(&mut self, arg: Option<&str>) -> Result<(), Error> {
+        let mut ctx = self.ctx.borrow_mut();
+        let arg = match arg {
+            Some(arg) => arg,
+            None => {
+                ctx.set_working_directory(None);
+                return Ok(());
+            }
+        };
+
+        let target = ctx.resolve(arg)?;
+        if arg.contains("://") {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "use connect to change filesystem",
+            ));
+        }
+        if !target.status()?.is_directory() {
+            return Err(Error::new(ErrorKind::InvalidInput, "Not a directory"));
+        }
+        ctx.set_working_directory(Some(target.path));
+        Ok(())
+    }
+
+    fn pwd(&self) -> Result<(), Error> {
+        let ctx = self.ctx.borrow();
+        let fs = ctx.default_fs()?;
+        println!("{}{}", fs.url(), ctx.working_directory()?);
+        Ok(())
+    }
+
+    fn connect(&mut self, name: &str, url: &str) -> Result<(), Error> {
+        self.save_working_directory();
+        self.ctx.borrow_mut().set_default_fs(url)?;
+        self.connections.insert(
+            name.to_string(),
+            Connection {
+                url: url.to_string(),
+                working_dir: None,
+            },
+        );
+        self.current = Some(name.to_string());
+        Ok(())
+    }
+
+    fn switch(&mut self, name: &str) -> Result<(), Error> {
+        let connection = self.connections.get(name).ok_or_else(|| {
+            Error::new(
+                ErrorKind::InvalidInput,
+                format!("no connection named {}", name),
+            )
+        })?;
+        let (url, working_dir) = (connection.url.clone(), connection.working_dir.clone());
+
+        self.save_working_directory();
+        let mut ctx = self.ctx.borrow_mut();
+        ctx.set_default_fs(&url)?;
+        ctx.set_working_directory(working_dir);
+        self.current = Some(name.to_string());
+        Ok(())
+    }
+
+    /// Remember working directory of the current connection
+    fn save_working_directory(&mut self) {
+        let ctx = self.ctx.borrow();
+        if let Some(connection) = self
+            .current
+            .as_ref()
+            .and_then(|name| self.connections.get_mut(name))
+        {
+            connection.working_dir = ctx.working_directory().ok();
+        }
+    }
+
+    fn list_connections(&self) {
+        for (name, connection) in &self.connections {
+            let marker = if self.current.as_deref() == Some(name) {
+                "*"
+            } else {
+                " "
+            };
+            println!("{} {}\t{}", marker, name, connection.url);
+        }
+    }
+}
+
+fn print_usage(usage: &str) -> Result<(), Error> {
+    eprintln!("Usage: {}", usage);
+    Ok(())
+}
+
+fn help() {
+    println!("Shell commands:");
+    for (name, usage, description) in BUILTINS {
+        println!("  {} {}\n\t{}", name, usage, description);
+    }
+    println!();
+    println!("Filesystem commands, leading dash is optional:");
+    for command in COMMANDS {
+        println!("  {} {}", command.name, command.usage);
+    }
+    println!();
+    println!("Output can be piped to a local command: cat /data/file.csv | grep error");
+}
+
+/// Run a command writing its output to standard input of a local command.
+/// Both report their failures on stderr, returned exit code is the one of
+/// the local command if it failed, otherwise of the command (as ```pipefail```).
+fn run_piped(ctx: &Context, args: &[String], pipe: &str) -> Result<i32, Error> {
+    let mut child = std::process::Command::new("sh")
+        .arg("-c")
+        .arg(pipe)
+        .stdin(Stdio::piped())
+        .spawn()?;
+    let code = {
+        let mut stdin = child.stdin.take().expect("stdin of the child is piped");
+        commands::run(ctx, args, &mut stdin)
+        // stdin is closed here so the child sees end of input
+    };
+    let status = child.wait()?;
+    if status.success() {
+        Ok(code)
+    } else {
+        report("sh", pipe, &Error::other(status.to_string()));
+        Ok(status.code().unwrap_or(commands::EXIT_FAILURE))
+    }
+}
+
+/// Split a line into arguments and optional local command the output
+/// is piped to. Single and double quotes and backslash escapes are
+/// supported, globs are left for commands to expand.
+fn split(line: &str) -> Result<(Vec<String>, Option<String>), String> {
+    let mut args = Vec::new();
+    let mut current: Option<String> = None;
+    let mut quote: Option<char> = None;
+    let mut chars = line.char_indices();
+
+    while let Some((idx, c)) = chars.next() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some('"'), '\\') | (None, '\\') => match chars.next() {
+                Some((_, escaped)) => current.get_or_insert_with(String::new).push(escaped),
+                None => return Err("unexpected end of line after \\".to_string()),
+            },
+            (Some(_), c) => current.get_or_insert_with(String::new).push(c),
+            (None, '\'') | (None, '"') => {
+                quote = Some(c);
+                current.get_or_insert_with(String::new);
+            }
+            (None, '|') => {
+                args.extend(current.take());
+                let pipe = line[idx + 1..].trim();
+                if pipe.is_empty() {
+                    return Err("missing command after |".to_string());
+                }
+                return Ok((args, Some(pipe.to_string())));
+            }
+            (None, c) if c.is_whitespace() => args.extend(current.take()),
+            (None, c) => current.get_or_insert_with(String::new).push(c),
+        }
+    }
+
+    if quote.is_some() {
+        return Err("unterminated quote".to_string());
+    }
+    args.extend(current.take());
+    Ok((args, None))
+}
+
+fn history_file() -> Option<PathBuf> {
+    std::env::var_os("HOME").map(|home| PathBuf::from(home).join(HISTORY_FILE))
+}
+
+/// Completion of command names and remote paths
+struct ShellHelper {
+    ctx: Rc<RefCell<Context>>,
+}
+
+impl ShellHelper {
+    fn complete_command(&self, word: &str) -> Vec<String> {
+        let dash = word.starts_with('-');
+        let name = word.strip_prefix('-').unwrap_or(word);
+        let builtins = BUILTINS.iter().map(|b| b.0).filter(|_| !dash);
+        let mut candidates: Vec<String> = COMMANDS
+            .iter()
+            .map(|c| c.name)
+            .chain(builtins)
+            .filter(|c| c.starts_with(name))
+            .map(|c| format!("{}{} ", if dash { "-" } else { "" }, c))
+            .collect();
+        candidates.sort();
+        candidates
+    }
+
+    fn complete_path(&self, word: &str) -> Vec<String> {
+        let (dir, prefix) = match word.rfind('/') {
+            Some(idx) => word.split_at(idx + 1),
+            None => ("", word),
+        };
+        let ctx = self.ctx.borrow();
+        let target = match ctx.resolve(if dir.is_empty() { "." } else { dir }) {
+            Ok(target) => target,
+            Err(_) => return vec![],
+        };
+        let children = match target.fs.list_status(&target.path) {
+            Ok(children) => children,
+            Err(_) => return vec![],
+        };
+
+        let mut candidates: Vec<String> = children
+            .iter()
+            .filter_map(|status| {
+                let name = base_name(status.name());
+                if !name.starts_with(prefix) {
+                    return None;
+                }
+                let suffix = if status.is_directory() { "/" } else { " " };
+                Some(format!("{}{}{}", dir, name, suffix))
+            })
+            .collect();
+        candidates.sort();
+        candidates
+    }
+}
+
+impl Completer for ShellHelper {
+    type Candidate = String;
+
+    fn complete(
+        &self,
+        line: &str,
+        pos: usize,
+        _ctx: &rustyline::Context<'_>,
+    ) -> rustyline::Result<(usize, Vec<String>)> {
+        let line = &line[..pos];
+        // local commands are not completed
+        if line.contains('|') {
+            return Ok((pos, vec![]));
+        }
+        let start = line.rfind(char::is_whitespace).map_or(0, |idx| idx + 1);
+        let word = &line[start..];
+        let candidates = if line[..start].trim().is_empty() {
+            self.complete_command(word)
+        } else {
+            self.complete_path(word)
+        };
+        Ok((start, candidates))
+    }
+}
+
+impl Hinter for ShellHelper {
+    type Hint = String;
+}
+
+impl Highlighter for ShellHelper {}
+
+impl Validator for ShellHelper {}
+
+impl Helper for ShellHelper {}
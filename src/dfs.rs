@@ -15,151 +15,273 @@
 // specific language governing permissions and limitations
 // under the License.
 
-use crate::{from_raw, to_raw};
-use libc::{c_char, c_int, c_short, c_void, time_t};
-use libhdfs3_sys::*;
+use crate::follow::{FollowOptions, Follower};
+use crate::list::ListStatus;
+use crate::metadata::{from_system_time, FileKind, FileMetadata};
+use crate::permission::FsPermission;
+#[cfg(feature = "libhdfs3")]
+use crate::raw::hdfsFS;
+use crate::recursive::{apply_recursive, Operation, RecursiveOptions, RecursiveReport};
+use crate::split::InputSplits;
+use crate::status::FsStatus;
+use crate::summary::ContentSummary;
+use crate::trash::Trash;
+use crate::walk::Walk;
+use libc::time_t;
+use log::warn;
 use std::cmp::min;
 use std::fmt::{Debug, Formatter};
 use std::io::{Error, ErrorKind};
 use std::io::{Read, Write};
 use std::string::String;
-use std::sync::Arc;
+use std::time::SystemTime;
 
 /// Includes host names where a particular block of a file is stored.
 pub struct BlockHosts {
-    ptr: *mut *mut *mut c_char,
+    blocks: Vec<Vec<String>>,
 }
 
-impl Drop for BlockHosts {
-    fn drop(&mut self) {
-        unsafe { hdfsFreeHosts(self.ptr) };
+impl BlockHosts {
+    /// Number of blocks
+    pub fn len(&self) -> usize {
+        self.blocks.len()
     }
-}
 
-struct HdfsFileInfoPtr {
-    pub ptr: *mut hdfsFileInfo,
-    pub len: i32,
-}
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
 
-impl Drop for HdfsFileInfoPtr {
-    fn drop(&mut self) {
-        unsafe { hdfsFreeFileInfo(self.ptr, self.len) };
+    /// Host names of the block at ```idx```
+    pub fn get(&self, idx: usize) -> Option<Vec<&str>> {
+        self.blocks
+            .get(idx)
+            .map(|hosts| hosts.iter().map(String::as_str).collect())
+    }
+
+    /// Iterate over host names of each block
+    pub fn iter(&self) -> BlockHostsIter<'_> {
+        BlockHostsIter {
+            hosts: self,
+            idx: 0,
+        }
     }
 }
 
-// this should be ok
-unsafe impl Send for HdfsFileInfoPtr {}
-unsafe impl Sync for HdfsFileInfoPtr {}
+impl<'a> IntoIterator for &'a BlockHosts {
+    type Item = Vec<&'a str>;
+    type IntoIter = BlockHostsIter<'a>;
 
-impl HdfsFileInfoPtr {
-    fn new(ptr: *mut hdfsFileInfo) -> HdfsFileInfoPtr {
-        HdfsFileInfoPtr { ptr, len: 1 }
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
     }
+}
+
+/// Iterator over host names of blocks, see [BlockHosts::iter]
+pub struct BlockHostsIter<'a> {
+    hosts: &'a BlockHosts,
+    idx: usize,
+}
+
+impl<'a> Iterator for BlockHostsIter<'a> {
+    type Item = Vec<&'a str>;
 
-    pub fn new_array(ptr: *mut hdfsFileInfo, len: i32) -> HdfsFileInfoPtr {
-        HdfsFileInfoPtr { ptr, len }
+    fn next(&mut self) -> Option<Self::Item> {
+        let block = self.hosts.get(self.idx)?;
+        self.idx += 1;
+        Some(block)
     }
 }
 
-/// Interface that represents the client side information for a file or directory.
-pub struct FileStatus {
-    raw: Arc<HdfsFileInfoPtr>,
-    idx: u32,
+/// Location of a block of a file, with DataNodes storing its replicas.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BlockLocation {
+    /// Offset of the block within the file
+    pub offset: u64,
+    /// Length of the block
+    pub length: u64,
+    /// Host names of DataNodes holding the block
+    pub hosts: Vec<String>,
+    /// DataNode names, as ```ip:port```
+    pub names: Vec<String>,
+    /// Network topology paths of DataNodes
+    pub topology_paths: Vec<String>,
+    /// Is the block corrupt
+    pub corrupt: bool,
 }
 
-impl FileStatus {
-    #[inline]
-    /// create FileStatus from *const hdfsFileInfo
-    fn new(ptr: *mut hdfsFileInfo) -> FileStatus {
-        FileStatus {
-            raw: Arc::new(HdfsFileInfoPtr::new(ptr)),
-            idx: 0,
+/// How a file is opened, see [Backend::open]
+pub(crate) enum OpenMode {
+    Read {
+        buf_size: i32,
+    },
+    Write {
+        overwrite: bool,
+        buf_size: i32,
+        replication: i16,
+        block_size: i64,
+    },
+    Append,
+}
+
+/// Filesystem implementation behind [HdfsFs], libhdfs3 or WebHDFS.
+///
+/// Operations report failures as errors, [HdfsFs] keeps its own
+/// signatures on top of them.
+pub(crate) trait Backend: Send + Sync {
+    fn open(&self, path: &str, mode: OpenMode) -> Result<Box<dyn FileBackend + '_>, Error>;
+    fn chmod(&self, path: &str, mode: i16) -> Result<(), Error>;
+    fn chown(&self, path: &str, owner: &str, group: &str) -> Result<(), Error>;
+    fn default_block_size(&self) -> Result<usize, Error>;
+    fn capacity(&self) -> Result<usize, Error>;
+    fn used(&self) -> Result<usize, Error>;
+    fn delete(&self, path: &str, recursive: bool) -> Result<(), Error>;
+    fn exist(&self, path: &str) -> bool;
+    /// Host names of each block overlapping the range
+    fn get_hosts(&self, path: &str, start: u64, length: u64) -> Result<Vec<Vec<String>>, Error>;
+    fn get_block_locations(
+        &self,
+        path: &str,
+        start: u64,
+        length: u64,
+    ) -> Result<Vec<BlockLocation>, Error>;
+    fn mkdir(&self, path: &str) -> Result<(), Error>;
+    /// Times are in seconds, ```-1``` keeps the current value
+    fn set_times(&self, path: &str, mtime: time_t, atime: time_t) -> Result<(), Error>;
+    fn set_replication(&self, path: &str, num: i16) -> Result<(), Error>;
+    fn rename(&self, old_path: &str, new_path: &str) -> Result<(), Error>;
+    fn list_status(&self, path: &str) -> Result<Vec<FileStatus>, Error>;
+    /// Page of ```path``` entries following ```start_after```, with the name
+    /// to continue after if more entries remain. Backends without paged
+    /// listing return all entries as a single page.
+    fn list_status_batch(
+        &self,
+        path: &str,
+        start_after: Option<&str>,
+    ) -> Result<(Vec<FileStatus>, Option<String>), Error> {
+        match start_after {
+            None => Ok((self.list_status(path)?, None)),
+            Some(_) => Ok((Vec::new(), None)),
         }
     }
+    fn working_directory(&self) -> Result<String, Error>;
+    fn get_file_status(&self, path: &str) -> Result<FileStatus, Error>;
 
-    /// create FileStatus from *const hdfsFileInfo which points
-    /// to dynamically allocated array.
-    #[inline]
-    fn from_array(raw: Arc<HdfsFileInfoPtr>, idx: u32) -> FileStatus {
-        FileStatus { raw, idx }
+    /// libhdfs3 handle of the filesystem, null for other backends
+    #[cfg(feature = "libhdfs3")]
+    fn raw(&self) -> hdfsFS {
+        std::ptr::null_mut()
     }
+}
 
-    #[inline]
-    fn ptr(&self) -> *const hdfsFileInfo {
-        unsafe { self.raw.ptr.offset(self.idx as isize) }
-    }
+/// File opened by [Backend::open], behind [HdfsFile]
+pub(crate) trait FileBackend {
+    fn available(&self) -> Result<(), Error>;
+    /// Called once, by [HdfsFile::close] or when [HdfsFile] is dropped
+    fn close(&mut self) -> Result<(), Error>;
+    fn flush(&mut self) -> Result<(), Error>;
+    fn hflush(&mut self) -> Result<(), Error>;
+    fn is_readable(&self) -> bool;
+    fn is_writable(&self) -> bool;
+    fn pos(&self) -> Result<u64, Error>;
+    fn read(&self, buf: &mut [u8]) -> Result<usize, Error>;
+    fn seek(&self, offset: u64) -> Result<(), Error>;
+    fn write(&mut self, buf: &[u8]) -> Result<usize, Error>;
+    fn sync(&mut self) -> Result<(), Error>;
+}
 
+/// Interface that represents the client side information for a file or directory.
+#[derive(Debug, Clone)]
+pub struct FileStatus {
+    pub(crate) name: String,
+    pub(crate) kind: FileKind,
+    pub(crate) owner: String,
+    pub(crate) group: String,
+    pub(crate) permission: i16,
+    pub(crate) len: usize,
+    pub(crate) block_size: usize,
+    pub(crate) replication: i16,
+    pub(crate) last_modified: time_t,
+    pub(crate) last_access: time_t,
+}
+
+impl FileStatus {
     /// Get the name of the file
     #[inline]
     pub fn name(&self) -> &str {
-        from_raw!((*self.ptr()).mName)
+        &self.name
     }
 
     /// Is this a file?
     #[inline]
     pub fn is_file(&self) -> bool {
-        match unsafe { &*self.ptr() }.mKind {
-            tObjectKind::kObjectKindFile => true,
-            tObjectKind::kObjectKindDirectory => false,
-        }
+        self.kind == FileKind::File
     }
 
     /// Is this a directory?
     #[inline]
     pub fn is_directory(&self) -> bool {
-        match unsafe { &*self.ptr() }.mKind {
-            tObjectKind::kObjectKindFile => false,
-            tObjectKind::kObjectKindDirectory => true,
-        }
+        self.kind == FileKind::Directory
     }
 
     /// Get the owner of the file
     #[inline]
     pub fn owner(&self) -> &str {
-        from_raw!((*self.ptr()).mOwner)
+        &self.owner
     }
 
     /// Get the group associated with the file
     #[inline]
     pub fn group(&self) -> &str {
-        from_raw!((*self.ptr()).mGroup)
+        &self.group
     }
 
     /// Get the permissions associated with the file
     #[inline]
     pub fn permission(&self) -> i16 {
-        unsafe { &*self.ptr() }.mPermissions
+        self.permission
+    }
+
+    /// Permission as [FsPermission]
+    pub fn fs_permission(&self) -> FsPermission {
+        FsPermission::from(self.permission())
     }
 
     /// Get the length of this file, in bytes.
     #[inline]
     #[allow(clippy::len_without_is_empty)]
     pub fn len(&self) -> usize {
-        unsafe { &*self.ptr() }.mSize as usize
+        self.len
     }
 
     /// Get the block size of the file.
     #[inline]
     pub fn block_size(&self) -> usize {
-        unsafe { &*self.ptr() }.mBlockSize as usize
+        self.block_size
     }
 
     /// Get the replication factor of a file.
     #[inline]
     pub fn replica_count(&self) -> i16 {
-        unsafe { &*self.ptr() }.mReplication
+        self.replication
     }
 
     /// Get the last modification time for the file in seconds
     #[inline]
     pub fn last_modified(&self) -> time_t {
-        unsafe { &*self.ptr() }.mLastMod
+        self.last_modified
     }
 
     /// Get the last access time for the file in seconds
     #[inline]
     pub fn last_accced(&self) -> time_t {
-        unsafe { &*self.ptr() }.mLastAccess
+        self.last_access
+    }
+
+    /// Create owned snapshot of this status
+    #[inline]
+    pub fn to_metadata(&self) -> FileMetadata {
+        FileMetadata::from(self)
     }
 }
 
@@ -172,18 +294,7 @@ impl FileStatus {
 // #[derive(Clone)]
 pub struct HdfsFs {
     pub url: String,
-    raw: hdfsFS,
-}
-
-unsafe impl Send for HdfsFs {}
-unsafe impl Sync for HdfsFs {}
-
-impl Drop for HdfsFs {
-    fn drop(&mut self) {
-        unsafe {
-            hdfsDisconnect(self.raw);
-        }
-    }
+    backend: Box<dyn Backend>,
 }
 
 impl Debug for HdfsFs {
@@ -195,15 +306,19 @@ impl Debug for HdfsFs {
 impl HdfsFs {
     /// create HdfsFs instance. Please use HdfsFsCache rather than using this API directly.
     #[inline]
-    pub(crate) fn new(url: String, raw: hdfsFS) -> HdfsFs {
-        HdfsFs { url, raw }
+    pub(crate) fn new(url: String, backend: Box<dyn Backend>) -> HdfsFs {
+        HdfsFs { url, backend }
     }
 
     /// Do not use, as it might leak reference to hdfsFs
-    /// its just there temporary
+    /// its just there temporary. Null if the filesystem is not
+    /// served by libhdfs3. Available with ```libhdfs3``` feature, when
+    /// ```dlopen``` is enabled as well the handle is created by the library
+    /// loaded at runtime and must only be passed to that library.
+    #[cfg(feature = "libhdfs3")]
     #[inline]
     pub fn raw(&self) -> hdfsFS {
-        self.raw
+        self.backend.raw()
     }
 
     /// Get HDFS name node url
@@ -218,26 +333,93 @@ impl HdfsFs {
             return Err(ErrorKind::NotFound.into());
         }
 
-        let file = unsafe { hdfsOpenFile(self.raw, to_raw!(path), HDFS_APPEND, 0, 0, 0) };
+        self.open_file(path, OpenMode::Append)
+    }
 
-        if file.is_null() {
-            Err(Error::last_os_error())
-        } else {
-            Ok(HdfsFile {
-                fs: self,
-                path: path.to_owned(),
-                file,
-            })
-        }
+    fn open_file(&self, path: &str, mode: OpenMode) -> Result<HdfsFile<'_>, Error> {
+        let file = self.backend.open(path, mode)?;
+
+        Ok(HdfsFile {
+            fs: self,
+            path: path.to_owned(),
+            file,
+            closed: false,
+        })
     }
 
     /// set permission
     pub fn chmod(&self, path: &str, mode: i16) -> bool {
-        (unsafe { hdfsChmod(self.raw, to_raw!(path), mode as c_short) }) == 0
+        self.try_chmod(path, mode).is_ok()
+    }
+
+    /// set permission, failure is returned as the error of the filesystem
+    pub fn try_chmod(&self, path: &str, mode: i16) -> Result<(), Error> {
+        self.backend.chmod(path, mode)
+    }
+
+    /// Set permission of a path
+    pub fn set_permission(&self, path: &str, permission: FsPermission) -> bool {
+        self.chmod(path, permission.into())
+    }
+
+    /// Set permission of a path, see [HdfsFs::try_chmod]
+    pub fn try_set_permission(&self, path: &str, permission: FsPermission) -> Result<(), Error> {
+        self.try_chmod(path, permission.into())
     }
 
     pub fn chown(&self, path: &str, owner: &str, group: &str) -> bool {
-        (unsafe { hdfsChown(self.raw, to_raw!(path), to_raw!(owner), to_raw!(group)) }) == 0
+        self.try_chown(path, owner, group).is_ok()
+    }
+
+    /// change owner and group, failure is returned as the error of the filesystem
+    pub fn try_chown(&self, path: &str, owner: &str, group: &str) -> Result<(), Error> {
+        self.backend.chown(path, owner, group)
+    }
+
+    /// set permission of ```path``` and all its descendants.
+    ///
+    /// Failures do not abort the operation, they are collected in the report.
+    pub fn chmod_recursive(
+        &self,
+        path: &str,
+        mode: i16,
+        opts: &RecursiveOptions,
+    ) -> RecursiveReport {
+        apply_recursive(self, path, Operation::Chmod(mode), opts)
+    }
+
+    /// apply octal or symbolic ```mode``` to ```path``` and all its
+    /// descendants, as ```hadoop fs -chmod -R``` does, see
+    /// [FsPermission::apply_mode].
+    ///
+    /// Invalid mode is returned as error, other failures do not abort the
+    /// operation, they are collected in the report.
+    pub fn chmod_mode_recursive(
+        &self,
+        path: &str,
+        mode: &str,
+        opts: &RecursiveOptions,
+    ) -> Result<RecursiveReport, Error> {
+        FsPermission::file_default().apply_mode(mode, false)?;
+        Ok(apply_recursive(
+            self,
+            path,
+            Operation::ApplyMode(mode),
+            opts,
+        ))
+    }
+
+    /// change owner and group of ```path``` and all its descendants.
+    ///
+    /// Failures do not abort the operation, they are collected in the report.
+    pub fn chown_recursive(
+        &self,
+        path: &str,
+        owner: &str,
+        group: &str,
+        opts: &RecursiveOptions,
+    ) -> RecursiveReport {
+        apply_recursive(self, path, Operation::Chown(owner, group), opts)
     }
 
     #[inline]
@@ -253,6 +435,18 @@ impl HdfsFs {
     ) -> Result<HdfsFile<'_>, Error> {
         self.create_with_params(path, overwrite, 0, 0, 0)
     }
+
+    /// Create a file with ```block_size``` instead of the default
+    /// block size of the filesystem.
+    #[inline]
+    pub fn create_with_block_size(
+        &self,
+        path: &str,
+        overwrite: bool,
+        block_size: usize,
+    ) -> Result<HdfsFile<'_>, Error> {
+        self.create_with_params(path, overwrite, 0, 0, block_size as i64)
+    }
     // changed visibility to private as buf size has not been respected
     // in the library
     fn create_with_params(
@@ -263,91 +457,90 @@ impl HdfsFs {
         replica_num: i16,
         block_size: i64,
     ) -> Result<HdfsFile<'_>, Error> {
-        if !overwrite && self.exist(path) {
-            return Err(ErrorKind::AlreadyExists.into());
-        }
-
-        let file = unsafe {
-            hdfsOpenFile(
-                self.raw,
-                to_raw!(path),
-                HDFS_WRITE,
-                buf_size as c_int,
-                replica_num as c_short,
-                block_size,
-            )
+        let mode = OpenMode::Write {
+            overwrite,
+            buf_size,
+            replication: replica_num,
+            block_size,
         };
-
-        if file.is_null() {
-            Err(Error::last_os_error())
-        } else {
-            Ok(HdfsFile {
-                fs: self,
-                path: path.to_owned(),
-                file,
-            })
-        }
+        self.open_file(path, mode)
     }
 
     /// Get the default block size.
     pub fn default_block_size(&self) -> Result<usize, Error> {
-        let block_sz = unsafe { hdfsGetDefaultBlockSize(self.raw) };
-
-        if block_sz < 0 {
-            Err(Error::last_os_error())
-        } else {
-            Ok(block_sz as usize)
-        }
+        self.backend.default_block_size()
     }
 
     /// Return the raw capacity of the filesystem.
     pub fn capacity(&self) -> Result<usize, Error> {
-        let block_sz = unsafe { hdfsGetCapacity(self.raw) };
+        self.backend.capacity()
+    }
 
-        if block_sz < 0 {
-            Err(Error::last_os_error())
-        } else {
-            Ok(block_sz as usize)
-        }
+    /// Return capacity, usage and defaults of the filesystem in one call.
+    pub fn status(&self) -> Result<FsStatus, Error> {
+        FsStatus::new(self)
     }
 
     /// Delete file.
     pub fn delete(&self, path: &str, recursive: bool) -> Result<bool, Error> {
-        let res = unsafe { hdfsDelete(self.raw, to_raw!(path), recursive as c_int) };
+        self.backend.delete(path, recursive).map(|_| true)
+    }
 
-        if res == 0 {
-            Ok(true)
-        } else {
-            Err(Error::last_os_error())
+    /// Delete file the way ```hadoop fs -rm``` does.
+    ///
+    /// If trash is enabled (```fs.trash.interval``` is greater than zero) and
+    /// ```skip_trash``` is not set, path is moved to the trash of the current
+    /// user, otherwise it is deleted. Paths already in the trash are deleted.
+    /// See [Trash::delete].
+    pub fn delete_with_trash(
+        &self,
+        path: &str,
+        recursive: bool,
+        skip_trash: bool,
+    ) -> Result<bool, Error> {
+        if !skip_trash {
+            return Trash::new(self)?.delete(path, recursive).map(|_| true);
+        }
+
+        if !recursive && self.get_file_status(path)?.is_directory() {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("[{}] is a directory", path),
+            ));
         }
+        self.delete(path, recursive)
     }
 
     /// Checks if a given path exsits on the filesystem
     pub fn exist(&self, path: &str) -> bool {
-        unsafe { hdfsExists(self.raw, to_raw!(path)) == 0 }
+        self.backend.exist(path)
     }
 
     /// Get hostnames where a particular block (determined by
-    /// pos & blocksize) of a file is stored. The last element in the array
-    /// is NULL. Due to replication, a single block could be present on
-    /// multiple hosts.
+    /// pos & blocksize) of a file is stored. Due to replication,
+    /// a single block could be present on multiple hosts.
     pub fn get_hosts(&self, path: &str, start: usize, length: usize) -> Result<BlockHosts, Error> {
-        let ptr = unsafe { hdfsGetHosts(self.raw, to_raw!(path), start as i64, length as i64) };
+        let blocks = self.backend.get_hosts(path, start as u64, length as u64)?;
 
-        if !ptr.is_null() {
-            Ok(BlockHosts { ptr })
-        } else {
-            Err(Error::last_os_error())
-        }
+        Ok(BlockHosts { blocks })
+    }
+
+    /// Get locations of blocks of a file which overlap
+    /// range ```start``` - ```start + length```.
+    ///
+    /// Empty files have no blocks.
+    pub fn get_block_locations(
+        &self,
+        path: &str,
+        start: u64,
+        length: u64,
+    ) -> Result<Vec<BlockLocation>, Error> {
+        self.backend.get_block_locations(path, start, length)
     }
 
     /// create a directory
     pub fn mkdir(&self, path: &str) -> Result<bool, Error> {
-        if unsafe { hdfsCreateDirectory(self.raw, to_raw!(path)) } == 0 {
-            Ok(true)
-        } else {
-            Err(Error::last_os_error())
-        }
+        self.backend.mkdir(path).map(|_| true)
     }
 
     /// open a file to read
@@ -359,90 +552,134 @@ impl HdfsFs {
     // in the library
     /// open a file to read with a buffer size
     fn open_with_bufsize(&self, path: &str, buf_size: i32) -> Result<HdfsFile<'_>, Error> {
-        let file =
-            unsafe { hdfsOpenFile(self.raw, to_raw!(path), HDFS_READ, buf_size as c_int, 0, 0) };
+        self.open_file(path, OpenMode::Read { buf_size })
+    }
 
-        if file.is_null() {
-            Err(Error::last_os_error())
-        } else {
-            Ok(HdfsFile {
-                fs: self,
-                path: path.to_owned(),
-                file,
-            })
-        }
+    /// Set modification and access time of a path, times which
+    /// are ```None``` are left unchanged.
+    ///
+    /// Times are truncated to seconds.
+    pub fn set_times(
+        &self,
+        path: &str,
+        mtime: Option<SystemTime>,
+        atime: Option<SystemTime>,
+    ) -> Result<bool, Error> {
+        // -1 keeps the current value
+        let mtime = mtime.map_or(Ok(-1), from_system_time)?;
+        let atime = atime.map_or(Ok(-1), from_system_time)?;
+
+        self.backend.set_times(path, mtime, atime).map(|_| true)
     }
 
     /// Set the replication of the specified file to the supplied value
     pub fn set_replication(&self, path: &str, num: i16) -> Result<bool, Error> {
-        let res = unsafe { hdfsSetReplication(self.raw, to_raw!(path), num) };
+        self.backend.set_replication(path, num).map(|_| true)
+    }
 
-        if res == 0 {
-            Ok(true)
-        } else {
-            Err(Error::last_os_error())
-        }
+    /// Set the replication of all files under ```path```.
+    ///
+    /// Failures do not abort the operation, they are collected in the report.
+    pub fn set_replication_recursive(
+        &self,
+        path: &str,
+        num: i16,
+        opts: &RecursiveOptions,
+    ) -> RecursiveReport {
+        apply_recursive(self, path, Operation::SetReplication(num), opts)
     }
 
     /// Rename file.
     pub fn rename(&self, old_path: &str, new_path: &str) -> Result<bool, Error> {
-        let res = unsafe { hdfsRename(self.raw, to_raw!(old_path), to_raw!(new_path)) };
+        self.backend.rename(old_path, new_path).map(|_| true)
+    }
 
-        if res == 0 {
-            Ok(true)
-        } else {
-            Err(Error::last_os_error())
-        }
+    /// Summarise length, number of files and directories and space consumed
+    /// of a file or directory tree, as ```hadoop fs -count``` does.
+    ///
+    /// Quotas are not available through libhdfs3 and are always ```None```.
+    pub fn content_summary(&self, path: &str) -> Result<ContentSummary, Error> {
+        crate::summary::content_summary(self, path)
+    }
+
+    /// Summary of each child of a directory, as ```hadoop fs -du``` does.
+    /// Returns summary of the file itself if ```path``` is a file.
+    pub fn du(&self, path: &str) -> Result<Vec<(String, ContentSummary)>, Error> {
+        crate::summary::du(self, path)
     }
 
     /// Return the total raw size of all files in the filesystem.
     pub fn used(&self) -> Result<usize, Error> {
-        let block_sz = unsafe { hdfsGetUsed(self.raw) };
-
-        if block_sz < 0 {
-            Err(Error::last_os_error())
-        } else {
-            Ok(block_sz as usize)
-        }
+        self.backend.used()
     }
 
+    /// List content of directory ```path```.
+    ///
+    /// All entries are fetched before it returns, see
+    /// [HdfsFs::list_status_iter] for a paged listing.
     pub fn list_status(&self, path: &str) -> Result<Vec<FileStatus>, Error> {
-        let mut entry_num: c_int = 0;
+        self.backend.list_status(path)
+    }
 
-        let ptr = unsafe { hdfsListDirectory(self.raw, to_raw!(path), &mut entry_num) };
+    /// Lazily list ```path```, entries are fetched page by page as the
+    /// iterator advances, see [ListStatus].
+    pub fn list_status_iter(&self, path: &str) -> ListStatus<'_> {
+        ListStatus::new(self, path)
+    }
 
-        if ptr.is_null() {
-            return Err(Error::last_os_error());
-        }
+    /// Single page of [HdfsFs::list_status_iter]
+    pub(crate) fn list_status_batch(
+        &self,
+        path: &str,
+        start_after: Option<&str>,
+    ) -> Result<(Vec<FileStatus>, Option<String>), Error> {
+        self.backend.list_status_batch(path, start_after)
+    }
 
-        let shared_ptr = Arc::new(HdfsFileInfoPtr::new_array(ptr, entry_num));
+    /// Return all files and directories matching a Hadoop glob ```pattern```
+    /// such as ```/data/2023-0[1-6]-*/{events,clicks}/part-*```.
+    ///
+    /// Only directories needed to resolve wildcard components are listed.
+    /// Result is sorted by path and is empty if nothing matches.
+    /// See [crate::glob] for supported syntax.
+    pub fn glob(&self, pattern: &str) -> Result<Vec<FileStatus>, Error> {
+        crate::glob::glob(self, pattern)
+    }
 
-        let mut list = Vec::new();
-        for idx in 0..entry_num {
-            list.push(FileStatus::from_array(shared_ptr.clone(), idx as u32));
-        }
+    /// Get the current working directory, ```/user/<user>``` unless changed
+    pub fn working_directory(&self) -> Result<String, Error> {
+        self.backend.working_directory()
+    }
 
-        Ok(list)
+    /// Recursively walk directory tree starting at ```path```.
+    ///
+    /// Returned iterator can be configured further, see [Walk].
+    pub fn walk(&self, path: &str) -> Walk<'_> {
+        Walk::new(self, path)
     }
 
-    pub fn get_file_status(&self, path: &str) -> Result<FileStatus, Error> {
-        let ptr = unsafe { hdfsGetPathInfo(self.raw, to_raw!(path)) };
+    /// Follow a growing file, as ```tail -f``` does, see [Follower].
+    pub fn follow(&self, path: &str, opts: &FollowOptions) -> Result<Follower<'_>, Error> {
+        Follower::new(self, path, opts)
+    }
 
-        if ptr.is_null() {
-            Err(Error::last_os_error())
-        } else {
-            Ok(FileStatus::new(ptr))
-        }
+    /// Plan locality aware input splits, see [InputSplits].
+    pub fn input_splits(&self) -> InputSplits<'_> {
+        InputSplits::new(self)
     }
 
-    pub fn get_last_error() -> &'static str {
-        let char_ptr = unsafe { libhdfs3_sys::hdfsGetLastError() };
+    pub fn get_file_status(&self, path: &str) -> Result<FileStatus, Error> {
+        self.backend.get_file_status(path)
+    }
 
-        if !char_ptr.is_null() {
-            from_raw!(char_ptr)
-        } else {
-            ""
-        }
+    /// Last error message of libhdfs3 on the calling thread,
+    /// empty without ```libhdfs3``` or ```dlopen``` feature
+    pub fn get_last_error() -> &'static str {
+        #[cfg(any(feature = "libhdfs3", feature = "dlopen"))]
+        let error = crate::native::last_error();
+        #[cfg(not(any(feature = "libhdfs3", feature = "dlopen")))]
+        let error = "";
+        error
     }
 }
 
@@ -450,16 +687,26 @@ impl HdfsFs {
 pub struct HdfsFile<'a> {
     fs: &'a HdfsFs,
     path: String,
-    file: hdfsFile,
+    file: Box<dyn FileBackend + 'a>,
+    /// set once the file has been closed by [HdfsFile::close]
+    closed: bool,
 }
 
 impl<'a> Drop for HdfsFile<'a> {
     fn drop(&mut self) {
-        if self.is_writable() {
-            self.flush();
+        // already closed by close
+        if self.closed {
+            return;
+        }
+        let flushed = if self.is_writable() {
+            self.file.flush()
+        } else {
+            Ok(())
+        };
+        // errors can not be returned from drop, use close to check them
+        if let Err(e) = flushed.and(self.file.close()) {
+            warn!("closing [{}] on drop failed: {}", self.path, e);
         }
-        // this is due to clippy suggestion
-        let _ = self.close();
     }
 }
 
@@ -468,48 +715,58 @@ impl<'a> Debug for HdfsFile<'a> {
         f.debug_struct("HdfsFile")
             .field("fs", &self.fs)
             .field("path", &self.path)
-            .field("file", &self.file)
             .finish()
     }
 }
 
 impl<'a> HdfsFile<'a> {
     pub fn available(&self) -> Result<bool, Error> {
-        if unsafe { hdfsAvailable(self.fs.raw, self.file) } == 0 {
-            Ok(true)
-        } else {
-            Err(Error::last_os_error())
-        }
+        self.file.available().map(|_| true)
     }
 
-    /// Close the opened file
-    fn close(&self) -> Result<bool, Error> {
-        if unsafe { hdfsCloseFile(self.fs.raw, self.file) } == 0 {
-            Ok(true)
+    /// Close the file, flushing written data first. Unlike dropping the
+    /// file, failure to flush or close is returned, which should be checked
+    /// before data is considered written.
+    pub fn close(mut self) -> Result<(), Error> {
+        let flushed = if self.is_writable() {
+            self.file.flush()
         } else {
-            Err(Error::last_os_error())
-        }
+            Ok(())
+        };
+        let closed = self.file.close();
+        self.closed = true;
+        flushed.and(closed)
     }
 
     /// Flush the data.
     pub fn flush(&mut self) -> bool {
-        (unsafe { hdfsFlush(self.fs.raw, self.file) }) == 0
+        self.try_flush().is_ok()
+    }
+
+    /// Flush the data, failure is returned as the error of the filesystem
+    pub fn try_flush(&mut self) -> Result<(), Error> {
+        self.file.flush()
     }
 
     /// Flush out the data in client's user buffer. After the return of this
     /// call, new readers will see the data.
     pub fn hflush(&mut self) -> bool {
-        (unsafe { hdfsHFlush(self.fs.raw, self.file) }) == 0
+        self.try_hflush().is_ok()
+    }
+
+    /// See [HdfsFile::hflush], failure is returned as the error of the filesystem
+    pub fn try_hflush(&mut self) -> Result<(), Error> {
+        self.file.hflush()
     }
 
     /// Determine if a file is open for read.
     pub fn is_readable(&self) -> bool {
-        (unsafe { hdfsFileIsOpenForRead(self.file) }) == 1
+        self.file.is_readable()
     }
 
     /// Determine if a file is open for write.
     pub fn is_writable(&self) -> bool {
-        (unsafe { hdfsFileIsOpenForWrite(self.file) }) == 1
+        self.file.is_writable()
     }
 
     /// Return a file path
@@ -519,59 +776,24 @@ impl<'a> HdfsFile<'a> {
 
     /// Get the current offset in the file, in bytes.
     pub fn pos(&self) -> Result<u64, Error> {
-        let pos = unsafe { hdfsTell(self.fs.raw, self.file) };
-
-        if pos < 0 {
-            Err(Error::last_os_error())
-        } else {
-            Ok(pos as u64)
-        }
+        self.file.pos()
     }
 
     /// Read data from an open file.
     pub fn read(&self, buf: &mut [u8]) -> Result<usize, Error> {
-        let read_len = unsafe {
-            hdfsRead(
-                self.fs.raw,
-                self.file,
-                buf.as_ptr() as *mut c_void,
-                buf.len() as tSize,
-            )
-        };
-
-        if read_len < 0 {
-            Err(Error::last_os_error())
-        } else {
-            Ok(read_len as usize)
-        }
+        self.file.read(buf)
     }
 
     /// Positional read of data from an open file.
     pub fn read_with_pos(&self, pos: i64, buf: &mut [u8]) -> Result<usize, Error> {
-        if self.seek(pos as u64) {
-            self.read(buf)
-        } else {
-            Err(Error::last_os_error())
-        }
+        self.file.seek(pos as u64)?;
+        self.read(buf)
     }
 
     /// Read data from an open file.
     pub fn read_length(&self, buf: &mut [u8], length: usize) -> Result<usize, Error> {
         let required_len = min(length, buf.len());
-        let read_len = unsafe {
-            hdfsRead(
-                self.fs.raw,
-                self.file,
-                buf.as_ptr() as *mut c_void,
-                required_len as tSize,
-            )
-        };
-
-        if read_len < 0 {
-            Err(Error::last_os_error())
-        } else {
-            Ok(read_len as usize)
-        }
+        self.file.read(&mut buf[..required_len])
     }
 
     /// Positional read of data from an open file.
@@ -581,59 +803,27 @@ impl<'a> HdfsFile<'a> {
         buf: &mut [u8],
         length: usize,
     ) -> Result<usize, Error> {
-        let required_len = min(length, buf.len());
-
-        if !self.seek(pos as u64) {
-            return Err(Error::last_os_error());
-        }
-
-        let read_len = unsafe {
-            hdfsRead(
-                self.fs.raw,
-                self.file,
-                buf.as_ptr() as *mut c_void,
-                required_len as tSize,
-            )
-        };
-
-        if read_len < 0 {
-            Err(Error::last_os_error())
-        } else {
-            Ok(read_len as usize)
-        }
+        self.file.seek(pos as u64)?;
+        self.read_length(buf, length)
     }
 
     /// Seek to given offset in file.
     pub fn seek(&self, offset: u64) -> bool {
-        (unsafe { hdfsSeek(self.fs.raw, self.file, offset as tOffset) }) == 0
+        self.try_seek(offset).is_ok()
+    }
+
+    /// Seek to given offset in file, failure is returned as the error of the filesystem
+    pub fn try_seek(&self, offset: u64) -> Result<(), Error> {
+        self.file.seek(offset)
     }
 
     /// Write data into an open file.
     pub fn write(&mut self, buf: &[u8]) -> Result<usize, Error> {
-        let written_len = unsafe {
-            hdfsWrite(
-                self.fs.raw,
-                self.file,
-                buf.as_ptr() as *mut c_void,
-                buf.len() as tSize,
-            )
-        };
-
-        if written_len < 0 {
-            Err(Error::last_os_error())
-        } else {
-            Ok(written_len as usize)
-        }
+        self.file.write(buf)
     }
 
     pub fn sync(&mut self) -> Result<(), Error> {
-        let written_len = unsafe { hdfsSync(self.fs.raw, self.file) };
-
-        if written_len < 0 {
-            Err(Error::last_os_error())
-        } else {
-            Ok(())
-        }
+        self.file.sync()
     }
 
     pub fn get_file_status(&self) -> Result<FileStatus, Error> {
@@ -647,8 +837,7 @@ impl<'a> Write for HdfsFile<'a> {
     }
 
     fn flush(&mut self) -> std::io::Result<()> {
-        HdfsFile::flush(self);
-        Ok(())
+        self.try_flush()
     }
 }
 
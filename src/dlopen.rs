@@ -0,0 +1,306 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Runtime loading of libhdfs3, enabled by ```dlopen``` feature
+//!
+//! Instead of calling functions linked from ```libhdfs3_sys```, the crate
+//! opens libhdfs3 with ```dlopen``` on first use, so binaries for which HDFS
+//! is optional start on machines without the library. Library is opened
+//! from the path given to [set_library_path], otherwise from
+//! ```LIBHDFS3_LIBRARY``` environment variable, otherwise by its platform
+//! name (```libhdfs3.so``` on Linux) from the library search path.
+//!
+//! Every function the crate calls is resolved when the library is loaded.
+//! Functions which call libhdfs3 before a filesystem is connected load it
+//! first: [crate::HdfsRegistry::get] and [crate::HdfsBuilder] report a
+//! library which can not be loaded as an error, configuration getters of
+//! [crate::HdfsUtil] return ```None``` and [crate::HdfsFs::get_last_error]
+//! an empty message. Failed loads are not cached, loading can be retried
+//! after the path is corrected.
+//!
+//! Types of libhdfs3 API are declared here, so the crate does not depend
+//! on ```libhdfs3_sys``` and libhdfs3 is not needed to build or link it
+//! when built with ```--no-default-features --features dlopen```. With
+//! ```libhdfs3``` feature also enabled functions are still loaded at
+//! runtime, [crate::raw] and [crate::HdfsFs::raw] are not available.
+//!
+//! ```ignore
+//! hdfs_native::dlopen::set_library_path(Some("/opt/hadoop/lib/libhdfs3.so"));
+//! let fs = HdfsRegistry::new().get("hdfs://localhost:9000")?;
+//! ```
+
+use libc::{c_char, c_int, c_short, c_void, time_t};
+use libloading::Library;
+use log::info;
+use std::io::{Error, ErrorKind};
+use std::sync::{Mutex, OnceLock};
+
+// types of libhdfs3 hdfs.h, as declared by libhdfs3_sys
+
+#[allow(non_camel_case_types)]
+pub(crate) type tSize = i32;
+#[allow(non_camel_case_types)]
+pub(crate) type tTime = time_t;
+#[allow(non_camel_case_types)]
+pub(crate) type tOffset = i64;
+#[allow(non_camel_case_types)]
+pub(crate) type tPort = u16;
+#[allow(non_camel_case_types)]
+pub(crate) type hdfsFS = *mut HdfsFileSystemInternalWrapper;
+#[allow(non_camel_case_types)]
+pub(crate) type hdfsFile = *mut HdfsFileInternalWrapper;
+
+pub(crate) const HDFS_READ: c_int = 0;
+pub(crate) const HDFS_WRITE: c_int = 1;
+pub(crate) const HDFS_APPEND: c_int = 1024;
+
+#[repr(C)]
+pub(crate) struct HdfsFileSystemInternalWrapper {
+    _private: [u8; 0],
+}
+
+#[repr(C)]
+pub(crate) struct HdfsFileInternalWrapper {
+    _private: [u8; 0],
+}
+
+#[allow(non_camel_case_types)]
+#[repr(C)]
+pub(crate) struct hdfsBuilder {
+    _private: [u8; 0],
+}
+
+// variants are constructed by libhdfs3
+#[allow(non_camel_case_types, clippy::enum_variant_names, dead_code)]
+#[repr(u32)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub(crate) enum tObjectKind {
+    kObjectKindFile = b'F' as u32,
+    kObjectKindDirectory = b'D' as u32,
+}
+
+#[allow(non_camel_case_types, non_snake_case)]
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct hdfsFileInfo {
+    pub mKind: tObjectKind,
+    pub mName: *mut c_char,
+    pub mLastMod: tTime,
+    pub mSize: tOffset,
+    pub mReplication: c_short,
+    pub mBlockSize: tOffset,
+    pub mOwner: *mut c_char,
+    pub mGroup: *mut c_char,
+    pub mPermissions: c_short,
+    pub mLastAccess: tTime,
+}
+
+#[allow(non_snake_case)]
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct BlockLocation {
+    pub corrupt: c_int,
+    pub numOfNodes: c_int,
+    pub hosts: *mut *mut c_char,
+    pub names: *mut *mut c_char,
+    pub topologyPaths: *mut *mut c_char,
+    pub length: tOffset,
+    pub offset: tOffset,
+}
+
+/// Environment variable with the path of libhdfs3
+pub const LIBRARY_ENV: &str = "LIBHDFS3_LIBRARY";
+
+static API: OnceLock<Api> = OnceLock::new();
+
+/// Path set by [set_library_path], its lock serializes loading
+static LIBRARY_PATH: Mutex<Option<String>> = Mutex::new(None);
+
+/// Load libhdfs3 from ```path```, ```None``` restores lookup by
+/// ```LIBHDFS3_LIBRARY``` or the default name. Has no effect once
+/// the library is loaded.
+pub fn set_library_path(path: Option<&str>) {
+    *LIBRARY_PATH.lock().unwrap() = path.map(str::to_string);
+}
+
+/// Load libhdfs3 unless it is already loaded.
+pub fn load_library() -> Result<(), Error> {
+    api().map(|_| ())
+}
+
+/// Has libhdfs3 been loaded?
+pub fn is_loaded() -> bool {
+    API.get().is_some()
+}
+
+fn api() -> Result<&'static Api, Error> {
+    if let Some(api) = API.get() {
+        return Ok(api);
+    }
+    let configured = LIBRARY_PATH.lock().unwrap();
+    if let Some(api) = API.get() {
+        return Ok(api);
+    }
+
+    let path = configured
+        .clone()
+        .or_else(|| std::env::var(LIBRARY_ENV).ok())
+        .unwrap_or_else(|| {
+            libloading::library_filename("hdfs3")
+                .to_string_lossy()
+                .into_owned()
+        });
+    let api = Api::load(&path)?;
+    info!("libhdfs3 loaded from [{}]", path);
+    Ok(API.get_or_init(|| api))
+}
+
+/// Loaded library, every caller of libhdfs3 functions loads it first
+/// or holds a filesystem connected by the loaded library
+fn loaded() -> &'static Api {
+    API.get()
+        .expect("libhdfs3 functions are called after load_library")
+}
+
+macro_rules! libhdfs3_api {
+    ($(fn $name:ident($($arg:ident: $ty:ty),* $(,)?) $(-> $ret:ty)?;)*) => {
+        /// Functions resolved from the loaded library
+        #[allow(non_snake_case)]
+        struct Api {
+            $($name: unsafe extern "C" fn($($ty),*) $(-> $ret)?,)*
+            _library: Library,
+        }
+
+        impl Api {
+            #[allow(non_snake_case)]
+            fn load(path: &str) -> Result<Api, Error> {
+                let library = unsafe { Library::new(path) }.map_err(|e| {
+                    Error::new(
+                        ErrorKind::NotFound,
+                        format!(
+                            "libhdfs3 could not be loaded from [{}], set its location with {}: {}",
+                            path, LIBRARY_ENV, e
+                        ),
+                    )
+                })?;
+                $(
+                    let $name = unsafe {
+                        library.get::<unsafe extern "C" fn($($ty),*) $(-> $ret)?>(
+                            concat!(stringify!($name), "\0").as_bytes(),
+                        )
+                    }
+                    .map(|symbol| *symbol)
+                    .map_err(|e| {
+                        Error::new(
+                            ErrorKind::InvalidData,
+                            format!(
+                                "library [{}] does not provide {}: {}",
+                                path,
+                                stringify!($name),
+                                e
+                            ),
+                        )
+                    })?;
+                )*
+                Ok(Api {
+                    $($name,)*
+                    _library: library,
+                })
+            }
+        }
+
+        $(
+            #[allow(non_snake_case)]
+            pub(crate) unsafe fn $name($($arg: $ty),*) $(-> $ret)? {
+                (loaded().$name)($($arg),*)
+            }
+        )*
+    };
+}
+
+libhdfs3_api! {
+    fn hdfsAvailable(fs: hdfsFS, file: hdfsFile) -> c_int;
+    fn hdfsBuilderConnect(bld: *mut hdfsBuilder) -> hdfsFS;
+    fn hdfsBuilderSetNameNode(bld: *mut hdfsBuilder, nn: *const c_char);
+    fn hdfsBuilderSetNameNodePort(bld: *mut hdfsBuilder, port: tPort);
+    fn hdfsChmod(fs: hdfsFS, path: *const c_char, mode: c_short) -> c_int;
+    fn hdfsChown(
+        fs: hdfsFS,
+        path: *const c_char,
+        owner: *const c_char,
+        group: *const c_char,
+    ) -> c_int;
+    fn hdfsCloseFile(fs: hdfsFS, file: hdfsFile) -> c_int;
+    fn hdfsConfGetInt(key: *const c_char, val: *mut i32) -> c_int;
+    fn hdfsConfGetStr(key: *const c_char, val: *mut *mut c_char) -> c_int;
+    fn hdfsConfStrFree(val: *mut c_char);
+    fn hdfsConnectAsUserNewInstance(nn: *const c_char, port: tPort, user: *const c_char) -> hdfsFS;
+    fn hdfsConnectNewInstance(nn: *const c_char, port: tPort) -> hdfsFS;
+    fn hdfsCreateDirectory(fs: hdfsFS, path: *const c_char) -> c_int;
+    fn hdfsDelete(fs: hdfsFS, path: *const c_char, recursive: c_int) -> c_int;
+    fn hdfsDisconnect(fs: hdfsFS) -> c_int;
+    fn hdfsExists(fs: hdfsFS, path: *const c_char) -> c_int;
+    fn hdfsFileIsOpenForRead(file: hdfsFile) -> c_int;
+    fn hdfsFileIsOpenForWrite(file: hdfsFile) -> c_int;
+    fn hdfsFlush(fs: hdfsFS, file: hdfsFile) -> c_int;
+    fn hdfsFreeBuilder(bld: *mut hdfsBuilder);
+    fn hdfsFreeFileBlockLocations(locations: *mut BlockLocation, num_of_block: c_int);
+    fn hdfsFreeFileInfo(infos: *mut hdfsFileInfo, num_entries: c_int);
+    fn hdfsFreeHosts(block_hosts: *mut *mut *mut c_char);
+    fn hdfsGetCapacity(fs: hdfsFS) -> tOffset;
+    fn hdfsGetDefaultBlockSize(fs: hdfsFS) -> tOffset;
+    fn hdfsGetFileBlockLocations(
+        fs: hdfsFS,
+        path: *const c_char,
+        start: tOffset,
+        length: tOffset,
+        num_of_block: *mut c_int,
+    ) -> *mut BlockLocation;
+    fn hdfsGetHosts(
+        fs: hdfsFS,
+        path: *const c_char,
+        start: tOffset,
+        length: tOffset,
+    ) -> *mut *mut *mut c_char;
+    fn hdfsGetLastError() -> *const c_char;
+    fn hdfsGetPathInfo(fs: hdfsFS, path: *const c_char) -> *mut hdfsFileInfo;
+    fn hdfsGetUsed(fs: hdfsFS) -> tOffset;
+    fn hdfsGetWorkingDirectory(fs: hdfsFS, buffer: *mut c_char, buffer_size: usize) -> *mut c_char;
+    fn hdfsHFlush(fs: hdfsFS, file: hdfsFile) -> c_int;
+    fn hdfsListDirectory(
+        fs: hdfsFS,
+        path: *const c_char,
+        num_entries: *mut c_int,
+    ) -> *mut hdfsFileInfo;
+    fn hdfsNewBuilder() -> *mut hdfsBuilder;
+    fn hdfsOpenFile(
+        fs: hdfsFS,
+        path: *const c_char,
+        flags: c_int,
+        buffer_size: c_int,
+        replication: c_short,
+        blocksize: tOffset,
+    ) -> hdfsFile;
+    fn hdfsRead(fs: hdfsFS, file: hdfsFile, buffer: *mut c_void, length: tSize) -> tSize;
+    fn hdfsRename(fs: hdfsFS, old_path: *const c_char, new_path: *const c_char) -> c_int;
+    fn hdfsSeek(fs: hdfsFS, file: hdfsFile, desired_pos: tOffset) -> c_int;
+    fn hdfsSetReplication(fs: hdfsFS, path: *const c_char, replication: i16) -> c_int;
+    fn hdfsSync(fs: hdfsFS, file: hdfsFile) -> c_int;
+    fn hdfsTell(fs: hdfsFS, file: hdfsFile) -> tOffset;
+    fn hdfsUtime(fs: hdfsFS, path: *const c_char, mtime: tTime, atime: tTime) -> c_int;
+    fn hdfsWrite(fs: hdfsFS, file: hdfsFile, buffer: *const c_void, length: tSize) -> tSize;
+}
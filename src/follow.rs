@@ -0,0 +1,304 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Following of growing files, as ```tail -f``` does
+//!
+//! New data is read only once [FileStatus::len] grows past the current
+//! offset; a reader only sees data which was flushed with ```hflush``` at the
+//! time the file was opened, so the file is reopened when the open handle
+//! does not return anything new.
+//!
+//! HDFS does not expose file identity through libhdfs3, so replacement of a
+//! file is detected if it disappears between two polls, becomes shorter than
+//! the current offset or its modification time goes backwards.
+//!
+//! [FileStatus::len]: crate::FileStatus::len
+
+use crate::dfs::{HdfsFile, HdfsFs};
+use libc::time_t;
+use log::debug;
+use std::io::{Error, ErrorKind};
+use std::time::Duration;
+
+/// Where following starts
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FollowFrom {
+    /// Beginning of the file
+    Start,
+    /// Current end of the file, only new data is returned
+    End,
+    /// Given offset
+    Offset(u64),
+    /// Last given number of bytes, as ```tail``` does
+    LastBytes(u64),
+}
+
+/// Options used by [HdfsFs::follow]
+#[derive(Debug, Clone)]
+pub struct FollowOptions {
+    pub from: FollowFrom,
+    /// How long to wait before checking the file again
+    pub poll_interval: Duration,
+    /// Size of the buffer used to read new data, which is also the maximum
+    /// size of a single [FollowEvent::Data]. Remaining data is returned by
+    /// following polls without waiting.
+    pub buf_size: usize,
+    /// Keep waiting for the file to be recreated after it has been deleted
+    /// or renamed, otherwise iteration ends after [FollowEvent::Deleted].
+    pub retry: bool,
+}
+
+impl Default for FollowOptions {
+    fn default() -> Self {
+        Self {
+            from: FollowFrom::End,
+            poll_interval: Duration::from_secs(1),
+            buf_size: 64 * 1024,
+            retry: true,
+        }
+    }
+}
+
+/// Change of a followed file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FollowEvent {
+    /// New data appended to the file
+    Data(Vec<u8>),
+    /// File became shorter than data already returned, following
+    /// continues from its beginning
+    Truncated,
+    /// File has been deleted or renamed
+    Deleted,
+    /// File appeared again after it has been deleted, or has been replaced
+    /// by an older file, following continues from its beginning
+    Recreated,
+}
+
+/// Follower of a growing file, created by [HdfsFs::follow].
+///
+/// [Follower::poll] checks the file once, iterating blocks until
+/// the next event, waiting ```poll_interval``` between checks.
+///
+/// ```ignore
+/// for line in fs.follow("/logs/app.log", &FollowOptions::default())?.lines() {
+///     println!("{}", line?);
+/// }
+/// ```
+pub struct Follower<'a> {
+    fs: &'a HdfsFs,
+    path: String,
+    opts: FollowOptions,
+    file: Option<HdfsFile<'a>>,
+    offset: u64,
+    /// Modification time seen by the last poll
+    modified: time_t,
+    deleted: bool,
+    finished: bool,
+}
+
+impl<'a> Follower<'a> {
+    pub(crate) fn new(
+        fs: &'a HdfsFs,
+        path: &str,
+        opts: &FollowOptions,
+    ) -> Result<Follower<'a>, Error> {
+        let status = fs.get_file_status(path)?;
+        if status.is_directory() {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("[{}] is a directory", path),
+            ));
+        }
+
+        let len = status.len() as u64;
+        let offset = match opts.from {
+            FollowFrom::Start => 0,
+            FollowFrom::End => len,
+            FollowFrom::Offset(offset) => offset.min(len),
+            FollowFrom::LastBytes(bytes) => len.saturating_sub(bytes),
+        };
+
+        Ok(Follower {
+            fs,
+            path: path.to_string(),
+            opts: opts.clone(),
+            file: None,
+            offset,
+            modified: status.last_modified(),
+            deleted: false,
+            finished: false,
+        })
+    }
+
+    /// Offset of the next byte to be returned
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    /// Check the file once, returns ```None``` if nothing has changed
+    pub fn poll(&mut self) -> Result<Option<FollowEvent>, Error> {
+        let status = match self.fs.get_file_status(&self.path) {
+            Ok(status) => status,
+            Err(e) if e.kind() == ErrorKind::NotFound => {
+                if self.deleted {
+                    return Ok(None);
+                }
+                debug!("followed file [{}] has been deleted", self.path);
+                self.deleted = true;
+                self.file = None;
+                return Ok(Some(FollowEvent::Deleted));
+            }
+            Err(e) => return Err(e),
+        };
+
+        let modified = std::mem::replace(&mut self.modified, status.last_modified());
+        if self.deleted || status.last_modified() < modified {
+            debug!("followed file [{}] has been recreated", self.path);
+            self.deleted = false;
+            self.file = None;
+            self.offset = 0;
+            return Ok(Some(FollowEvent::Recreated));
+        }
+
+        let len = status.len() as u64;
+        if len < self.offset {
+            debug!("followed file [{}] has been truncated", self.path);
+            self.file = None;
+            self.offset = 0;
+            return Ok(Some(FollowEvent::Truncated));
+        }
+        if len == self.offset {
+            return Ok(None);
+        }
+
+        let mut data = self.read_new()?;
+        if data.is_empty() && self.file.is_some() {
+            // open handle does not see data flushed after it has been opened
+            self.file = None;
+            data = self.read_new()?;
+        }
+
+        if data.is_empty() {
+            Ok(None)
+        } else {
+            self.offset += data.len() as u64;
+            Ok(Some(FollowEvent::Data(data)))
+        }
+    }
+
+    /// Read up to ```buf_size``` bytes visible through the current
+    /// (or newly opened) handle
+    fn read_new(&mut self) -> Result<Vec<u8>, Error> {
+        if self.file.is_none() {
+            self.file = Some(self.fs.open(&self.path)?);
+        }
+        let file = self.file.as_ref().unwrap();
+
+        // seek after the end of data visible to the handle is refused,
+        // there is nothing new to read through it then
+        match file.try_seek(self.offset) {
+            Ok(()) => (),
+            Err(e) if is_past_end(&e) => {
+                debug!(
+                    "followed file [{}] has no data visible at [{}]: {}",
+                    self.path, self.offset, e
+                );
+                return Ok(Vec::new());
+            }
+            Err(e) => return Err(e),
+        }
+
+        let mut data = vec![0; self.opts.buf_size.max(1)];
+        let mut len = 0;
+        while len < data.len() {
+            match file.read(&mut data[len..])? {
+                0 => break,
+                read => len += read,
+            }
+        }
+        data.truncate(len);
+        Ok(data)
+    }
+
+    /// Iterate over lines, partial last line is returned once it is
+    /// terminated. Lines are returned without line terminator.
+    pub fn lines(self) -> Lines<'a> {
+        Lines {
+            follower: self,
+            buf: Vec::new(),
+        }
+    }
+}
+
+/// Is ```e``` returned by a seek past the end of data, libhdfs3 reports it
+/// as ```EINVAL``` or as end of stream (```EOVERFLOW```)
+fn is_past_end(e: &Error) -> bool {
+    matches!(e.kind(), ErrorKind::InvalidInput | ErrorKind::UnexpectedEof)
+        || e.raw_os_error() == Some(libc::EOVERFLOW)
+}
+
+impl<'a> Iterator for Follower<'a> {
+    type Item = Result<FollowEvent, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+        loop {
+            match self.poll() {
+                Ok(Some(event)) => {
+                    if event == FollowEvent::Deleted && !self.opts.retry {
+                        self.finished = true;
+                    }
+                    return Some(Ok(event));
+                }
+                Ok(None) => std::thread::sleep(self.opts.poll_interval),
+                Err(e) => return Some(Err(e)),
+            }
+        }
+    }
+}
+
+/// Iterator over lines of a followed file, see [Follower::lines]
+pub struct Lines<'a> {
+    follower: Follower<'a>,
+    buf: Vec<u8>,
+}
+
+impl<'a> Iterator for Lines<'a> {
+    type Item = Result<String, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(idx) = self.buf.iter().position(|b| *b == b'\n') {
+                let mut line: Vec<u8> = self.buf.drain(..=idx).collect();
+                line.pop();
+                if line.last() == Some(&b'\r') {
+                    line.pop();
+                }
+                return Some(Ok(String::from_utf8_lossy(&line).to_string()));
+            }
+
+            match self.follower.next()? {
+                Ok(FollowEvent::Data(data)) => self.buf.extend_from_slice(&data),
+                // partial line of the previous file is not going to be completed
+                Ok(_) => self.buf.clear(),
+                Err(e) => return Some(Err(e)),
+            }
+        }
+    }
+}
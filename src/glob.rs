@@ -0,0 +1,422 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Hadoop compatible glob expansion
+//!
+//! Supported syntax, same as in Hadoop's `globStatus`:
+//!
+//! * `?` - matches any single character
+//! * `*` - matches zero or more characters
+//! * `[abc]` - matches a single character from the set
+//! * `[a-b]` - matches a single character from the range
+//! * `[^a]` - matches a single character not in the set or range
+//! * `{ab,cd}` - matches a string from the set, may be nested
+//! * `\c` - removes special meaning of character `c`
+
+use crate::dfs::{FileStatus, HdfsFs};
+use crate::util::{base_name, join_path};
+use log::debug;
+use std::io::{Error, ErrorKind};
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Literal(char),
+    Any,
+    Star,
+    Class {
+        negated: bool,
+        ranges: Vec<(char, char)>,
+    },
+}
+
+/// Compiled glob pattern matching a single path component
+#[derive(Debug, Clone)]
+pub struct GlobPattern {
+    alternatives: Vec<Vec<Token>>,
+    has_wildcard: bool,
+}
+
+impl GlobPattern {
+    /// Compile ```pattern```, returns [ErrorKind::InvalidInput] for malformed patterns
+    pub fn new(pattern: &str) -> Result<GlobPattern, Error> {
+        let expanded = expand_braces(pattern, false)?;
+        let has_wildcard = expanded.len() > 1 || has_wildcard(pattern);
+        let alternatives = expanded
+            .iter()
+            .map(|p| tokenize(p))
+            .collect::<Result<Vec<_>, Error>>()?;
+
+        Ok(GlobPattern {
+            alternatives,
+            has_wildcard,
+        })
+    }
+
+    /// Does pattern contain any wildcard
+    pub fn has_wildcard(&self) -> bool {
+        self.has_wildcard
+    }
+
+    /// Checks if ```name``` matches the pattern
+    pub fn matches(&self, name: &str) -> bool {
+        let name: Vec<char> = name.chars().collect();
+        self.alternatives
+            .iter()
+            .any(|tokens| matches_tokens(tokens, &name))
+    }
+}
+
+fn invalid(pattern: &str, reason: &str) -> Error {
+    Error::new(
+        ErrorKind::InvalidInput,
+        format!("invalid glob pattern [{}]: {}", pattern, reason),
+    )
+}
+
+fn has_wildcard(pattern: &str) -> bool {
+    let mut chars = pattern.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                chars.next();
+            }
+            '*' | '?' | '[' | '{' => return true,
+            _ => (),
+        }
+    }
+    false
+}
+
+/// Removes escape characters from a pattern without wildcards
+fn unescape(pattern: &str) -> String {
+    let mut result = String::with_capacity(pattern.len());
+    let mut chars = pattern.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => result.extend(chars.next()),
+            c => result.push(c),
+        }
+    }
+    result
+}
+
+/// Expands ```{a,b}``` groups into separate patterns. If ```only_with_slash```
+/// is set, only groups containing path separator are expanded.
+fn expand_braces(pattern: &str, only_with_slash: bool) -> Result<Vec<String>, Error> {
+    let chars: Vec<char> = pattern.chars().collect();
+    let mut idx = 0;
+    let mut in_class = false;
+
+    while idx < chars.len() {
+        match chars[idx] {
+            '\\' => idx += 1,
+            '[' if !in_class => in_class = true,
+            ']' if in_class => in_class = false,
+            '{' if !in_class => {
+                let (close, alternatives) = split_group(pattern, &chars, idx)?;
+                let contains_slash = chars[idx..close].contains(&'/');
+                if !only_with_slash || contains_slash {
+                    let prefix: String = chars[..idx].iter().collect();
+                    let suffix: String = chars[close + 1..].iter().collect();
+                    let mut result = Vec::new();
+                    for alternative in alternatives {
+                        let expanded = format!("{}{}{}", prefix, alternative, suffix);
+                        result.extend(expand_braces(&expanded, only_with_slash)?);
+                    }
+                    return Ok(result);
+                }
+                idx = close;
+            }
+            '}' if !in_class && !only_with_slash => return Err(invalid(pattern, "unopened '}'")),
+            _ => (),
+        }
+        idx += 1;
+    }
+
+    if in_class {
+        return Err(invalid(pattern, "unclosed '['"));
+    }
+
+    Ok(vec![pattern.to_string()])
+}
+
+/// Returns index of the closing brace of a group starting at ```open```
+/// and top level alternatives of the group
+fn split_group(pattern: &str, chars: &[char], open: usize) -> Result<(usize, Vec<String>), Error> {
+    let mut alternatives = Vec::new();
+    let mut current = String::new();
+    let mut depth = 0;
+    let mut in_class = false;
+    let mut idx = open + 1;
+
+    while idx < chars.len() {
+        let c = chars[idx];
+        match c {
+            '\\' if idx + 1 < chars.len() => {
+                current.push(c);
+                current.push(chars[idx + 1]);
+                idx += 2;
+                continue;
+            }
+            '[' if !in_class => in_class = true,
+            ']' if in_class => in_class = false,
+            '{' if !in_class => depth += 1,
+            '}' if !in_class && depth == 0 => {
+                alternatives.push(current);
+                return Ok((idx, alternatives));
+            }
+            '}' if !in_class => depth -= 1,
+            ',' if !in_class && depth == 0 => {
+                alternatives.push(std::mem::take(&mut current));
+                idx += 1;
+                continue;
+            }
+            _ => (),
+        }
+        current.push(c);
+        idx += 1;
+    }
+
+    Err(invalid(pattern, "unclosed '{'"))
+}
+
+fn tokenize(pattern: &str) -> Result<Vec<Token>, Error> {
+    let mut tokens = Vec::new();
+    let mut chars = pattern.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some(c) => tokens.push(Token::Literal(c)),
+                None => return Err(invalid(pattern, "dangling '\\'")),
+            },
+            '*' => {
+                // consecutive stars are equivalent to a single one
+                if tokens.last() != Some(&Token::Star) {
+                    tokens.push(Token::Star)
+                }
+            }
+            '?' => tokens.push(Token::Any),
+            '[' => {
+                let negated = matches!(chars.peek(), Some('^') | Some('!'));
+                if negated {
+                    chars.next();
+                }
+                let mut ranges = Vec::new();
+                loop {
+                    let start = match chars.next() {
+                        Some(']') => break,
+                        Some('\\') => chars.next(),
+                        c => c,
+                    }
+                    .ok_or_else(|| invalid(pattern, "unclosed '['"))?;
+
+                    if chars.peek() == Some(&'-') {
+                        chars.next();
+                        let end = match chars.next() {
+                            Some('\\') => chars.next(),
+                            c => c,
+                        }
+                        .ok_or_else(|| invalid(pattern, "unclosed '['"))?;
+                        if end < start {
+                            return Err(invalid(pattern, "invalid character range"));
+                        }
+                        ranges.push((start, end));
+                    } else {
+                        ranges.push((start, start));
+                    }
+                }
+                tokens.push(Token::Class { negated, ranges });
+            }
+            c => tokens.push(Token::Literal(c)),
+        }
+    }
+
+    Ok(tokens)
+}
+
+fn matches_token(token: &Token, c: char) -> bool {
+    match token {
+        Token::Literal(l) => *l == c,
+        Token::Any => true,
+        Token::Star => true,
+        Token::Class { negated, ranges } => {
+            ranges.iter().any(|(start, end)| *start <= c && c <= *end) != *negated
+        }
+    }
+}
+
+fn matches_tokens(tokens: &[Token], name: &[char]) -> bool {
+    let (mut t, mut n) = (0, 0);
+    // position of last star and name position it has been tried with
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while n < name.len() {
+        if t < tokens.len() && tokens[t] == Token::Star {
+            backtrack = Some((t, n));
+            t += 1;
+        } else if t < tokens.len() && matches_token(&tokens[t], name[n]) {
+            t += 1;
+            n += 1;
+        } else if let Some((star, star_n)) = backtrack {
+            t = star + 1;
+            n = star_n + 1;
+            backtrack = Some((star, star_n + 1));
+        } else {
+            return false;
+        }
+    }
+
+    tokens[t..].iter().all(|token| *token == Token::Star)
+}
+
+/// Candidate path, status is not known for literal path components
+struct Candidate {
+    path: String,
+    status: Option<FileStatus>,
+}
+
+/// Splits pattern into a root (```/```, ```scheme://authority/``` or
+/// empty for relative paths) and path components
+fn split_root(pattern: &str) -> (String, Vec<&str>) {
+    let (root, rest) = match pattern.find("://") {
+        Some(idx) => {
+            let authority_end = pattern[idx + 3..]
+                .find('/')
+                .map_or(pattern.len(), |i| idx + 3 + i);
+            (
+                format!("{}/", &pattern[..authority_end]),
+                &pattern[authority_end..],
+            )
+        }
+        None if pattern.starts_with('/') => ("/".to_string(), pattern),
+        None => (String::new(), pattern),
+    };
+
+    (root, rest.split('/').filter(|c| !c.is_empty()).collect())
+}
+
+fn list_children(fs: &HdfsFs, candidate: &Candidate) -> Result<Vec<FileStatus>, Error> {
+    if let Some(status) = &candidate.status {
+        if !status.is_directory() {
+            return Ok(Vec::new());
+        }
+    }
+
+    let path = if candidate.path.is_empty() {
+        "."
+    } else {
+        &candidate.path
+    };
+    let children = match fs.list_status(path) {
+        Ok(children) => children,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+
+    // listing of a file returns the file itself, which can not be told
+    // apart from a directory with a single entry without its status
+    if children.len() == 1 && candidate.status.is_none() {
+        match fs.get_file_status(path) {
+            Ok(status) if status.is_directory() => (),
+            _ => return Ok(Vec::new()),
+        }
+    }
+
+    Ok(children)
+}
+
+fn glob_expanded(fs: &HdfsFs, pattern: &str) -> Result<Vec<(String, FileStatus)>, Error> {
+    let (root, components) = split_root(pattern);
+    let mut candidates = vec![Candidate {
+        path: root,
+        status: None,
+    }];
+
+    for component in components {
+        let glob = GlobPattern::new(component)?;
+        let mut next = Vec::new();
+
+        if glob.has_wildcard() {
+            for candidate in &candidates {
+                for child in list_children(fs, candidate)? {
+                    let name = base_name(child.name());
+                    if glob.matches(name) {
+                        next.push(Candidate {
+                            path: join_child(&candidate.path, name),
+                            status: Some(child),
+                        });
+                    }
+                }
+            }
+        } else {
+            // literal components do not require listing
+            let name = unescape(component);
+            for candidate in &candidates {
+                if matches!(&candidate.status, Some(status) if !status.is_directory()) {
+                    continue;
+                }
+                next.push(Candidate {
+                    path: join_child(&candidate.path, &name),
+                    status: None,
+                });
+            }
+        }
+
+        candidates = next;
+        if candidates.is_empty() {
+            break;
+        }
+    }
+
+    let mut result = Vec::with_capacity(candidates.len());
+    for candidate in candidates {
+        match candidate.status {
+            Some(status) => result.push((candidate.path, status)),
+            None => match fs.get_file_status(&candidate.path) {
+                Ok(status) => result.push((candidate.path, status)),
+                Err(e) if e.kind() == ErrorKind::NotFound => (),
+                Err(e) => return Err(e),
+            },
+        }
+    }
+
+    Ok(result)
+}
+
+fn join_child(parent: &str, name: &str) -> String {
+    if parent.is_empty() {
+        name.to_string()
+    } else {
+        join_path(parent, name)
+    }
+}
+
+/// Returns all paths matching ```pattern``` sorted by path,
+/// see [HdfsFs::glob] for details.
+pub(crate) fn glob(fs: &HdfsFs, pattern: &str) -> Result<Vec<FileStatus>, Error> {
+    let mut result = Vec::new();
+    for expanded in expand_braces(pattern, true)? {
+        debug!("glob [{}] expanded to [{}]", pattern, expanded);
+        result.extend(glob_expanded(fs, &expanded)?);
+    }
+
+    result.sort_by(|a, b| a.0.cmp(&b.0));
+    result.dedup_by(|a, b| a.0 == b.0);
+
+    Ok(result.into_iter().map(|(_, status)| status).collect())
+}
@@ -18,24 +18,88 @@
 //! A rust wrapper over libhdfs3
 
 /// Rust APIs wrapping libhdfs3 API, providing better semantic and abstraction
+#[cfg(feature = "async")]
+pub mod async_fs;
 pub mod dfs;
+#[cfg(feature = "dlopen")]
+pub mod dlopen;
+pub mod follow;
+pub mod glob;
+pub mod list;
+pub mod metadata;
+#[cfg(any(feature = "libhdfs3", feature = "dlopen"))]
+mod native;
+#[cfg(feature = "object_store")]
+pub mod object_store;
+#[cfg(feature = "parquet")]
+pub mod parquet;
+pub mod permission;
+pub mod recursive;
+pub mod split;
+pub mod status;
+pub mod summary;
+pub mod sync;
+pub mod trash;
 pub mod util;
+pub mod walk;
+#[cfg(feature = "webhdfs")]
+mod webhdfs;
+#[cfg(feature = "async")]
+pub use crate::async_fs::{AsyncHdfsFile, AsyncHdfsFs, BlockingPool};
 pub use crate::dfs::*;
+pub use crate::follow::{FollowEvent, FollowFrom, FollowOptions, Follower};
+pub use crate::list::{ListBatches, ListStatus};
+pub use crate::metadata::{FileKind, FileMetadata};
+#[cfg(feature = "object_store")]
+pub use crate::object_store::HdfsObjectStore;
+#[cfg(feature = "parquet")]
+pub use crate::parquet::{AsyncHdfsParquetReader, HdfsParquetReader, ParquetReaderOptions};
+pub use crate::permission::{FsAction, FsPermission, SymbolicMode};
+pub use crate::split::{InputSplit, InputSplits, SplitAssignment};
+pub use crate::status::{FsStatus, UsageLevel, UsageThresholds};
+pub use crate::summary::ContentSummary;
 pub use crate::util::HdfsUtil;
-use libhdfs3_sys::*;
+pub use crate::walk::Walk;
+#[cfg(feature = "webhdfs")]
+use crate::webhdfs::WebHdfsFs;
+#[cfg(any(feature = "libhdfs3", feature = "dlopen"))]
+use crate::sys::{
+    hdfsBuilder, hdfsBuilderConnect, hdfsBuilderSetNameNode, hdfsBuilderSetNameNodePort,
+    hdfsConnectAsUserNewInstance, hdfsConnectNewInstance, hdfsFreeBuilder, hdfsNewBuilder,
+};
 use log::{debug, info};
 use std::collections::HashMap;
 use std::io::{Error, ErrorKind};
 use std::sync::{Arc, Mutex};
 use url::Url;
 
+/// libhdfs3 as linked with ```libhdfs3``` feature, see [HdfsFs::raw]
+#[cfg(feature = "libhdfs3")]
 pub mod raw {
     pub use libhdfs3_sys::*;
 }
 
+/// libhdfs3 as called by the crate, resolved at runtime with ```dlopen``` feature
+#[cfg(any(feature = "libhdfs3", feature = "dlopen"))]
+mod sys {
+    #[cfg(feature = "dlopen")]
+    pub(crate) use crate::dlopen::*;
+    #[cfg(not(feature = "dlopen"))]
+    pub(crate) use libhdfs3_sys::*;
+}
+
+#[cfg(not(any(feature = "libhdfs3", feature = "dlopen", feature = "webhdfs")))]
+compile_error!("either libhdfs3, dlopen or webhdfs feature is required");
+
 static LOCAL_FS_SCHEME: &str = "file";
+static WEBHDFS_SCHEMES: [&str; 2] = ["webhdfs", "swebhdfs"];
 
 /// HdfsRegistry which stores seen HdfsFs instances.
+///
+/// ```hdfs://host:port``` urls are served by libhdfs3, enabled by ```libhdfs3```
+/// or ```dlopen``` feature. ```webhdfs://[user@]host:port``` and ```swebhdfs://``` urls are
+/// served by a WebHDFS client which does not use libhdfs3, enabled by
+/// ```webhdfs``` feature.
 #[derive(Debug)]
 pub struct HdfsRegistry {
     all_fs: Arc<Mutex<HashMap<String, Arc<HdfsFs>>>>,
@@ -55,6 +119,8 @@ struct HostPort {
 enum NNScheme {
     Local,
     Remote(HostPort),
+    #[cfg(feature = "webhdfs")]
+    WebHdfs(Box<WebHdfsFs>),
 }
 
 impl ToString for NNScheme {
@@ -62,6 +128,37 @@ impl ToString for NNScheme {
         match self {
             NNScheme::Local => "file:///".to_string(),
             NNScheme::Remote(hp) => format!("{}:{}", hp.host, hp.port),
+            // clients of different users are not shared
+            #[cfg(feature = "webhdfs")]
+            NNScheme::WebHdfs(fs) => match fs.user() {
+                Some(user) => format!("{}@{}", user, fs.url()),
+                None => fs.url().to_string(),
+            },
+        }
+    }
+}
+
+impl NNScheme {
+    fn connect(self) -> Result<HdfsFs, Error> {
+        match self {
+            NNScheme::Local => Err(ErrorKind::Unsupported.into()),
+            #[cfg(any(feature = "libhdfs3", feature = "dlopen"))]
+            NNScheme::Remote(hp) => {
+                let mut builder = HdfsBuilder::builder();
+                builder.set_name_node(&hp.host);
+                builder.set_name_port(hp.port);
+                builder.connect()
+            }
+            #[cfg(not(any(feature = "libhdfs3", feature = "dlopen")))]
+            NNScheme::Remote(hp) => Err(Error::new(
+                ErrorKind::Unsupported,
+                format!(
+                    "[{}] is served by libhdfs3, enabled by libhdfs3 or dlopen feature",
+                    hp.host
+                ),
+            )),
+            #[cfg(feature = "webhdfs")]
+            NNScheme::WebHdfs(fs) => Ok(HdfsFs::new(fs.url().to_string(), fs)),
         }
     }
 }
@@ -78,6 +175,8 @@ impl HdfsRegistry {
             Ok(url) => {
                 if url.scheme() == LOCAL_FS_SCHEME {
                     Ok(NNScheme::Local)
+                } else if WEBHDFS_SCHEMES.contains(&url.scheme()) {
+                    webhdfs_scheme(path)
                 } else if url.host().is_some() && url.port().is_some() {
                     Ok(NNScheme::Remote(HostPort {
                         host: format!("{}://{}", &url.scheme(), url.host().unwrap()),
@@ -93,109 +192,105 @@ impl HdfsRegistry {
 
     pub fn get(&self, path: &str) -> Result<Arc<HdfsFs>, Error> {
         debug!("fs get for path: [{}]", path);
-        let host_port = self.get_name_node(path)?;
+        let name_node = self.get_name_node(path)?;
+        let key = name_node.to_string();
         let mut map = self.all_fs.lock().unwrap();
 
-        let entry: &mut Arc<HdfsFs> = map.entry(host_port.to_string()).or_insert({
-            debug!("fs get for path: [{}] ... creating new FS", path);
-            let mut builder = HdfsBuilder::builder();
-            match host_port {
-                NNScheme::Local => return Err(ErrorKind::Unsupported.into()),
-                NNScheme::Remote(ref hp) => {
-                    builder.set_name_node(&hp.host);
-                    builder.set_name_port(hp.port);
-                }
-            }
-            let fs = builder.connect()?;
-            info!("fs get for path: [{}] ... connected", path);
-            Arc::new(fs)
-        });
+        if let Some(fs) = map.get(&key) {
+            return Ok(fs.clone());
+        }
 
-        Ok(entry.clone())
+        debug!("fs get for path: [{}] ... creating new FS", path);
+        let fs = Arc::new(name_node.connect()?);
+        info!("fs get for path: [{}] ... connected", path);
+        map.insert(key, fs.clone());
+
+        Ok(fs)
     }
 }
 
+#[cfg(feature = "webhdfs")]
+fn webhdfs_scheme(path: &str) -> Result<NNScheme, Error> {
+    WebHdfsFs::new(path).map(|fs| NNScheme::WebHdfs(Box::new(fs)))
+}
+
+#[cfg(not(feature = "webhdfs"))]
+fn webhdfs_scheme(path: &str) -> Result<NNScheme, Error> {
+    Err(Error::new(
+        ErrorKind::Unsupported,
+        format!("[{}] is served by webhdfs feature", path),
+    ))
+}
+
+#[cfg(any(feature = "libhdfs3", feature = "dlopen"))]
+/// Name node to connect to, libhdfs3 builder is created by [HdfsBuilder::connect]
 pub struct HdfsBuilder {
-    builder: *mut hdfsBuilder,
     host: String,
     port: u16,
 }
 
+#[cfg(any(feature = "libhdfs3", feature = "dlopen"))]
 impl HdfsBuilder {
     pub fn builder() -> Self {
-        let builder: *mut hdfsBuilder = unsafe { hdfsNewBuilder() };
         let host = "default".to_string();
         let port = 0;
-        Self { builder, host, port }
+        Self { host, port }
     }
 
     pub fn connect_name_node(host: &str, port: u16 ) -> Result<HdfsFs, Error> {
+        #[cfg(feature = "dlopen")]
+        dlopen::load_library()?;
+
         let hdfs_fs = unsafe {
             hdfsConnectNewInstance(to_raw!(host), port)
         };
 
-        if hdfs_fs.is_null() {
-            Err(Error::last_os_error())
-        } else {
-            let host_port = format!("{}:{}", host, port);
-            Ok(HdfsFs::new(host_port, hdfs_fs))
-        }
+        native::connected(format!("{}:{}", host, port), hdfs_fs)
     }
 
     pub fn connect_name_node_as_user(host: &str, port: u16, user: &str ) -> Result<HdfsFs, Error> {
+        #[cfg(feature = "dlopen")]
+        dlopen::load_library()?;
+
         let hdfs_fs = unsafe {
             hdfsConnectAsUserNewInstance(to_raw!(host), port, to_raw!(user))
         };
 
-        if hdfs_fs.is_null() {
-            Err(Error::last_os_error())
-        } else {
-            let host_port = format!("{}:{}", host, port);
-            Ok(HdfsFs::new(host_port, hdfs_fs))
-        }
+        native::connected(format!("{}:{}", host, port), hdfs_fs)
     }
 
     pub fn set_name_node_port(&mut self, host: &str, port: u16) {
         self.host = host.to_string();
         self.port = port;
-        unsafe {
-            hdfsBuilderSetNameNode(self.builder, to_raw!(host));
-            hdfsBuilderSetNameNodePort(self.builder, port);
-        }
     }
 
     pub fn set_name_node(&mut self, host: &str) {
         self.host = host.to_string();
-
-        unsafe {
-            hdfsBuilderSetNameNode(self.builder, to_raw!(host));
-        }
     }
 
     pub fn set_name_port(&mut self, port: u16) {
         self.port = port;
-        unsafe {
-            hdfsBuilderSetNameNodePort(self.builder, port);
-        }
     }
+
     pub fn connect(self) -> Result<HdfsFs, Error> {
-        let hdfs_fs = unsafe { hdfsBuilderConnect(self.builder) };
+        #[cfg(feature = "dlopen")]
+        dlopen::load_library()?;
 
-        if hdfs_fs.is_null() {
-            Err(Error::last_os_error())
-        } else {
-            let host_port = format!("{}:{}", self.host, self.port);
-            Ok(HdfsFs::new(host_port, hdfs_fs))
-        }
-    }
-}
+        let hdfs_fs = unsafe {
+            let builder: *mut hdfsBuilder = hdfsNewBuilder();
+            hdfsBuilderSetNameNode(builder, to_raw!(self.host.as_str()));
+            hdfsBuilderSetNameNodePort(builder, self.port);
+            let hdfs_fs = hdfsBuilderConnect(builder);
+            hdfsFreeBuilder(builder);
+            hdfs_fs
+        };
 
-impl Drop for HdfsBuilder {
-    fn drop(&mut self) {
-        unsafe { hdfsFreeBuilder(self.builder) }
+        native::connected(format!("{}:{}", self.host, self.port), hdfs_fs)
     }
 }
 
+
+#[cfg(any(feature = "libhdfs3", feature = "dlopen"))]
 impl Default for HdfsBuilder {
     fn default() -> Self {
         Self::builder()
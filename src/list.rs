@@ -0,0 +1,144 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Lazy directory listing
+
+use crate::dfs::{FileStatus, HdfsFs};
+use std::io::Error;
+use std::vec::IntoIter;
+
+/// Iterator over entries of a directory, created by [HdfsFs::list_status_iter].
+///
+/// Directory is listed page by page as the iterator advances, so an
+/// iterator which is never advanced costs nothing and iteration can stop
+/// early. Entries are handed out one by one, or in batches with
+/// [ListStatus::batches].
+///
+/// WebHDFS pages are fetched with ```LISTSTATUS_BATCH```, their size is set
+/// by the name node (```dfs.ls.limit```). libhdfs3 C API has no paged
+/// listing, the whole directory is fetched as a single page by one
+/// ```hdfsListDirectory``` call.
+///
+/// ```ignore
+/// for status in fs.list_status_iter("/data").filter_map(Result::ok) {
+///     if status.name().ends_with("_SUCCESS") {
+///         break;
+///     }
+/// }
+/// ```
+pub struct ListStatus<'a> {
+    fs: &'a HdfsFs,
+    path: String,
+    page: IntoIter<FileStatus>,
+    /// Name to continue after, ```None``` once the last page is fetched
+    next: Option<Option<String>>,
+    done: bool,
+}
+
+impl<'a> ListStatus<'a> {
+    pub(crate) fn new(fs: &'a HdfsFs, path: &str) -> ListStatus<'a> {
+        ListStatus {
+            fs,
+            path: path.to_string(),
+            page: Vec::new().into_iter(),
+            next: Some(None),
+            done: false,
+        }
+    }
+
+    /// Listed directory
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Group entries into vectors of at most ```size``` entries.
+    pub fn batches(self, size: usize) -> ListBatches<'a> {
+        ListBatches {
+            inner: self,
+            size: size.max(1),
+        }
+    }
+}
+
+impl Iterator for ListStatus<'_> {
+    type Item = Result<FileStatus, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if self.done {
+                return None;
+            }
+            if let Some(status) = self.page.next() {
+                return Some(Ok(status));
+            }
+            let start_after = match self.next.take() {
+                Some(start_after) => start_after,
+                None => {
+                    self.done = true;
+                    return None;
+                }
+            };
+            match self
+                .fs
+                .list_status_batch(&self.path, start_after.as_deref())
+            {
+                Ok((entries, next)) => {
+                    self.next = next.map(Some);
+                    self.page = entries.into_iter();
+                }
+                Err(e) => {
+                    self.done = true;
+                    return Some(Err(e));
+                }
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match (self.done, &self.next) {
+            (true, _) => (0, Some(0)),
+            (false, None) => (self.page.len(), Some(self.page.len())),
+            (false, Some(_)) => (self.page.len(), None),
+        }
+    }
+}
+
+/// Iterator over batches of directory entries, see [ListStatus::batches].
+pub struct ListBatches<'a> {
+    inner: ListStatus<'a>,
+    size: usize,
+}
+
+impl Iterator for ListBatches<'_> {
+    type Item = Result<Vec<FileStatus>, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut batch = Vec::new();
+        while batch.len() < self.size {
+            match self.inner.next() {
+                Some(Ok(status)) => batch.push(status),
+                Some(Err(e)) => return Some(Err(e)),
+                None => break,
+            }
+        }
+        if batch.is_empty() {
+            None
+        } else {
+            Some(Ok(batch))
+        }
+    }
+}
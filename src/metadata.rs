@@ -0,0 +1,106 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Owned snapshot of [FileStatus]
+
+use crate::dfs::FileStatus;
+use crate::permission::FsPermission;
+use libc::time_t;
+use std::io::{Error, ErrorKind};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Kind of a file system object
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum FileKind {
+    File,
+    Directory,
+}
+
+/// Copy of [FileStatus] with public typed fields, so it can be
+/// cached, compared or sent to other services.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FileMetadata {
+    /// Full path of the file
+    pub path: String,
+    pub kind: FileKind,
+    /// Length of the file, in bytes
+    pub len: u64,
+    pub block_size: u64,
+    pub replication: i16,
+    pub owner: String,
+    pub group: String,
+    pub permission: FsPermission,
+    pub modified: SystemTime,
+    pub accessed: SystemTime,
+}
+
+impl FileMetadata {
+    /// Is this a file?
+    pub fn is_file(&self) -> bool {
+        self.kind == FileKind::File
+    }
+
+    /// Is this a directory?
+    pub fn is_directory(&self) -> bool {
+        self.kind == FileKind::Directory
+    }
+}
+
+/// converts seconds since epoch to [SystemTime]
+pub(crate) fn to_system_time(secs: time_t) -> SystemTime {
+    UNIX_EPOCH + Duration::from_secs(secs.max(0) as u64)
+}
+
+/// converts [SystemTime] to seconds since epoch, times before epoch are rejected
+pub(crate) fn from_system_time(time: SystemTime) -> Result<time_t, Error> {
+    match time.duration_since(UNIX_EPOCH) {
+        Ok(duration) => Ok(duration.as_secs() as time_t),
+        Err(_) => Err(Error::new(
+            ErrorKind::InvalidInput,
+            "time before unix epoch is not supported",
+        )),
+    }
+}
+
+impl From<&FileStatus> for FileMetadata {
+    fn from(status: &FileStatus) -> Self {
+        FileMetadata {
+            path: status.name().to_string(),
+            kind: if status.is_directory() {
+                FileKind::Directory
+            } else {
+                FileKind::File
+            },
+            len: status.len() as u64,
+            block_size: status.block_size() as u64,
+            replication: status.replica_count(),
+            owner: status.owner().to_string(),
+            group: status.group().to_string(),
+            permission: FsPermission::from(status.permission()),
+            modified: to_system_time(status.last_modified()),
+            accessed: to_system_time(status.last_accced()),
+        }
+    }
+}
+
+impl From<FileStatus> for FileMetadata {
+    fn from(status: FileStatus) -> Self {
+        FileMetadata::from(&status)
+    }
+}
@@ -0,0 +1,400 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! libhdfs3 backend of [HdfsFs], enabled by ```libhdfs3``` or ```dlopen``` feature
+
+use crate::dfs::{Backend, BlockLocation, FileBackend, FileStatus, HdfsFs, OpenMode};
+use crate::metadata::FileKind;
+use crate::sys::*;
+use crate::{from_raw, to_raw};
+use libc::{c_char, c_int, c_short, c_void, time_t};
+use std::io::{Error, ErrorKind};
+
+/// Wraps filesystem connected by libhdfs3, ```raw``` is null if
+/// connection failed.
+pub(crate) fn connected(url: String, raw: hdfsFS) -> Result<HdfsFs, Error> {
+    if raw.is_null() {
+        Err(Error::last_os_error())
+    } else {
+        Ok(HdfsFs::new(url, Box::new(NativeFs { raw })))
+    }
+}
+
+/// Last error message of libhdfs3 on the calling thread
+pub(crate) fn last_error() -> &'static str {
+    // nothing has failed in a library which is not loaded
+    #[cfg(feature = "dlopen")]
+    if !crate::dlopen::is_loaded() {
+        return "";
+    }
+    let char_ptr = unsafe { hdfsGetLastError() };
+
+    if !char_ptr.is_null() {
+        from_raw!(char_ptr)
+    } else {
+        ""
+    }
+}
+
+/// Converts return code of calls returning ```0``` on success
+fn check(res: c_int) -> Result<(), Error> {
+    if res == 0 {
+        Ok(())
+    } else {
+        Err(Error::last_os_error())
+    }
+}
+
+/// Converts return value of calls returning negative value on failure
+fn check_size(res: i64) -> Result<usize, Error> {
+    if res < 0 {
+        Err(Error::last_os_error())
+    } else {
+        Ok(res as usize)
+    }
+}
+
+/// Collects strings of an array which is either NULL terminated
+/// or has ```len``` elements.
+fn c_str_array(array: *mut *mut c_char, len: Option<usize>) -> Vec<String> {
+    let mut result = Vec::new();
+    if array.is_null() {
+        return result;
+    }
+
+    let mut idx = 0;
+    loop {
+        if len.is_some_and(|len| idx >= len) {
+            break;
+        }
+        let item = unsafe { *array.add(idx) };
+        if item.is_null() {
+            break;
+        }
+        result.push(from_raw!(item).to_string());
+        idx += 1;
+    }
+    result
+}
+
+fn block_location(raw: &crate::sys::BlockLocation) -> BlockLocation {
+    let nodes = Some(raw.numOfNodes.max(0) as usize);
+
+    BlockLocation {
+        offset: raw.offset as u64,
+        length: raw.length as u64,
+        hosts: c_str_array(raw.hosts, nodes),
+        names: c_str_array(raw.names, nodes),
+        topology_paths: c_str_array(raw.topologyPaths, nodes),
+        corrupt: raw.corrupt != 0,
+    }
+}
+
+fn file_status(info: &hdfsFileInfo) -> FileStatus {
+    FileStatus {
+        name: from_raw!(info.mName).to_string(),
+        kind: match info.mKind {
+            tObjectKind::kObjectKindFile => FileKind::File,
+            tObjectKind::kObjectKindDirectory => FileKind::Directory,
+        },
+        owner: from_raw!(info.mOwner).to_string(),
+        group: from_raw!(info.mGroup).to_string(),
+        permission: info.mPermissions,
+        len: info.mSize as usize,
+        block_size: info.mBlockSize as usize,
+        replication: info.mReplication,
+        last_modified: info.mLastMod,
+        last_access: info.mLastAccess,
+    }
+}
+
+/// Copies ```len``` entries allocated by libhdfs3 and releases them
+fn file_statuses(ptr: *mut hdfsFileInfo, len: c_int) -> Vec<FileStatus> {
+    let statuses = (0..len.max(0) as usize)
+        .map(|idx| file_status(unsafe { &*ptr.add(idx) }))
+        .collect();
+
+    unsafe { hdfsFreeFileInfo(ptr, len) };
+    statuses
+}
+
+/// Filesystem connected by libhdfs3
+struct NativeFs {
+    raw: hdfsFS,
+}
+
+// this should be ok
+unsafe impl Send for NativeFs {}
+unsafe impl Sync for NativeFs {}
+
+impl Drop for NativeFs {
+    fn drop(&mut self) {
+        unsafe {
+            hdfsDisconnect(self.raw);
+        }
+    }
+}
+
+impl Backend for NativeFs {
+    fn open(&self, path: &str, mode: OpenMode) -> Result<Box<dyn FileBackend + '_>, Error> {
+        let (flags, buf_size, replication, block_size) = match mode {
+            OpenMode::Read { buf_size } => (HDFS_READ, buf_size, 0, 0),
+            OpenMode::Write { overwrite, .. } if !overwrite && self.exist(path) => {
+                return Err(ErrorKind::AlreadyExists.into());
+            }
+            OpenMode::Write {
+                buf_size,
+                replication,
+                block_size,
+                ..
+            } => (HDFS_WRITE, buf_size, replication, block_size),
+            OpenMode::Append => (HDFS_APPEND, 0, 0, 0),
+        };
+
+        let file = unsafe {
+            hdfsOpenFile(
+                self.raw,
+                to_raw!(path),
+                flags,
+                buf_size as c_int,
+                replication as c_short,
+                block_size,
+            )
+        };
+
+        if file.is_null() {
+            Err(Error::last_os_error())
+        } else {
+            Ok(Box::new(NativeFile { fs: self.raw, file }))
+        }
+    }
+
+    fn chmod(&self, path: &str, mode: i16) -> Result<(), Error> {
+        check(unsafe { hdfsChmod(self.raw, to_raw!(path), mode as c_short) })
+    }
+
+    fn chown(&self, path: &str, owner: &str, group: &str) -> Result<(), Error> {
+        check(unsafe { hdfsChown(self.raw, to_raw!(path), to_raw!(owner), to_raw!(group)) })
+    }
+
+    fn default_block_size(&self) -> Result<usize, Error> {
+        check_size(unsafe { hdfsGetDefaultBlockSize(self.raw) })
+    }
+
+    fn capacity(&self) -> Result<usize, Error> {
+        check_size(unsafe { hdfsGetCapacity(self.raw) })
+    }
+
+    fn used(&self) -> Result<usize, Error> {
+        check_size(unsafe { hdfsGetUsed(self.raw) })
+    }
+
+    fn delete(&self, path: &str, recursive: bool) -> Result<(), Error> {
+        check(unsafe { hdfsDelete(self.raw, to_raw!(path), recursive as c_int) })
+    }
+
+    fn exist(&self, path: &str) -> bool {
+        unsafe { hdfsExists(self.raw, to_raw!(path)) == 0 }
+    }
+
+    fn get_hosts(&self, path: &str, start: u64, length: u64) -> Result<Vec<Vec<String>>, Error> {
+        let ptr = unsafe { hdfsGetHosts(self.raw, to_raw!(path), start as i64, length as i64) };
+
+        if ptr.is_null() {
+            return Err(Error::last_os_error());
+        }
+
+        // the last element of the array is NULL
+        let mut blocks = Vec::new();
+        loop {
+            let block = unsafe { *ptr.add(blocks.len()) };
+            if block.is_null() {
+                break;
+            }
+            blocks.push(c_str_array(block, None));
+        }
+
+        unsafe { hdfsFreeHosts(ptr) };
+
+        Ok(blocks)
+    }
+
+    fn get_block_locations(
+        &self,
+        path: &str,
+        start: u64,
+        length: u64,
+    ) -> Result<Vec<BlockLocation>, Error> {
+        let mut num_blocks: c_int = 0;
+        let ptr = unsafe {
+            hdfsGetFileBlockLocations(
+                self.raw,
+                to_raw!(path),
+                start as i64,
+                length as i64,
+                &mut num_blocks,
+            )
+        };
+
+        if ptr.is_null() {
+            let error = Error::last_os_error();
+            // there is nothing to allocate if range has no blocks
+            return match self.get_file_status(path) {
+                Ok(status) if status.is_file() => Ok(Vec::new()),
+                _ => Err(error),
+            };
+        }
+
+        let locations = (0..num_blocks as usize)
+            .map(|idx| block_location(unsafe { &*ptr.add(idx) }))
+            .collect();
+
+        unsafe { hdfsFreeFileBlockLocations(ptr, num_blocks) };
+
+        Ok(locations)
+    }
+
+    fn mkdir(&self, path: &str) -> Result<(), Error> {
+        check(unsafe { hdfsCreateDirectory(self.raw, to_raw!(path)) })
+    }
+
+    fn set_times(&self, path: &str, mtime: time_t, atime: time_t) -> Result<(), Error> {
+        check(unsafe { hdfsUtime(self.raw, to_raw!(path), mtime, atime) })
+    }
+
+    fn set_replication(&self, path: &str, num: i16) -> Result<(), Error> {
+        check(unsafe { hdfsSetReplication(self.raw, to_raw!(path), num) })
+    }
+
+    fn rename(&self, old_path: &str, new_path: &str) -> Result<(), Error> {
+        check(unsafe { hdfsRename(self.raw, to_raw!(old_path), to_raw!(new_path)) })
+    }
+
+    fn list_status(&self, path: &str) -> Result<Vec<FileStatus>, Error> {
+        let mut entry_num: c_int = 0;
+
+        let ptr = unsafe { hdfsListDirectory(self.raw, to_raw!(path), &mut entry_num) };
+
+        if ptr.is_null() {
+            let error = Error::last_os_error();
+            // libhdfs3 returns null for empty directories as well
+            return match self.get_file_status(path) {
+                Ok(status) if status.is_directory() => Ok(Vec::new()),
+                _ => Err(error),
+            };
+        }
+
+        Ok(file_statuses(ptr, entry_num))
+    }
+
+    fn working_directory(&self) -> Result<String, Error> {
+        let mut buf: Vec<c_char> = vec![0; 4096];
+        let ptr = unsafe { hdfsGetWorkingDirectory(self.raw, buf.as_mut_ptr(), buf.len()) };
+
+        if ptr.is_null() {
+            Err(Error::last_os_error())
+        } else {
+            Ok(from_raw!(ptr).to_string())
+        }
+    }
+
+    fn get_file_status(&self, path: &str) -> Result<FileStatus, Error> {
+        let ptr = unsafe { hdfsGetPathInfo(self.raw, to_raw!(path)) };
+
+        if ptr.is_null() {
+            Err(Error::last_os_error())
+        } else {
+            Ok(file_statuses(ptr, 1).remove(0))
+        }
+    }
+
+    #[cfg(feature = "libhdfs3")]
+    fn raw(&self) -> libhdfs3_sys::hdfsFS {
+        // the same opaque pointer, declared by crate::dlopen with dlopen feature
+        self.raw.cast()
+    }
+}
+
+/// File opened by libhdfs3
+struct NativeFile {
+    fs: hdfsFS,
+    file: hdfsFile,
+}
+
+impl FileBackend for NativeFile {
+    fn available(&self) -> Result<(), Error> {
+        check(unsafe { hdfsAvailable(self.fs, self.file) })
+    }
+
+    fn close(&mut self) -> Result<(), Error> {
+        check(unsafe { hdfsCloseFile(self.fs, self.file) })
+    }
+
+    fn flush(&mut self) -> Result<(), Error> {
+        check(unsafe { hdfsFlush(self.fs, self.file) })
+    }
+
+    fn hflush(&mut self) -> Result<(), Error> {
+        check(unsafe { hdfsHFlush(self.fs, self.file) })
+    }
+
+    fn is_readable(&self) -> bool {
+        (unsafe { hdfsFileIsOpenForRead(self.file) }) == 1
+    }
+
+    fn is_writable(&self) -> bool {
+        (unsafe { hdfsFileIsOpenForWrite(self.file) }) == 1
+    }
+
+    fn pos(&self) -> Result<u64, Error> {
+        check_size(unsafe { hdfsTell(self.fs, self.file) }).map(|pos| pos as u64)
+    }
+
+    fn read(&self, buf: &mut [u8]) -> Result<usize, Error> {
+        let read_len = unsafe {
+            hdfsRead(
+                self.fs,
+                self.file,
+                buf.as_mut_ptr() as *mut c_void,
+                buf.len() as tSize,
+            )
+        };
+
+        check_size(read_len as i64)
+    }
+
+    fn seek(&self, offset: u64) -> Result<(), Error> {
+        check(unsafe { hdfsSeek(self.fs, self.file, offset as tOffset) })
+    }
+
+    fn write(&mut self, buf: &[u8]) -> Result<usize, Error> {
+        let written_len = unsafe {
+            hdfsWrite(
+                self.fs,
+                self.file,
+                buf.as_ptr() as *mut c_void,
+                buf.len() as tSize,
+            )
+        };
+
+        check_size(written_len as i64)
+    }
+
+    fn sync(&mut self) -> Result<(), Error> {
+        check_size(unsafe { hdfsSync(self.fs, self.file) } as i64).map(|_| ())
+    }
+}
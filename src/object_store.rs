@@ -0,0 +1,614 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! [ObjectStore] implementation over [HdfsFs], enabled by ```object_store``` feature
+//!
+//! Objects are files under a root directory, directories are presented as
+//! common prefixes. Writes go to a staged file ```<path>#<suffix>``` next to
+//! the destination which is renamed into place once complete, so readers
+//! never observe partially written objects. Staged files are not listed.
+//!
+//! Blocking libhdfs3 calls are executed on the tokio blocking pool if
+//! called from within a tokio runtime, otherwise in place.
+//!
+//! E-tags are derived from modification time and length, as modification
+//! time has a resolution of a second, rewrites of an object with the same
+//! length within a second are not detected by conditional requests.
+//!
+//! ```ignore
+//! let fs = HdfsRegistry::new().get("hdfs://localhost:9000")?;
+//! let store = HdfsObjectStore::new_with_root(fs, "/warehouse");
+//! let bytes = store.get(&Path::from("table/part-0.parquet")).await?.bytes().await?;
+//! ```
+
+use crate::dfs::{FileStatus, HdfsFs};
+use crate::util::base_name;
+use async_trait::async_trait;
+use bytes::Bytes;
+use chrono::{DateTime, Utc};
+use futures::channel::oneshot;
+use futures::stream::{self, BoxStream, StreamExt, TryStreamExt};
+use log::debug;
+use object_store::path::Path;
+use object_store::{
+    GetOptions, GetResult, GetResultPayload, ListResult, MultipartUpload, ObjectMeta, ObjectStore,
+    PutMode, PutMultipartOptions, PutOptions, PutPayload, PutResult, Result, UploadPart,
+};
+use std::fmt::{Debug, Display, Formatter};
+use std::io::{ErrorKind, Write};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc;
+use std::sync::Arc;
+
+/// Store name used in generic errors
+const STORE: &str = "HDFS";
+
+/// Size of chunks of a ```get``` stream, each chunk is a separate positional read
+const CHUNK_SIZE: u64 = 8 * 1024 * 1024;
+
+/// Suffix of staged files, makes concurrent writes to the same object unique
+static STAGED: AtomicUsize = AtomicUsize::new(0);
+
+/// [ObjectStore] over [HdfsFs], see [module documentation](self)
+#[derive(Debug, Clone)]
+pub struct HdfsObjectStore {
+    fs: Arc<HdfsFs>,
+    root: String,
+}
+
+impl HdfsObjectStore {
+    /// Store with objects relative to filesystem root
+    pub fn new(fs: Arc<HdfsFs>) -> HdfsObjectStore {
+        Self::new_with_root(fs, "/")
+    }
+
+    /// Store with objects relative to ```root``` directory
+    pub fn new_with_root(fs: Arc<HdfsFs>, root: &str) -> HdfsObjectStore {
+        let root = format!("/{}", root.trim_matches('/'));
+        HdfsObjectStore { fs, root }
+    }
+
+    /// Absolute HDFS path of an object
+    fn hdfs_path(&self, location: &Path) -> String {
+        match (self.root.as_str(), location.as_ref()) {
+            (root, "") => root.to_string(),
+            ("/", location) => format!("/{}", location),
+            (root, location) => format!("{}/{}", root, location),
+        }
+    }
+
+    fn context(&self, location: &Path) -> Context {
+        Context {
+            fs: self.fs.clone(),
+            root: self.root.clone(),
+            path: self.hdfs_path(location),
+        }
+    }
+}
+
+impl Display for HdfsObjectStore {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "HdfsObjectStore({}{})", self.fs.url(), self.root)
+    }
+}
+
+/// State moved to blocking calls
+#[derive(Clone)]
+struct Context {
+    fs: Arc<HdfsFs>,
+    root: String,
+    /// absolute HDFS path of the object
+    path: String,
+}
+
+impl Context {
+    /// Object location of an absolute HDFS path under the root
+    fn location(&self, path: &str) -> Result<Path> {
+        let relative = path
+            .strip_prefix(self.root.as_str())
+            .unwrap_or(path)
+            .trim_start_matches('/');
+        Ok(Path::parse(relative)?)
+    }
+
+    fn meta(&self, path: &str, status: &FileStatus) -> Result<ObjectMeta> {
+        Ok(ObjectMeta {
+            location: self.location(path)?,
+            last_modified: DateTime::<Utc>::from_timestamp(status.last_modified(), 0)
+                .unwrap_or_default(),
+            size: status.len() as u64,
+            e_tag: Some(e_tag(status)),
+            version: None,
+        })
+    }
+
+    /// Status of an object, directories are not objects
+    fn status(&self) -> Result<FileStatus> {
+        let status = self
+            .fs
+            .get_file_status(&self.path)
+            .map_err(|e| to_error(&self.path, e))?;
+        if status.is_directory() {
+            return Err(object_store::Error::NotFound {
+                path: self.path.clone(),
+                source: format!("{} is a directory", self.path).into(),
+            });
+        }
+        Ok(status)
+    }
+
+    /// Bytes ```start..start + len``` of the object
+    fn read(&self, start: u64, len: u64) -> Result<Bytes> {
+        let file = self
+            .fs
+            .open(&self.path)
+            .map_err(|e| to_error(&self.path, e))?;
+        let mut buf = vec![0; len as usize];
+        let mut read = 0;
+        while read < buf.len() {
+            let remaining = buf.len() - read;
+            let n = file
+                .read_with_pos_length((start as usize + read) as i64, &mut buf[read..], remaining)
+                .map_err(|e| to_error(&self.path, e))?;
+            if n == 0 {
+                return Err(to_error(
+                    &self.path,
+                    std::io::Error::new(ErrorKind::UnexpectedEof, "object has been truncated"),
+                ));
+            }
+            read += n;
+        }
+        Ok(buf.into())
+    }
+
+    /// Move ```staged``` file to the object path, removing the staged file
+    /// on failure
+    fn commit(&self, staged: &str, overwrite: bool) -> Result<PutResult> {
+        let result = self.rename(staged, &self.path, overwrite);
+        if result.is_err() {
+            let _ = self.fs.delete(staged, false);
+        }
+        result?;
+
+        let status = self.status()?;
+        Ok(PutResult {
+            e_tag: Some(e_tag(&status)),
+            version: None,
+        })
+    }
+
+    /// Rename a file, HDFS does not replace existing destination
+    fn rename(&self, from: &str, to: &str, overwrite: bool) -> Result<()> {
+        if overwrite && self.fs.exist(to) {
+            self.fs.delete(to, false).map_err(|e| to_error(to, e))?;
+        }
+        let parent = &to[..to.rfind('/').unwrap_or(0)];
+        if !parent.is_empty() && !self.fs.exist(parent) {
+            self.fs.mkdir(parent).map_err(|e| to_error(parent, e))?;
+        }
+
+        match self.fs.rename(from, to) {
+            Ok(true) => Ok(()),
+            result => {
+                if self.fs.exist(to) {
+                    return Err(object_store::Error::AlreadyExists {
+                        path: to.to_string(),
+                        source: format!("{} already exists", to).into(),
+                    });
+                }
+                let e = result.err().unwrap_or_else(|| ErrorKind::Other.into());
+                Err(to_error(from, e))
+            }
+        }
+    }
+
+    /// Copy the object to ```to``` through a staged file
+    fn copy(&self, to: &Context, overwrite: bool) -> Result<()> {
+        self.status()?;
+        if !overwrite && to.fs.exist(&to.path) {
+            return Err(object_store::Error::AlreadyExists {
+                path: to.path.clone(),
+                source: format!("{} already exists", to.path).into(),
+            });
+        }
+
+        let staged = staged_path(&to.path);
+        let copied = (|| {
+            let mut from = self.fs.open(&self.path)?;
+            let mut file = to.fs.create_with_overwrite(&staged, true)?;
+            std::io::copy(&mut from, &mut file)?;
+            file.close()
+        })();
+        if let Err(e) = copied {
+            let _ = to.fs.delete(&staged, false);
+            return Err(to_error(&self.path, e));
+        }
+        to.commit(&staged, overwrite).map(|_| ())
+    }
+
+    /// All objects under the path, in no particular order
+    fn list(&self) -> Result<Vec<ObjectMeta>> {
+        let mut objects = Vec::new();
+        for entry in self.fs.walk(&self.path).min_depth(1) {
+            let (path, status) = match entry {
+                Ok(entry) => entry,
+                // prefix without objects
+                Err(e) if e.kind() == ErrorKind::NotFound => break,
+                Err(e) => return Err(to_error(&self.path, e)),
+            };
+            if status.is_file() && !is_staged(&path) {
+                objects.push(self.meta(&path, &status)?);
+            }
+        }
+        Ok(objects)
+    }
+
+    fn list_with_delimiter(&self) -> Result<ListResult> {
+        let mut result = ListResult {
+            common_prefixes: Vec::new(),
+            objects: Vec::new(),
+        };
+        // listing of a file returns the file itself, objects are not prefixes
+        match self.fs.get_file_status(&self.path) {
+            Ok(status) if status.is_directory() => (),
+            Ok(_) => return Ok(result),
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(result),
+            Err(e) => return Err(to_error(&self.path, e)),
+        }
+        let children = self
+            .fs
+            .list_status(&self.path)
+            .map_err(|e| to_error(&self.path, e))?;
+
+        for status in children {
+            let path = format!(
+                "{}/{}",
+                self.path.trim_end_matches('/'),
+                base_name(status.name())
+            );
+            if status.is_directory() {
+                result.common_prefixes.push(self.location(&path)?);
+            } else if !is_staged(&path) {
+                result.objects.push(self.meta(&path, &status)?);
+            }
+        }
+        Ok(result)
+    }
+}
+
+/// E-tag of a file, derived from modification time and length
+fn e_tag(status: &FileStatus) -> String {
+    format!("{:x}-{:x}", status.last_modified(), status.len())
+}
+
+fn staged_path(path: &str) -> String {
+    format!(
+        "{}#{}-{}",
+        path,
+        std::process::id(),
+        STAGED.fetch_add(1, Ordering::Relaxed)
+    )
+}
+
+/// Is a file an in progress write, named ```<path>#<pid>-<n>```
+fn is_staged(path: &str) -> bool {
+    match base_name(path).rsplit_once('#') {
+        Some((_, suffix)) => {
+            !suffix.is_empty() && suffix.chars().all(|c| c.is_ascii_digit() || c == '-')
+        }
+        None => false,
+    }
+}
+
+fn to_error(path: &str, e: std::io::Error) -> object_store::Error {
+    match e.kind() {
+        ErrorKind::NotFound => object_store::Error::NotFound {
+            path: path.to_string(),
+            source: Box::new(e),
+        },
+        ErrorKind::AlreadyExists => object_store::Error::AlreadyExists {
+            path: path.to_string(),
+            source: Box::new(e),
+        },
+        ErrorKind::PermissionDenied => object_store::Error::PermissionDenied {
+            path: path.to_string(),
+            source: Box::new(e),
+        },
+        _ => object_store::Error::Generic {
+            store: STORE,
+            source: Box::new(e),
+        },
+    }
+}
+
+/// Execute a blocking call on tokio blocking pool if called from within
+/// a runtime
+async fn maybe_spawn_blocking<F, T>(f: F) -> Result<T>
+where
+    F: FnOnce() -> Result<T> + Send + 'static,
+    T: Send + 'static,
+{
+    match tokio::runtime::Handle::try_current() {
+        Ok(runtime) => runtime.spawn_blocking(f).await?,
+        Err(_) => f(),
+    }
+}
+
+#[async_trait]
+impl ObjectStore for HdfsObjectStore {
+    async fn put_opts(
+        &self,
+        location: &Path,
+        payload: PutPayload,
+        opts: PutOptions,
+    ) -> Result<PutResult> {
+        let overwrite = match opts.mode {
+            PutMode::Overwrite => true,
+            PutMode::Create => false,
+            PutMode::Update(_) => return Err(object_store::Error::NotImplemented),
+        };
+
+        let ctx = self.context(location);
+        maybe_spawn_blocking(move || {
+            if !overwrite && ctx.fs.exist(&ctx.path) {
+                return Err(object_store::Error::AlreadyExists {
+                    path: ctx.path.clone(),
+                    source: format!("{} already exists", ctx.path).into(),
+                });
+            }
+
+            let staged = staged_path(&ctx.path);
+            let written = (|| {
+                let mut file = ctx.fs.create_with_overwrite(&staged, true)?;
+                for bytes in payload.iter() {
+                    file.write_all(bytes)?;
+                }
+                file.close()
+            })();
+            if let Err(e) = written {
+                let _ = ctx.fs.delete(&staged, false);
+                return Err(to_error(&ctx.path, e));
+            }
+            ctx.commit(&staged, overwrite)
+        })
+        .await
+    }
+
+    async fn put_multipart_opts(
+        &self,
+        location: &Path,
+        _opts: PutMultipartOptions,
+    ) -> Result<Box<dyn MultipartUpload>> {
+        let ctx = self.context(location);
+        let (sender, receiver) = mpsc::channel();
+        let path = ctx.path.clone();
+        std::thread::Builder::new()
+            .name("hdfs-upload".to_string())
+            .spawn(move || upload(ctx, receiver))
+            .map_err(|e| to_error(&path, e))?;
+        Ok(Box::new(HdfsUpload { path, sender }))
+    }
+
+    async fn get_opts(&self, location: &Path, options: GetOptions) -> Result<GetResult> {
+        let ctx = self.context(location);
+        let head = options.head;
+        let (meta, range) = {
+            let ctx = ctx.clone();
+            maybe_spawn_blocking(move || {
+                let status = ctx.status()?;
+                let meta = ctx.meta(&ctx.path, &status)?;
+                options.check_preconditions(&meta)?;
+                let range = match &options.range {
+                    Some(range) => {
+                        range
+                            .as_range(meta.size)
+                            .map_err(|e| object_store::Error::Generic {
+                                store: STORE,
+                                source: Box::new(e),
+                            })?
+                    }
+                    None => 0..meta.size,
+                };
+                Ok((meta, range))
+            })
+            .await?
+        };
+
+        let end = if head { range.start } else { range.end };
+        let chunks = stream::try_unfold(range.start, move |offset| {
+            let ctx = ctx.clone();
+            async move {
+                if offset >= end {
+                    return Ok(None);
+                }
+                let len = (end - offset).min(CHUNK_SIZE);
+                let bytes = maybe_spawn_blocking(move || ctx.read(offset, len)).await?;
+                Ok(Some((bytes, offset + len)))
+            }
+        });
+
+        Ok(GetResult {
+            payload: GetResultPayload::Stream(chunks.boxed()),
+            meta,
+            range,
+            attributes: Default::default(),
+        })
+    }
+
+    async fn delete(&self, location: &Path) -> Result<()> {
+        let ctx = self.context(location);
+        maybe_spawn_blocking(move || {
+            ctx.status()?;
+            ctx.fs
+                .delete(&ctx.path, false)
+                .map(|_| ())
+                .map_err(|e| to_error(&ctx.path, e))
+        })
+        .await
+    }
+
+    fn list(&self, prefix: Option<&Path>) -> BoxStream<'static, Result<ObjectMeta>> {
+        let ctx = self.context(prefix.unwrap_or(&Path::default()));
+        stream::once(maybe_spawn_blocking(move || ctx.list()))
+            .map_ok(|objects| stream::iter(objects.into_iter().map(Ok)))
+            .try_flatten()
+            .boxed()
+    }
+
+    async fn list_with_delimiter(&self, prefix: Option<&Path>) -> Result<ListResult> {
+        let ctx = self.context(prefix.unwrap_or(&Path::default()));
+        maybe_spawn_blocking(move || ctx.list_with_delimiter()).await
+    }
+
+    async fn copy(&self, from: &Path, to: &Path) -> Result<()> {
+        let (from, to) = (self.context(from), self.context(to));
+        maybe_spawn_blocking(move || from.copy(&to, true)).await
+    }
+
+    async fn copy_if_not_exists(&self, from: &Path, to: &Path) -> Result<()> {
+        let (from, to) = (self.context(from), self.context(to));
+        maybe_spawn_blocking(move || from.copy(&to, false)).await
+    }
+
+    async fn rename(&self, from: &Path, to: &Path) -> Result<()> {
+        let (from, to) = (self.context(from), self.hdfs_path(to));
+        maybe_spawn_blocking(move || {
+            from.status()?;
+            from.rename(&from.path, &to, true)
+        })
+        .await
+    }
+
+    async fn rename_if_not_exists(&self, from: &Path, to: &Path) -> Result<()> {
+        let (from, to) = (self.context(from), self.hdfs_path(to));
+        maybe_spawn_blocking(move || {
+            from.status()?;
+            from.rename(&from.path, &to, false)
+        })
+        .await
+    }
+}
+
+/// Command sent to the thread writing a multipart upload
+enum UploadCommand {
+    Write(PutPayload, oneshot::Sender<std::io::Result<()>>),
+    Complete(oneshot::Sender<Result<PutResult>>),
+    Abort(oneshot::Sender<std::io::Result<()>>),
+}
+
+/// Multipart upload streaming parts to a staged file in the order
+/// [MultipartUpload::put_part] has been called, written by a dedicated
+/// thread as open files can not be shared between threads.
+struct HdfsUpload {
+    path: String,
+    sender: mpsc::Sender<UploadCommand>,
+}
+
+impl Debug for HdfsUpload {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("HdfsUpload")
+            .field("path", &self.path)
+            .finish()
+    }
+}
+
+impl HdfsUpload {
+    fn closed(&self) -> object_store::Error {
+        to_error(
+            &self.path,
+            std::io::Error::new(ErrorKind::BrokenPipe, "upload has been closed"),
+        )
+    }
+}
+
+#[async_trait]
+impl MultipartUpload for HdfsUpload {
+    fn put_part(&mut self, data: PutPayload) -> UploadPart {
+        let (reply, result) = oneshot::channel();
+        let sent = self.sender.send(UploadCommand::Write(data, reply));
+        let closed = self.closed();
+        let path = self.path.clone();
+        Box::pin(async move {
+            if sent.is_err() {
+                return Err(closed);
+            }
+            match result.await {
+                Ok(result) => result.map_err(|e| to_error(&path, e)),
+                Err(_) => Err(closed),
+            }
+        })
+    }
+
+    async fn complete(&mut self) -> Result<PutResult> {
+        let (reply, result) = oneshot::channel();
+        self.sender
+            .send(UploadCommand::Complete(reply))
+            .map_err(|_| self.closed())?;
+        result.await.map_err(|_| self.closed())?
+    }
+
+    async fn abort(&mut self) -> Result<()> {
+        let (reply, result) = oneshot::channel();
+        self.sender
+            .send(UploadCommand::Abort(reply))
+            .map_err(|_| self.closed())?;
+        let path = self.path.clone();
+        result
+            .await
+            .map_err(|_| self.closed())?
+            .map_err(|e| to_error(&path, e))
+    }
+}
+
+/// Body of the upload thread, staged file is removed unless upload completes
+fn upload(ctx: Context, commands: mpsc::Receiver<UploadCommand>) {
+    let staged = staged_path(&ctx.path);
+    let mut file = ctx.fs.create_with_overwrite(&staged, true);
+
+    for command in commands {
+        match command {
+            UploadCommand::Write(payload, reply) => {
+                let result = match &mut file {
+                    Ok(file) => payload.iter().try_for_each(|bytes| file.write_all(bytes)),
+                    Err(e) => Err(std::io::Error::new(e.kind(), e.to_string())),
+                };
+                let _ = reply.send(result);
+            }
+            UploadCommand::Complete(reply) => {
+                // staged file has to be closed successfully before it is committed
+                let result = match file.and_then(|file| file.close()) {
+                    Ok(()) => ctx.commit(&staged, true),
+                    Err(e) => {
+                        let _ = ctx.fs.delete(&staged, false);
+                        Err(to_error(&ctx.path, e))
+                    }
+                };
+                let _ = reply.send(result);
+                return;
+            }
+            UploadCommand::Abort(reply) => {
+                drop(file);
+                let _ = reply.send(ctx.fs.delete(&staged, false).map(|_| ()));
+                return;
+            }
+        }
+    }
+
+    debug!("upload of [{}] dropped, removing staged file", ctx.path);
+    drop(file);
+    let _ = ctx.fs.delete(&staged, false);
+}
@@ -0,0 +1,335 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Parquet readers over HDFS files, enabled by ```parquet``` feature
+//!
+//! [HdfsParquetReader] implements parquet's ```ChunkReader``` used by the
+//! synchronous readers, [AsyncHdfsParquetReader] implements
+//! ```AsyncFileReader``` used by ```ParquetRecordBatchStreamBuilder```,
+//! its reads are executed on the [BlockingPool] of an [AsyncHdfsFs].
+//!
+//! Tail of the file is read when a reader is opened, so footer and metadata
+//! are served without further round trips to data nodes. Metadata is parsed
+//! once and shared by clones of a reader. Ranges requested together by the
+//! async reader are merged into a single read when the gap between them is
+//! small enough.
+//!
+//! ```ignore
+//! let fs = AsyncHdfsFs::new(HdfsRegistry::new().get("hdfs://localhost:9000")?, 8)?;
+//! let reader = AsyncHdfsParquetReader::open(&fs, "/data/part-0.parquet", &Default::default()).await?;
+//! let mut batches = ParquetRecordBatchStreamBuilder::new(reader).await?.build()?;
+//! while let Some(batch) = batches.try_next().await? {
+//!     println!("{} rows", batch.num_rows());
+//! }
+//! ```
+
+use crate::async_fs::{AsyncHdfsFs, BlockingPool, OwnedFile};
+use crate::dfs::{HdfsFile, HdfsFs};
+use bytes::Bytes;
+use futures::future::BoxFuture;
+use futures::FutureExt;
+use log::debug;
+use parquet::arrow::async_reader::AsyncFileReader;
+use parquet::errors::Result;
+use parquet::file::metadata::{ParquetMetaData, ParquetMetaDataReader};
+use parquet::file::reader::{ChunkReader, Length};
+use std::cmp::min;
+use std::io::{Error, ErrorKind, Read};
+use std::ops::Range;
+use std::sync::{Arc, Mutex, OnceLock};
+
+/// Options of parquet readers
+#[derive(Debug, Clone)]
+pub struct ParquetReaderOptions {
+    /// Number of bytes read from the end of the file when it is opened,
+    /// large enough to hold the footer of most files
+    pub footer_prefetch: usize,
+    /// Ranges requested together which are closer than this are read at once
+    pub coalesce_gap: usize,
+}
+
+impl Default for ParquetReaderOptions {
+    fn default() -> Self {
+        ParquetReaderOptions {
+            footer_prefetch: 64 * 1024,
+            coalesce_gap: 1024 * 1024,
+        }
+    }
+}
+
+struct Inner {
+    path: String,
+    // positional reads seek the file, so reads are serialized
+    file: Mutex<OwnedFile>,
+    len: u64,
+    /// last bytes of the file
+    tail: Bytes,
+    metadata: OnceLock<Arc<ParquetMetaData>>,
+}
+
+impl Inner {
+    fn tail_start(&self) -> u64 {
+        self.len - self.tail.len() as u64
+    }
+}
+
+/// Parquet ```ChunkReader``` over a file, clones share the open file.
+///
+/// ```ignore
+/// let reader = HdfsParquetReader::open(fs, "/data/part-0.parquet", &Default::default())?;
+/// for batch in ParquetRecordBatchReaderBuilder::try_new(reader)?.build()? {
+///     println!("{} rows", batch?.num_rows());
+/// }
+/// ```
+#[derive(Clone)]
+pub struct HdfsParquetReader {
+    inner: Arc<Inner>,
+}
+
+impl HdfsParquetReader {
+    /// Open ```path``` and read its tail.
+    pub fn open(
+        fs: Arc<HdfsFs>,
+        path: &str,
+        opts: &ParquetReaderOptions,
+    ) -> Result<HdfsParquetReader, Error> {
+        let file = OwnedFile::open(fs, |fs| fs.open(path))?;
+        let len = file.file.get_file_status()?.len() as u64;
+
+        let mut tail = vec![0u8; min(opts.footer_prefetch as u64, len) as usize];
+        read_fully(&file.file, len - tail.len() as u64, &mut tail)?;
+        debug!("parquet file [{}] of {} bytes opened", path, len);
+
+        Ok(HdfsParquetReader {
+            inner: Arc::new(Inner {
+                path: path.to_string(),
+                file: Mutex::new(file),
+                len,
+                tail: Bytes::from(tail),
+                metadata: OnceLock::new(),
+            }),
+        })
+    }
+
+    pub fn path(&self) -> &str {
+        &self.inner.path
+    }
+
+    /// Parquet metadata, parsed on first call
+    pub fn metadata(&self) -> Result<Arc<ParquetMetaData>> {
+        if let Some(metadata) = self.inner.metadata.get() {
+            return Ok(metadata.clone());
+        }
+        let metadata = Arc::new(ParquetMetaDataReader::new().parse_and_finish(self)?);
+        Ok(self.inner.metadata.get_or_init(|| metadata).clone())
+    }
+
+    /// Read ```length``` bytes starting at ```start```
+    fn read_range(&self, start: u64, length: usize) -> Result<Bytes, Error> {
+        let end = start + length as u64;
+        if end > self.inner.len {
+            return Err(Error::new(
+                ErrorKind::UnexpectedEof,
+                format!(
+                    "range {}..{} is past the end of [{}] of {} bytes",
+                    start, end, self.inner.path, self.inner.len
+                ),
+            ));
+        }
+
+        let tail_start = self.inner.tail_start();
+        if start >= tail_start {
+            let from = (start - tail_start) as usize;
+            return Ok(self.inner.tail.slice(from..from + length));
+        }
+
+        let mut buf = vec![0u8; length];
+        read_fully(&self.inner.file.lock().unwrap().file, start, &mut buf)?;
+        Ok(Bytes::from(buf))
+    }
+
+    /// Read at most ```buf.len()``` bytes at ```pos```, returns 0 at the end of the file
+    fn read_at(&self, pos: u64, buf: &mut [u8]) -> Result<usize, Error> {
+        let len = min(buf.len() as u64, self.inner.len.saturating_sub(pos)) as usize;
+        if len == 0 {
+            return Ok(0);
+        }
+
+        let tail_start = self.inner.tail_start();
+        if pos >= tail_start {
+            let from = (pos - tail_start) as usize;
+            buf[..len].copy_from_slice(&self.inner.tail[from..from + len]);
+            return Ok(len);
+        }
+
+        let file = self.inner.file.lock().unwrap();
+        file.file.read_with_pos(pos as i64, &mut buf[..len])
+    }
+}
+
+impl Length for HdfsParquetReader {
+    fn len(&self) -> u64 {
+        self.inner.len
+    }
+}
+
+impl ChunkReader for HdfsParquetReader {
+    type T = ChunkRead;
+
+    fn get_read(&self, start: u64) -> Result<ChunkRead> {
+        Ok(ChunkRead {
+            reader: self.clone(),
+            pos: start,
+        })
+    }
+
+    fn get_bytes(&self, start: u64, length: usize) -> Result<Bytes> {
+        Ok(self.read_range(start, length)?)
+    }
+}
+
+/// Sequential reader returned by ```ChunkReader::get_read```
+pub struct ChunkRead {
+    reader: HdfsParquetReader,
+    pos: u64,
+}
+
+impl Read for ChunkRead {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let read = self.reader.read_at(self.pos, buf)?;
+        self.pos += read as u64;
+        Ok(read)
+    }
+}
+
+/// Parquet ```AsyncFileReader``` reading on the pool of an [AsyncHdfsFs]
+#[derive(Clone)]
+pub struct AsyncHdfsParquetReader {
+    reader: HdfsParquetReader,
+    pool: Arc<BlockingPool>,
+    coalesce_gap: usize,
+}
+
+impl AsyncHdfsParquetReader {
+    /// Open ```path``` and read its tail.
+    pub async fn open(
+        fs: &AsyncHdfsFs,
+        path: &str,
+        opts: &ParquetReaderOptions,
+    ) -> Result<AsyncHdfsParquetReader, Error> {
+        let hdfs = fs.fs().clone();
+        let path = path.to_string();
+        let open_opts = opts.clone();
+        let reader = fs
+            .pool()
+            .spawn(move || HdfsParquetReader::open(hdfs, &path, &open_opts))
+            .await?;
+
+        Ok(AsyncHdfsParquetReader {
+            reader,
+            pool: fs.pool().clone(),
+            coalesce_gap: opts.coalesce_gap,
+        })
+    }
+
+    /// Synchronous reader sharing the open file and metadata
+    pub fn reader(&self) -> &HdfsParquetReader {
+        &self.reader
+    }
+}
+
+impl AsyncFileReader for AsyncHdfsParquetReader {
+    fn get_bytes(&mut self, range: Range<usize>) -> BoxFuture<'_, Result<Bytes>> {
+        let reader = self.reader.clone();
+        let read = self
+            .pool
+            .spawn(move || reader.read_range(range.start as u64, range.len()));
+        async move { Ok(read.await?) }.boxed()
+    }
+
+    fn get_byte_ranges(&mut self, ranges: Vec<Range<usize>>) -> BoxFuture<'_, Result<Vec<Bytes>>> {
+        let reader = self.reader.clone();
+        let gap = self.coalesce_gap;
+        let read = self.pool.spawn(move || {
+            let merged = coalesce_ranges(&ranges, gap);
+            debug!(
+                "reading {} ranges of [{}] as {}",
+                ranges.len(),
+                reader.path(),
+                merged.len()
+            );
+            let fetched = merged
+                .iter()
+                .map(|range| reader.read_range(range.start as u64, range.len()))
+                .collect::<Result<Vec<_>, Error>>()?;
+
+            Ok(ranges
+                .iter()
+                .map(|range| {
+                    // first merged range ending at or after the range contains it
+                    let idx = merged.partition_point(|merged| merged.end < range.end);
+                    let offset = merged[idx].start;
+                    fetched[idx].slice(range.start - offset..range.end - offset)
+                })
+                .collect())
+        });
+        async move { Ok(read.await?) }.boxed()
+    }
+
+    fn get_metadata(&mut self) -> BoxFuture<'_, Result<Arc<ParquetMetaData>>> {
+        if let Some(metadata) = self.reader.inner.metadata.get() {
+            let metadata = metadata.clone();
+            return async move { Ok(metadata) }.boxed();
+        }
+        let reader = self.reader.clone();
+        let parse = self.pool.spawn(move || Ok(reader.metadata()));
+        async move { parse.await? }.boxed()
+    }
+}
+
+/// Sorted ranges covering ```ranges```, ranges closer than ```gap``` are merged
+fn coalesce_ranges(ranges: &[Range<usize>], gap: usize) -> Vec<Range<usize>> {
+    let mut sorted = ranges.to_vec();
+    sorted.sort_by_key(|range| range.start);
+
+    let mut merged: Vec<Range<usize>> = Vec::with_capacity(sorted.len());
+    for range in sorted {
+        match merged.last_mut() {
+            Some(last) if range.start <= last.end.saturating_add(gap) => {
+                last.end = last.end.max(range.end)
+            }
+            _ => merged.push(range),
+        }
+    }
+    merged
+}
+
+fn read_fully(file: &HdfsFile<'_>, pos: u64, buf: &mut [u8]) -> Result<(), Error> {
+    let mut read = 0;
+    while read < buf.len() {
+        match file.read_with_pos((pos + read as u64) as i64, &mut buf[read..])? {
+            0 => {
+                return Err(Error::new(
+                    ErrorKind::UnexpectedEof,
+                    format!("unexpected end of file at {}", pos + read as u64),
+                ))
+            }
+            n => read += n,
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,425 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! File system permission
+//!
+//! Mirrors Hadoop's ```FsPermission``` and ```FsAction```, including
+//! octal (```0755```) and symbolic (```u+rwx,g-w,o=```) modes used by
+//! ```hadoop fs -chmod``` and umask handling for newly created files.
+
+use crate::metadata::FileKind;
+use crate::util::HdfsUtil;
+use std::fmt::{Display, Formatter};
+use std::io::{Error, ErrorKind};
+use std::str::FromStr;
+
+/// Configuration key of umask applied to new files and directories
+pub const UMASK_KEY: &str = "fs.permissions.umask-mode";
+
+/// Umask used if it is not configured
+pub const DEFAULT_UMASK: u16 = 0o022;
+
+const STICKY_BIT: u16 = 0o1000;
+
+/// Actions which can be performed on a file or directory
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FsAction {
+    None,
+    Execute,
+    Write,
+    WriteExecute,
+    Read,
+    ReadExecute,
+    ReadWrite,
+    All,
+}
+
+impl FsAction {
+    const VALUES: [FsAction; 8] = [
+        FsAction::None,
+        FsAction::Execute,
+        FsAction::Write,
+        FsAction::WriteExecute,
+        FsAction::Read,
+        FsAction::ReadExecute,
+        FsAction::ReadWrite,
+        FsAction::All,
+    ];
+
+    /// Action from the lowest three bits of ```bits```
+    pub fn from_bits(bits: u16) -> FsAction {
+        FsAction::VALUES[(bits & 0o7) as usize]
+    }
+
+    /// Action as ```rwx``` bits
+    pub fn bits(&self) -> u16 {
+        *self as u16
+    }
+
+    /// Does this action include all of ```other```
+    pub fn implies(&self, other: FsAction) -> bool {
+        self.bits() & other.bits() == other.bits()
+    }
+
+    /// Union of both actions
+    pub fn or(&self, other: FsAction) -> FsAction {
+        FsAction::from_bits(self.bits() | other.bits())
+    }
+
+    /// Intersection of both actions
+    pub fn and(&self, other: FsAction) -> FsAction {
+        FsAction::from_bits(self.bits() & other.bits())
+    }
+
+    /// Complement of this action
+    pub fn not(&self) -> FsAction {
+        FsAction::from_bits(!self.bits())
+    }
+}
+
+impl Display for FsAction {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let bits = self.bits();
+        let flag = |bit: u16, c: char| if bits & bit != 0 { c } else { '-' };
+        write!(f, "{}{}{}", flag(4, 'r'), flag(2, 'w'), flag(1, 'x'))
+    }
+}
+
+/// Permission of a file or directory, as used by HDFS.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FsPermission {
+    mode: u16,
+}
+
+impl FsPermission {
+    /// Create permission from actions of user, group and others
+    pub fn new(user: FsAction, group: FsAction, other: FsAction, sticky: bool) -> FsPermission {
+        let sticky = if sticky { STICKY_BIT } else { 0 };
+        FsPermission {
+            mode: sticky | user.bits() << 6 | group.bits() << 3 | other.bits(),
+        }
+    }
+
+    /// Create permission from mode bits such as ```0o755```
+    pub fn from_mode(mode: u16) -> FsPermission {
+        FsPermission {
+            mode: mode & 0o1777,
+        }
+    }
+
+    /// Parse octal mode such as ```755``` or ```1777```
+    pub fn from_octal(mode: &str) -> Result<FsPermission, Error> {
+        if mode.is_empty() || mode.len() > 4 || !mode.bytes().all(|b| (b'0'..=b'7').contains(&b)) {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("invalid octal mode [{}]", mode),
+            ));
+        }
+        let mode = u16::from_str_radix(mode, 8).unwrap();
+        if mode > 0o1777 {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("unsupported mode bits [{:o}]", mode),
+            ));
+        }
+        Ok(FsPermission::from_mode(mode))
+    }
+
+    /// Default permission of new files before umask, ```rw-rw-rw-```
+    pub fn file_default() -> FsPermission {
+        FsPermission::from_mode(0o666)
+    }
+
+    /// Default permission of new directories before umask, ```rwxrwxrwx```
+    pub fn dir_default() -> FsPermission {
+        FsPermission::from_mode(0o777)
+    }
+
+    /// Umask from ```fs.permissions.umask-mode``` of client configuration,
+    /// octal and symbolic values are supported.
+    pub fn umask() -> FsPermission {
+        let default = FsPermission::from_mode(DEFAULT_UMASK);
+        match HdfsUtil::get_conf_str(UMASK_KEY) {
+            Some(umask) => match FsPermission::from_octal(umask.trim()) {
+                Ok(umask) => umask,
+                // symbolic umask specifies allowed permissions, as in `umask -S`
+                Err(_) => match SymbolicMode::parse(umask.trim()) {
+                    Ok(mode) => FsPermission::from_mode(
+                        !mode.apply(FsPermission::from_mode(0), false).mode & 0o777,
+                    ),
+                    Err(_) => default,
+                },
+            },
+            None => default,
+        }
+    }
+
+    /// Permission with ```umask``` bits cleared
+    pub fn apply_umask(&self, umask: FsPermission) -> FsPermission {
+        FsPermission::from_mode(self.mode & !umask.mode)
+    }
+
+    /// Mode bits of the permission
+    pub fn mode(&self) -> u16 {
+        self.mode
+    }
+
+    pub fn user_action(&self) -> FsAction {
+        FsAction::from_bits(self.mode >> 6)
+    }
+
+    pub fn group_action(&self) -> FsAction {
+        FsAction::from_bits(self.mode >> 3)
+    }
+
+    pub fn other_action(&self) -> FsAction {
+        FsAction::from_bits(self.mode)
+    }
+
+    pub fn sticky_bit(&self) -> bool {
+        self.mode & STICKY_BIT != 0
+    }
+
+    /// Apply octal or symbolic ```mode```, as ```hadoop fs -chmod``` does.
+    /// ```is_dir``` is needed to resolve conditional execute (```X```).
+    pub fn apply_mode(&self, mode: &str, is_dir: bool) -> Result<FsPermission, Error> {
+        match FsPermission::from_octal(mode) {
+            Ok(permission) => Ok(permission),
+            Err(_) => Ok(SymbolicMode::parse(mode)?.apply(*self, is_dir)),
+        }
+    }
+
+    /// Render as ```ls``` does, e.g. ```drwxr-xr-x```
+    pub fn to_ls_string(&self, kind: FileKind) -> String {
+        let prefix = match kind {
+            FileKind::Directory => 'd',
+            FileKind::File => '-',
+        };
+        format!("{}{}", prefix, self)
+    }
+}
+
+impl Display for FsPermission {
+    /// Renders as ```rwxr-xr-t```, sticky bit replaces execute of others
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let other = self.other_action().to_string();
+        let other = if self.sticky_bit() {
+            let sticky = if self.other_action().implies(FsAction::Execute) {
+                't'
+            } else {
+                'T'
+            };
+            format!("{}{}", &other[..2], sticky)
+        } else {
+            other
+        };
+        write!(f, "{}{}{}", self.user_action(), self.group_action(), other)
+    }
+}
+
+impl FromStr for FsPermission {
+    type Err = Error;
+
+    /// Parses octal (```755```) or ```ls``` style (```rwxr-xr-x```) permission
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Ok(permission) = FsPermission::from_octal(s) {
+            return Ok(permission);
+        }
+
+        let chars: Vec<char> = s.chars().collect();
+        let chars = match chars.len() {
+            10 => &chars[1..],
+            9 => &chars[..],
+            _ => {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    format!("invalid permission [{}]", s),
+                ))
+            }
+        };
+
+        let mut mode = 0;
+        for (idx, c) in chars.iter().enumerate() {
+            let bit = 1 << (8 - idx);
+            match (idx % 3, c) {
+                (_, '-') => (),
+                (0, 'r') | (1, 'w') | (2, 'x') => mode |= bit,
+                (2, 't') if idx == 8 => mode |= bit | STICKY_BIT,
+                (2, 'T') if idx == 8 => mode |= STICKY_BIT,
+                _ => {
+                    return Err(Error::new(
+                        ErrorKind::InvalidInput,
+                        format!("invalid permission [{}]", s),
+                    ))
+                }
+            }
+        }
+
+        Ok(FsPermission::from_mode(mode))
+    }
+}
+
+impl From<i16> for FsPermission {
+    fn from(mode: i16) -> Self {
+        FsPermission::from_mode(mode as u16)
+    }
+}
+
+impl From<FsPermission> for i16 {
+    fn from(permission: FsPermission) -> Self {
+        permission.mode as i16
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Add,
+    Remove,
+    Set,
+}
+
+#[derive(Debug, Clone)]
+struct Clause {
+    /// mask of affected rwx bits, for user, group and other
+    who: u16,
+    op: Op,
+    /// rwx bits, repeated for user, group and other
+    perm: u16,
+    conditional_execute: bool,
+    sticky: bool,
+}
+
+/// Parsed symbolic mode such as ```u+rwx,g-w,o=```
+#[derive(Debug, Clone)]
+pub struct SymbolicMode {
+    clauses: Vec<Clause>,
+}
+
+impl SymbolicMode {
+    /// Parse comma separated clauses of form ```[ugoa]*([-+=][rwxXt]*)+```.
+    /// Missing ```who``` is equivalent to ```a```.
+    pub fn parse(mode: &str) -> Result<SymbolicMode, Error> {
+        let invalid = || {
+            Error::new(
+                ErrorKind::InvalidInput,
+                format!("invalid symbolic mode [{}]", mode),
+            )
+        };
+
+        let mut clauses = Vec::new();
+        for clause in mode.split(',') {
+            let mut chars = clause.chars().peekable();
+            let mut who = 0;
+            while let Some(c) = chars.peek() {
+                who |= match c {
+                    'u' => 0o700,
+                    'g' => 0o070,
+                    'o' => 0o007,
+                    'a' => 0o777,
+                    _ => break,
+                };
+                chars.next();
+            }
+            if who == 0 {
+                who = 0o777;
+            }
+
+            let mut has_op = false;
+            while let Some(c) = chars.next() {
+                let op = match c {
+                    '+' => Op::Add,
+                    '-' => Op::Remove,
+                    '=' => Op::Set,
+                    _ => return Err(invalid()),
+                };
+                has_op = true;
+
+                let mut perm = 0;
+                let mut conditional_execute = false;
+                let mut sticky = false;
+                while let Some(c) = chars.peek() {
+                    match c {
+                        'r' => perm |= 0o444,
+                        'w' => perm |= 0o222,
+                        'x' => perm |= 0o111,
+                        'X' => conditional_execute = true,
+                        't' => sticky = true,
+                        _ => break,
+                    }
+                    chars.next();
+                }
+
+                clauses.push(Clause {
+                    who,
+                    op,
+                    perm,
+                    conditional_execute,
+                    sticky,
+                });
+            }
+
+            if !has_op {
+                return Err(invalid());
+            }
+        }
+
+        Ok(SymbolicMode { clauses })
+    }
+
+    /// Apply the mode on ```permission``` of a file or directory
+    pub fn apply(&self, permission: FsPermission, is_dir: bool) -> FsPermission {
+        let mut mode = permission.mode;
+        for clause in &self.clauses {
+            let mut perm = clause.perm;
+            // X adds execute to directories and files executable by someone
+            if clause.conditional_execute && (is_dir || mode & 0o111 != 0) {
+                perm |= 0o111;
+            }
+            let perm = perm & clause.who;
+            // sticky bit is only applicable to all or others
+            let sticky = if clause.sticky && clause.who & 0o007 != 0 {
+                STICKY_BIT
+            } else {
+                0
+            };
+
+            mode = match clause.op {
+                Op::Add => mode | perm | sticky,
+                Op::Remove => mode & !(perm | sticky),
+                Op::Set => {
+                    let cleared = mode & !clause.who;
+                    let cleared = if clause.who & 0o007 != 0 {
+                        cleared & !STICKY_BIT
+                    } else {
+                        cleared
+                    };
+                    cleared | perm | sticky
+                }
+            };
+        }
+        FsPermission::from_mode(mode)
+    }
+}
+
+impl FromStr for SymbolicMode {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        SymbolicMode::parse(s)
+    }
+}
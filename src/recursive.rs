@@ -0,0 +1,181 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Recursive (```-R```) variants of chmod, chown and set replication
+
+use crate::dfs::{FileStatus, HdfsFs};
+use crate::walk::error_path;
+use log::{debug, info};
+use std::io::Error;
+use std::sync::Mutex;
+
+/// Number of entries collected from the walk before they are processed
+const BATCH_SIZE: usize = 1024;
+
+/// Predicate selecting paths which should be modified
+pub type PathFilter<'a> = &'a (dyn Fn(&str, &FileStatus) -> bool + Sync);
+
+/// Options of recursive operations such as [HdfsFs::chmod_recursive]
+#[derive(Clone, Copy, Default)]
+pub struct RecursiveOptions<'a> {
+    /// Number of threads modifying paths concurrently, values lower than 2
+    /// process paths sequentially
+    pub parallelism: usize,
+    /// Only report what would be changed
+    pub dry_run: bool,
+    /// Only paths for which filter returns ```true``` are modified,
+    /// directories are traversed regardless of the filter
+    pub filter: Option<PathFilter<'a>>,
+}
+
+/// Outcome of a recursive operation
+#[derive(Debug, Default)]
+pub struct RecursiveReport {
+    /// Number of paths modified, or which would be modified in dry run mode
+    pub succeeded: u64,
+    /// Number of paths excluded by the filter
+    pub skipped: u64,
+    /// Paths which could not be modified or listed
+    pub failures: Vec<(String, Error)>,
+}
+
+impl RecursiveReport {
+    /// Were all paths processed successfully
+    pub fn is_success(&self) -> bool {
+        self.failures.is_empty()
+    }
+}
+
+#[derive(Debug)]
+pub(crate) enum Operation<'a> {
+    Chmod(i16),
+    /// Octal or symbolic mode, applied to the permission of each path
+    ApplyMode(&'a str),
+    Chown(&'a str, &'a str),
+    SetReplication(i16),
+}
+
+impl<'a> Operation<'a> {
+    /// Replication is only defined for files, directories are left intact
+    fn applies_to(&self, status: &FileStatus) -> bool {
+        match self {
+            Operation::SetReplication(_) => status.is_file(),
+            _ => true,
+        }
+    }
+
+    fn apply(&self, fs: &HdfsFs, path: &str, status: &FileStatus) -> Result<(), Error> {
+        match self {
+            Operation::Chmod(mode) => fs.try_chmod(path, *mode),
+            Operation::ApplyMode(mode) => {
+                let permission = status
+                    .fs_permission()
+                    .apply_mode(mode, status.is_directory())?;
+                fs.try_set_permission(path, permission)
+            }
+            Operation::Chown(owner, group) => fs.try_chown(path, owner, group),
+            Operation::SetReplication(num) => fs.set_replication(path, *num).map(|_| ()),
+        }
+    }
+}
+
+fn process_batch(
+    fs: &HdfsFs,
+    operation: &Operation,
+    opts: &RecursiveOptions,
+    batch: Vec<(String, FileStatus)>,
+    report: &mut RecursiveReport,
+) {
+    if opts.dry_run {
+        for (path, _) in batch {
+            info!("dry run: {:?} [{}]", operation, path);
+            report.succeeded += 1;
+        }
+        return;
+    }
+
+    if opts.parallelism < 2 {
+        for (path, status) in batch {
+            match operation.apply(fs, &path, &status) {
+                Ok(()) => report.succeeded += 1,
+                Err(e) => report.failures.push((path, e)),
+            }
+        }
+        return;
+    }
+
+    let queue = Mutex::new(batch);
+    let report = Mutex::new(report);
+    std::thread::scope(|scope| {
+        for _ in 0..opts.parallelism {
+            scope.spawn(|| loop {
+                let (path, status) = match queue.lock().unwrap().pop() {
+                    Some(entry) => entry,
+                    None => return,
+                };
+                let result = operation.apply(fs, &path, &status);
+                let mut report = report.lock().unwrap();
+                match result {
+                    Ok(()) => report.succeeded += 1,
+                    Err(e) => report.failures.push((path, e)),
+                }
+            });
+        }
+    });
+}
+
+/// Applies ```operation``` on ```path``` and all its descendants
+pub(crate) fn apply_recursive(
+    fs: &HdfsFs,
+    path: &str,
+    operation: Operation,
+    opts: &RecursiveOptions,
+) -> RecursiveReport {
+    debug!("applying {:?} recursively on [{}]", operation, path);
+    let mut report = RecursiveReport::default();
+    let mut batch = Vec::with_capacity(BATCH_SIZE);
+
+    for entry in fs.walk(path).continue_on_error(true) {
+        let (path, status) = match entry {
+            Ok(entry) => entry,
+            Err(e) => {
+                let failed = error_path(&e).unwrap_or(path).to_string();
+                report.failures.push((failed, e));
+                continue;
+            }
+        };
+
+        if !operation.applies_to(&status) {
+            continue;
+        }
+        if let Some(filter) = opts.filter {
+            if !filter(&path, &status) {
+                report.skipped += 1;
+                continue;
+            }
+        }
+
+        batch.push((path, status));
+        if batch.len() == BATCH_SIZE {
+            let full = std::mem::replace(&mut batch, Vec::with_capacity(BATCH_SIZE));
+            process_batch(fs, &operation, opts, full, &mut report);
+        }
+    }
+    process_batch(fs, &operation, opts, batch, &mut report);
+
+    report
+}
@@ -0,0 +1,319 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Locality aware input splits
+//!
+//! Splits files into byte ranges the same way Hadoop's ```FileInputFormat```
+//! does, so that workers can read data where it is stored.
+
+use crate::dfs::{BlockLocation, FileStatus, HdfsFs};
+use crate::glob::GlobPattern;
+use crate::util::base_name;
+use std::collections::HashMap;
+use std::io::{Error, ErrorKind};
+
+/// Last split of a file may be up to 10% larger than split size
+const SPLIT_SLOP: f64 = 1.1;
+
+/// Byte range of a file to be processed by a single worker
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct InputSplit {
+    pub path: String,
+    pub start: u64,
+    pub length: u64,
+    /// Hosts storing the range, ordered by number of bytes stored locally
+    pub hosts: Vec<String>,
+}
+
+impl InputSplit {
+    /// Is ```host``` one of preferred hosts
+    pub fn is_local_to(&self, host: &str) -> bool {
+        self.hosts.iter().any(|h| h.eq_ignore_ascii_case(host))
+    }
+}
+
+/// Splits assigned to a worker, see [InputSplits::assign]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SplitAssignment {
+    pub worker: String,
+    pub splits: Vec<InputSplit>,
+}
+
+impl SplitAssignment {
+    /// Total number of bytes assigned
+    pub fn bytes(&self) -> u64 {
+        self.splits.iter().map(|s| s.length).sum()
+    }
+
+    /// Number of assigned bytes stored on the worker
+    pub fn local_bytes(&self) -> u64 {
+        self.splits
+            .iter()
+            .filter(|s| s.is_local_to(&self.worker))
+            .map(|s| s.length)
+            .sum()
+    }
+}
+
+/// Planner of input splits.
+///
+/// Split size is computed as ```max(min_size, min(max_size, split_size))```,
+/// where split size defaults to block size of each file, or to default block
+/// size of the filesystem if the file does not report one.
+///
+/// ```ignore
+/// let splits = InputSplits::new(&fs)
+///     .min_size(64 * 1024 * 1024)
+///     .plan(&["/data/2023-*/part-*"])?;
+/// let assignments = InputSplits::assign(splits, &["worker1", "worker2"]);
+/// ```
+pub struct InputSplits<'a> {
+    fs: &'a HdfsFs,
+    split_size: Option<u64>,
+    min_size: u64,
+    max_size: u64,
+    recursive: bool,
+}
+
+impl<'a> InputSplits<'a> {
+    pub fn new(fs: &'a HdfsFs) -> InputSplits<'a> {
+        InputSplits {
+            fs,
+            split_size: None,
+            min_size: 1,
+            max_size: u64::MAX,
+            recursive: false,
+        }
+    }
+
+    /// Target split size, block size of a file is used if not set, must not be 0
+    pub fn split_size(mut self, size: u64) -> Self {
+        self.split_size = Some(size);
+        self
+    }
+
+    /// Lower bound of split size
+    pub fn min_size(mut self, size: u64) -> Self {
+        self.min_size = size.max(1);
+        self
+    }
+
+    /// Upper bound of split size
+    pub fn max_size(mut self, size: u64) -> Self {
+        self.max_size = size;
+        self
+    }
+
+    /// Should files of nested directories be included
+    pub fn recursive(mut self, recursive: bool) -> Self {
+        self.recursive = recursive;
+        self
+    }
+
+    /// Compute splits of all files matched by ```paths```.
+    ///
+    /// Each path can be a file, a directory or a glob pattern. Hidden
+    /// files, starting with ```_``` or ```.```, are skipped in directories.
+    /// Returns error if a path does not exist or a pattern matches nothing.
+    pub fn plan(&self, paths: &[&str]) -> Result<Vec<InputSplit>, Error> {
+        let mut splits = Vec::new();
+        for file in self.list_files(paths)? {
+            self.split_file(&file, &mut splits)?;
+        }
+        Ok(splits)
+    }
+
+    fn list_files(&self, paths: &[&str]) -> Result<Vec<FileStatus>, Error> {
+        let mut files = Vec::new();
+        for path in paths {
+            let matched = if GlobPattern::new(path)?.has_wildcard() {
+                self.fs.glob(path)?
+            } else {
+                vec![self.fs.get_file_status(path)?]
+            };
+
+            if matched.is_empty() {
+                return Err(Error::new(
+                    ErrorKind::NotFound,
+                    format!("input pattern [{}] matches 0 files", path),
+                ));
+            }
+
+            for status in matched {
+                if status.is_file() {
+                    files.push(status);
+                } else {
+                    self.list_directory(status.name(), &mut files)?;
+                }
+            }
+        }
+        Ok(files)
+    }
+
+    fn list_directory(&self, path: &str, files: &mut Vec<FileStatus>) -> Result<(), Error> {
+        let max_depth = if self.recursive { usize::MAX } else { 1 };
+        let walk = self
+            .fs
+            .walk(path)
+            .min_depth(1)
+            .max_depth(max_depth)
+            .filter_dir(|path, _| !is_hidden(path));
+
+        for entry in walk {
+            let (path, status) = entry?;
+            if status.is_file() && !is_hidden(&path) {
+                files.push(status);
+            }
+        }
+        Ok(())
+    }
+
+    fn split_file(&self, file: &FileStatus, splits: &mut Vec<InputSplit>) -> Result<(), Error> {
+        let path = file.name();
+        let len = file.len() as u64;
+
+        if len == 0 {
+            splits.push(InputSplit {
+                path: path.to_string(),
+                start: 0,
+                length: 0,
+                hosts: Vec::new(),
+            });
+            return Ok(());
+        }
+
+        let blocks = self.fs.get_block_locations(path, 0, len)?;
+        let split_size = self.compute_split_size(file.block_size() as u64)?;
+
+        let mut start = 0;
+        let mut remaining = len;
+        while remaining as f64 / split_size as f64 > SPLIT_SLOP {
+            splits.push(InputSplit {
+                path: path.to_string(),
+                start,
+                length: split_size,
+                hosts: split_hosts(&blocks, start, split_size),
+            });
+            start += split_size;
+            remaining -= split_size;
+        }
+
+        if remaining != 0 {
+            splits.push(InputSplit {
+                path: path.to_string(),
+                start,
+                length: remaining,
+                hosts: split_hosts(&blocks, start, remaining),
+            });
+        }
+
+        Ok(())
+    }
+
+    fn compute_split_size(&self, block_size: u64) -> Result<u64, Error> {
+        let target = match (self.split_size, block_size) {
+            (Some(split_size), _) => split_size,
+            // block size is not reported for some files, use the default one
+            (None, 0) => self.fs.default_block_size()? as u64,
+            (None, block_size) => block_size,
+        };
+        if target == 0 {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "split size and block size are 0",
+            ));
+        }
+        Ok(self.min_size.max(self.max_size.min(target)))
+    }
+
+    /// Assign ```splits``` to ```workers``` maximising local reads
+    /// while keeping number of bytes per worker balanced.
+    ///
+    /// Largest splits are assigned first, to the least loaded worker storing
+    /// the data if it has not exceeded its fair share, otherwise to the least
+    /// loaded worker. Result has an entry for each worker, in given order.
+    pub fn assign(splits: Vec<InputSplit>, workers: &[&str]) -> Vec<SplitAssignment> {
+        let mut assignments: Vec<SplitAssignment> = workers
+            .iter()
+            .map(|w| SplitAssignment {
+                worker: w.to_string(),
+                splits: Vec::new(),
+            })
+            .collect();
+
+        if assignments.is_empty() {
+            return assignments;
+        }
+
+        let total: u64 = splits.iter().map(|s| s.length).sum();
+        let fair_share = total.div_ceil(assignments.len() as u64);
+
+        let mut splits = splits;
+        splits.sort_by_key(|s| std::cmp::Reverse(s.length));
+
+        let mut load = vec![0u64; assignments.len()];
+        for split in splits {
+            let local = (0..assignments.len())
+                .filter(|&idx| split.is_local_to(&assignments[idx].worker))
+                .filter(|&idx| load[idx] < fair_share)
+                .min_by_key(|&idx| load[idx]);
+
+            let idx = match local {
+                Some(idx) => idx,
+                None => (0..assignments.len()).min_by_key(|&idx| load[idx]).unwrap(),
+            };
+
+            load[idx] += split.length;
+            assignments[idx].splits.push(split);
+        }
+
+        assignments
+    }
+}
+
+fn is_hidden(path: &str) -> bool {
+    let name = base_name(path);
+    name.starts_with('_') || name.starts_with('.')
+}
+
+/// Hosts of blocks overlapping the range, ordered by number of bytes
+/// of the range they store.
+fn split_hosts(blocks: &[BlockLocation], start: u64, length: u64) -> Vec<String> {
+    let end = start + length;
+    let mut bytes: HashMap<&str, u64> = HashMap::new();
+    let mut order: Vec<&str> = Vec::new();
+
+    for block in blocks {
+        let block_end = block.offset + block.length;
+        if block_end <= start || block.offset >= end {
+            continue;
+        }
+        let overlap = block_end.min(end) - block.offset.max(start);
+        for host in &block.hosts {
+            let counter = bytes.entry(host.as_str()).or_insert_with(|| {
+                order.push(host.as_str());
+                0
+            });
+            *counter += overlap;
+        }
+    }
+
+    // stable sort keeps replica order of the first block for ties
+    order.sort_by(|a, b| bytes[b].cmp(&bytes[a]));
+    order.into_iter().map(String::from).collect()
+}
@@ -0,0 +1,126 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Capacity of a filesystem and usage alerting
+
+use crate::dfs::HdfsFs;
+use crate::util::HdfsUtil;
+use std::io::Error;
+
+/// Configuration key of default replication
+pub const REPLICATION_KEY: &str = "dfs.replication";
+
+/// Replication used if it is not configured
+pub const DEFAULT_REPLICATION: i16 = 3;
+
+/// Capacity and usage of a filesystem, see [HdfsFs::status]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FsStatus {
+    /// Raw capacity in bytes
+    pub capacity: u64,
+    /// Raw bytes used by all files
+    pub used: u64,
+    /// Raw bytes available, approximated as ```capacity - used```.
+    ///
+    /// libhdfs3 does not expose the remaining space reported by the namenode,
+    /// which also subtracts space used by non HDFS data and reserved space on
+    /// datanodes, so the real value can be lower. Keep it in mind when
+    /// setting remaining thresholds of [UsageThresholds].
+    pub remaining: u64,
+    pub default_block_size: u64,
+    pub default_replication: i16,
+}
+
+impl FsStatus {
+    pub(crate) fn new(fs: &HdfsFs) -> Result<FsStatus, Error> {
+        let capacity = fs.capacity()? as u64;
+        let used = fs.used()? as u64;
+        let default_block_size = fs.default_block_size()? as u64;
+        let default_replication = HdfsUtil::get_conf_int(REPLICATION_KEY)
+            .map_or(DEFAULT_REPLICATION, |replication| replication as i16);
+
+        Ok(FsStatus {
+            capacity,
+            used,
+            remaining: capacity.saturating_sub(used),
+            default_block_size,
+            default_replication,
+        })
+    }
+
+    /// Fraction of capacity used, between ```0.0``` and ```1.0```
+    pub fn used_ratio(&self) -> f64 {
+        if self.capacity == 0 {
+            0.0
+        } else {
+            (self.used as f64 / self.capacity as f64).min(1.0)
+        }
+    }
+
+    /// Usage level according to ```thresholds```
+    pub fn check(&self, thresholds: &UsageThresholds) -> UsageLevel {
+        let ratio = self.used_ratio();
+        let below = |min: Option<u64>| min.is_some_and(|min| self.remaining < min);
+
+        if ratio >= thresholds.critical_ratio || below(thresholds.critical_remaining) {
+            UsageLevel::Critical
+        } else if ratio >= thresholds.warning_ratio || below(thresholds.warning_remaining) {
+            UsageLevel::Warning
+        } else {
+            UsageLevel::Ok
+        }
+    }
+}
+
+/// Thresholds used by [FsStatus::check]
+#[derive(Debug, Clone)]
+pub struct UsageThresholds {
+    /// Used fraction of capacity reported as warning
+    pub warning_ratio: f64,
+    /// Used fraction of capacity reported as critical
+    pub critical_ratio: f64,
+    /// Remaining bytes below which a warning is reported
+    pub warning_remaining: Option<u64>,
+    /// Remaining bytes below which critical level is reported
+    pub critical_remaining: Option<u64>,
+}
+
+impl Default for UsageThresholds {
+    fn default() -> Self {
+        Self {
+            warning_ratio: 0.8,
+            critical_ratio: 0.9,
+            warning_remaining: None,
+            critical_remaining: None,
+        }
+    }
+}
+
+/// Outcome of [FsStatus::check]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum UsageLevel {
+    Ok,
+    Warning,
+    Critical,
+}
+
+impl UsageLevel {
+    pub fn is_ok(&self) -> bool {
+        *self == UsageLevel::Ok
+    }
+}
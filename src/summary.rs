@@ -0,0 +1,113 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Content summary of directory trees (```hadoop fs -count``` / ```-du```)
+//!
+//! libhdfs3 does not expose ```getContentSummary```, so summaries are
+//! computed by a parallel traversal of the tree.
+
+use crate::dfs::{FileStatus, HdfsFs};
+use crate::util::join_path;
+use std::io::Error;
+
+/// Number of directories listed concurrently
+const PARALLELISM: usize = 8;
+
+/// Totals of a file or directory tree
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ContentSummary {
+    /// Sum of file lengths
+    pub length: u64,
+    pub file_count: u64,
+    /// Number of directories, including the summarised one
+    pub directory_count: u64,
+    /// Sum of file lengths multiplied by their replication
+    pub space_consumed: u64,
+    /// Name quota, ```None``` if not set or not available
+    pub quota: Option<u64>,
+    /// Space quota, ```None``` if not set or not available
+    pub space_quota: Option<u64>,
+}
+
+impl ContentSummary {
+    fn add(&mut self, status: &FileStatus) {
+        if status.is_directory() {
+            self.directory_count += 1;
+        } else {
+            let len = status.len() as u64;
+            self.file_count += 1;
+            self.length += len;
+            self.space_consumed += len * status.replica_count().max(0) as u64;
+        }
+    }
+}
+
+/// Summary of ```path``` and, if ```per_child``` is set, of each of
+/// its children keyed by child path.
+fn summarise(
+    fs: &HdfsFs,
+    path: &str,
+    per_child: bool,
+) -> Result<(ContentSummary, Vec<(String, ContentSummary)>), Error> {
+    let prefix = path.trim_end_matches('/');
+    let mut total = ContentSummary::default();
+    let mut children: Vec<(String, ContentSummary)> = Vec::new();
+
+    for entry in fs.walk(path).parallelism(PARALLELISM) {
+        let (entry_path, status) = entry?;
+        total.add(&status);
+
+        if !per_child || entry_path.len() <= prefix.len() {
+            continue;
+        }
+
+        // walk is pre order, children are yielded before their content
+        let relative = entry_path[prefix.len()..].trim_start_matches('/');
+        let child = relative.split('/').next().unwrap_or(relative);
+        let child_path = join_path(path, child);
+        match children.last_mut() {
+            Some((last, summary)) if *last == child_path => summary.add(&status),
+            _ => {
+                let mut summary = ContentSummary::default();
+                summary.add(&status);
+                children.push((child_path, summary));
+            }
+        }
+    }
+
+    Ok((total, children))
+}
+
+/// See [HdfsFs::content_summary]
+pub(crate) fn content_summary(fs: &HdfsFs, path: &str) -> Result<ContentSummary, Error> {
+    summarise(fs, path, false).map(|(total, _)| total)
+}
+
+/// See [HdfsFs::du]
+pub(crate) fn du(fs: &HdfsFs, path: &str) -> Result<Vec<(String, ContentSummary)>, Error> {
+    let status = fs.get_file_status(path)?;
+    if status.is_file() {
+        let mut summary = ContentSummary::default();
+        summary.add(&status);
+        return Ok(vec![(path.to_string(), summary)]);
+    }
+
+    let (_, mut children) = summarise(fs, path, true)?;
+    children.sort_by(|a, b| a.0.cmp(&b.0));
+    Ok(children)
+}
@@ -0,0 +1,445 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Directory synchronisation between HDFS filesystems and local disk
+
+use crate::dfs::{HdfsFile, HdfsFs};
+use crate::metadata::to_system_time;
+use crate::util::join_path;
+use libc::time_t;
+use log::{debug, info};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs::FileTimes;
+use std::io::{Error, ErrorKind, Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+/// Source or destination of [crate::HdfsUtil::sync]
+#[derive(Debug, Clone, Copy)]
+pub enum SyncEndpoint<'a> {
+    /// Directory on a HDFS filesystem
+    Hdfs(&'a HdfsFs, &'a str),
+    /// Directory on local disk
+    Local(&'a Path),
+}
+
+/// Options used by [crate::HdfsUtil::sync]
+#[derive(Debug, Clone)]
+pub struct SyncOptions {
+    /// Compare content of files with the same size instead of modification
+    /// time. Expensive, as both files have to be read.
+    pub compare_content: bool,
+    /// Delete destination files and directories which do not exist in source
+    pub delete: bool,
+    /// Only report what would be done
+    pub dry_run: bool,
+    /// Size of the buffer used to stream data
+    pub buf_size: usize,
+    /// Set modification time of copied files to the source one, so they
+    /// are not seen as newer than source
+    pub preserve_times: bool,
+}
+
+impl Default for SyncOptions {
+    fn default() -> Self {
+        Self {
+            compare_content: false,
+            delete: false,
+            dry_run: false,
+            buf_size: 1024 * 1024,
+            preserve_times: false,
+        }
+    }
+}
+
+/// Outcome of a synchronisation, paths are relative to the synchronised directories
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct SyncReport {
+    /// Files copied as they were new or changed
+    pub copied: Vec<String>,
+    /// Directories created on destination
+    pub created: Vec<String>,
+    /// Destination paths deleted as they do not exist in source
+    pub deleted: Vec<String>,
+    /// Number of files which were already in sync
+    pub unchanged: u64,
+    /// Number of bytes copied
+    pub bytes: u64,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Meta {
+    is_dir: bool,
+    len: u64,
+    modified: time_t,
+}
+
+impl<'a> SyncEndpoint<'a> {
+    fn describe(&self, relative: &str) -> String {
+        match self {
+            SyncEndpoint::Hdfs(fs, root) => {
+                format!("{}{}", fs.url(), self.hdfs_path(root, relative))
+            }
+            SyncEndpoint::Local(root) => self.local_path(root, relative).display().to_string(),
+        }
+    }
+
+    fn hdfs_path(&self, root: &str, relative: &str) -> String {
+        if relative.is_empty() {
+            root.to_string()
+        } else {
+            join_path(root, relative)
+        }
+    }
+
+    fn local_path(&self, root: &Path, relative: &str) -> PathBuf {
+        if relative.is_empty() {
+            root.to_path_buf()
+        } else {
+            root.join(relative)
+        }
+    }
+
+    fn root_meta(&self) -> Result<Option<Meta>, Error> {
+        let result = match self {
+            SyncEndpoint::Hdfs(fs, root) => fs.get_file_status(root).map(|status| Meta {
+                is_dir: status.is_directory(),
+                len: status.len() as u64,
+                modified: status.last_modified(),
+            }),
+            SyncEndpoint::Local(root) => std::fs::metadata(root).map(|m| local_meta(&m)),
+        };
+
+        match result {
+            Ok(meta) => Ok(Some(meta)),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// All entries under root keyed by their relative path
+    fn entries(&self) -> Result<BTreeMap<String, Meta>, Error> {
+        let mut entries = BTreeMap::new();
+        match self {
+            SyncEndpoint::Hdfs(fs, root) => {
+                let prefix = root.trim_end_matches('/');
+                for entry in fs.walk(root).min_depth(1) {
+                    let (path, status) = entry?;
+                    let relative = path[prefix.len()..].trim_start_matches('/').to_string();
+                    entries.insert(
+                        relative,
+                        Meta {
+                            is_dir: status.is_directory(),
+                            len: status.len() as u64,
+                            modified: status.last_modified(),
+                        },
+                    );
+                }
+            }
+            SyncEndpoint::Local(root) => local_entries(root, "", &mut entries)?,
+        }
+        Ok(entries)
+    }
+
+    fn open(&self, relative: &str) -> Result<Box<dyn Read + 'a>, Error> {
+        match *self {
+            SyncEndpoint::Hdfs(fs, root) => Ok(Box::new(fs.open(&self.hdfs_path(root, relative))?)),
+            SyncEndpoint::Local(root) => Ok(Box::new(std::fs::File::open(
+                self.local_path(root, relative),
+            )?)),
+        }
+    }
+
+    fn create(&self, relative: &str) -> Result<SyncWriter<'a>, Error> {
+        match *self {
+            SyncEndpoint::Hdfs(fs, root) => Ok(SyncWriter::Hdfs(
+                fs.create_with_overwrite(&self.hdfs_path(root, relative), true)?,
+            )),
+            SyncEndpoint::Local(root) => Ok(SyncWriter::Local(std::fs::File::create(
+                self.local_path(root, relative),
+            )?)),
+        }
+    }
+
+    fn set_modified(&self, relative: &str, modified: time_t) -> Result<(), Error> {
+        let modified = to_system_time(modified);
+        match self {
+            SyncEndpoint::Hdfs(fs, root) => fs
+                .set_times(&self.hdfs_path(root, relative), Some(modified), None)
+                .map(|_| ()),
+            SyncEndpoint::Local(root) => {
+                let times = FileTimes::new().set_modified(modified);
+                std::fs::File::open(self.local_path(root, relative))?.set_times(times)
+            }
+        }
+    }
+
+    fn mkdir(&self, relative: &str) -> Result<(), Error> {
+        match self {
+            SyncEndpoint::Hdfs(fs, root) => fs.mkdir(&self.hdfs_path(root, relative)).map(|_| ()),
+            SyncEndpoint::Local(root) => std::fs::create_dir_all(self.local_path(root, relative)),
+        }
+    }
+
+    fn delete(&self, relative: &str, is_dir: bool) -> Result<(), Error> {
+        match self {
+            SyncEndpoint::Hdfs(fs, root) => fs
+                .delete(&self.hdfs_path(root, relative), is_dir)
+                .map(|_| ()),
+            SyncEndpoint::Local(root) if is_dir => {
+                std::fs::remove_dir_all(self.local_path(root, relative))
+            }
+            SyncEndpoint::Local(root) => std::fs::remove_file(self.local_path(root, relative)),
+        }
+    }
+}
+
+/// File created by [SyncEndpoint::create], has to be closed by [SyncWriter::close]
+enum SyncWriter<'a> {
+    Hdfs(HdfsFile<'a>),
+    Local(std::fs::File),
+}
+
+impl SyncWriter<'_> {
+    /// Closes HDFS file or syncs local file, reporting the error of either
+    fn close(self) -> Result<(), Error> {
+        match self {
+            SyncWriter::Hdfs(file) => file.close(),
+            SyncWriter::Local(file) => file.sync_all(),
+        }
+    }
+}
+
+impl Write for SyncWriter<'_> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        match self {
+            SyncWriter::Hdfs(file) => file.write(buf),
+            SyncWriter::Local(file) => file.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        match self {
+            SyncWriter::Hdfs(file) => Write::flush(file),
+            SyncWriter::Local(file) => file.flush(),
+        }
+    }
+}
+
+fn local_meta(metadata: &std::fs::Metadata) -> Meta {
+    let modified = metadata
+        .modified()
+        .ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map_or(0, |d| d.as_secs() as time_t);
+    Meta {
+        is_dir: metadata.is_dir(),
+        len: metadata.len(),
+        modified,
+    }
+}
+
+fn local_entries(
+    root: &Path,
+    relative: &str,
+    entries: &mut BTreeMap<String, Meta>,
+) -> Result<(), Error> {
+    for entry in std::fs::read_dir(root.join(relative))? {
+        let entry = entry?;
+        let name = entry.file_name().to_string_lossy().to_string();
+        let child = if relative.is_empty() {
+            name
+        } else {
+            format!("{}/{}", relative, name)
+        };
+        let meta = local_meta(&std::fs::metadata(entry.path())?);
+        entries.insert(child.clone(), meta);
+        if meta.is_dir {
+            local_entries(root, &child, entries)?;
+        }
+    }
+    Ok(())
+}
+
+fn read_fully(reader: &mut dyn Read, buf: &mut [u8]) -> Result<usize, Error> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match reader.read(&mut buf[filled..])? {
+            0 => break,
+            read => filled += read,
+        }
+    }
+    Ok(filled)
+}
+
+fn same_content(
+    src: &SyncEndpoint,
+    dst: &SyncEndpoint,
+    relative: &str,
+    buf_size: usize,
+) -> Result<bool, Error> {
+    let mut src_reader = src.open(relative)?;
+    let mut dst_reader = dst.open(relative)?;
+    let mut src_buf = vec![0; buf_size];
+    let mut dst_buf = vec![0; buf_size];
+
+    loop {
+        let src_read = read_fully(&mut src_reader, &mut src_buf)?;
+        let dst_read = read_fully(&mut dst_reader, &mut dst_buf)?;
+        if src_buf[..src_read] != dst_buf[..dst_read] {
+            return Ok(false);
+        }
+        if src_read == 0 {
+            return Ok(true);
+        }
+    }
+}
+
+fn copy_file(
+    src: &SyncEndpoint,
+    dst: &SyncEndpoint,
+    relative: &str,
+    buf_size: usize,
+) -> Result<u64, Error> {
+    let mut reader = src.open(relative)?;
+    let mut writer = dst.create(relative)?;
+    let mut buf = vec![0; buf_size];
+    let mut copied = 0;
+    loop {
+        let read = reader.read(&mut buf)?;
+        if read == 0 {
+            break;
+        }
+        writer.write_all(&buf[..read])?;
+        copied += read as u64;
+    }
+    writer.close()?;
+    Ok(copied)
+}
+
+/// Synchronises ```dst``` directory with ```src```, see [crate::HdfsUtil::sync]
+pub(crate) fn sync(
+    src: SyncEndpoint,
+    dst: SyncEndpoint,
+    opts: &SyncOptions,
+) -> Result<SyncReport, Error> {
+    let buf_size = opts.buf_size.max(1);
+    match src.root_meta()? {
+        Some(meta) if meta.is_dir => (),
+        Some(_) => {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("source [{}] is not a directory", src.describe("")),
+            ))
+        }
+        None => {
+            return Err(Error::new(
+                ErrorKind::NotFound,
+                format!("source [{}] does not exist", src.describe("")),
+            ))
+        }
+    }
+
+    let mut report = SyncReport::default();
+    let src_entries = src.entries()?;
+    let dst_entries = match dst.root_meta()? {
+        Some(meta) if meta.is_dir => dst.entries()?,
+        Some(_) => {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("destination [{}] is not a directory", dst.describe("")),
+            ))
+        }
+        None => {
+            if !opts.dry_run {
+                dst.mkdir("")?;
+            }
+            BTreeMap::new()
+        }
+    };
+
+    // deletes go first, so a file can replace a directory with the same name
+    let mut deleted_dirs: BTreeSet<&str> = BTreeSet::new();
+    for (relative, dst_meta) in &dst_entries {
+        // descendants of deleted directories are gone already, they do not
+        // have to directly follow their ancestor (```a```, ```a.1```, ```a/b```)
+        let ancestor_deleted = relative
+            .match_indices('/')
+            .any(|(idx, _)| deleted_dirs.contains(&relative[..idx]));
+        if ancestor_deleted {
+            continue;
+        }
+
+        let replaced = match src_entries.get(relative) {
+            Some(src_meta) => src_meta.is_dir != dst_meta.is_dir,
+            None => opts.delete,
+        };
+        if replaced {
+            info!("sync deleting [{}]", dst.describe(relative));
+            if !opts.dry_run {
+                dst.delete(relative, dst_meta.is_dir)?;
+            }
+            report.deleted.push(relative.clone());
+            if dst_meta.is_dir {
+                deleted_dirs.insert(relative);
+            }
+        }
+    }
+
+    for (relative, src_meta) in &src_entries {
+        let dst_meta = dst_entries
+            .get(relative)
+            .filter(|dst_meta| dst_meta.is_dir == src_meta.is_dir);
+
+        if src_meta.is_dir {
+            if dst_meta.is_none() {
+                debug!("sync creating directory [{}]", dst.describe(relative));
+                if !opts.dry_run {
+                    dst.mkdir(relative)?;
+                }
+                report.created.push(relative.clone());
+            }
+            continue;
+        }
+
+        let changed = match dst_meta {
+            None => true,
+            Some(dst_meta) if dst_meta.len != src_meta.len => true,
+            Some(_) if opts.compare_content => !same_content(&src, &dst, relative, buf_size)?,
+            Some(dst_meta) => src_meta.modified > dst_meta.modified,
+        };
+
+        if changed {
+            info!(
+                "sync copying [{}] to [{}]",
+                src.describe(relative),
+                dst.describe(relative)
+            );
+            if !opts.dry_run {
+                report.bytes += copy_file(&src, &dst, relative, buf_size)?;
+                if opts.preserve_times {
+                    dst.set_modified(relative, src_meta.modified)?;
+                }
+            }
+            report.copied.push(relative.clone());
+        } else {
+            report.unchanged += 1;
+        }
+    }
+
+    Ok(report)
+}
@@ -0,0 +1,335 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! HDFS trash, compatible with Hadoop's ```TrashPolicyDefault```
+//!
+//! Deleted paths are moved to ```/user/<user>/.Trash/Current/<original path>```.
+//! [Trash::checkpoint] renames ```Current``` to a timestamped directory and
+//! [Trash::expunge] removes checkpoints older than ```fs.trash.interval```.
+
+use crate::dfs::HdfsFs;
+use crate::util::{base_name, is_ancestor, join_path, strip_authority, HdfsUtil};
+use log::{debug, info, warn};
+use std::io::{Error, ErrorKind};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Configuration key of trash interval in minutes, ```0``` disables trash
+pub const TRASH_INTERVAL_KEY: &str = "fs.trash.interval";
+
+const TRASH: &str = ".Trash";
+const CURRENT: &str = "Current";
+
+/// Trash of a single user
+pub struct Trash<'a> {
+    fs: &'a HdfsFs,
+    root: String,
+    interval: Duration,
+}
+
+impl<'a> Trash<'a> {
+    /// Trash of the connected user, located in its home directory.
+    /// Interval is read from ```fs.trash.interval``` of the client configuration.
+    pub fn new(fs: &'a HdfsFs) -> Result<Trash<'a>, Error> {
+        let home = strip_authority(&fs.working_directory()?);
+        Ok(Self::with_home(fs, &home))
+    }
+
+    /// Trash of ```user```, located in ```/user/<user>/.Trash```
+    pub fn for_user(fs: &'a HdfsFs, user: &str) -> Trash<'a> {
+        Self::with_home(fs, &format!("/user/{}", user))
+    }
+
+    fn with_home(fs: &'a HdfsFs, home: &str) -> Trash<'a> {
+        let minutes = HdfsUtil::get_conf_int(TRASH_INTERVAL_KEY)
+            .unwrap_or(0)
+            .max(0);
+        Trash {
+            fs,
+            root: join_path(home, TRASH),
+            interval: Duration::from_secs(minutes as u64 * 60),
+        }
+    }
+
+    /// Override trash interval, ```Duration::ZERO``` disables trash
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Is trash enabled, i.e. is interval greater than zero
+    pub fn is_enabled(&self) -> bool {
+        !self.interval.is_zero()
+    }
+
+    /// Trash root, ```/user/<user>/.Trash```
+    pub fn root(&self) -> &str {
+        &self.root
+    }
+
+    /// Directory where paths are moved to, ```/user/<user>/.Trash/Current```
+    pub fn current(&self) -> String {
+        join_path(&self.root, CURRENT)
+    }
+
+    /// Is ```path``` located inside of the trash
+    pub fn is_in_trash(&self, path: &str) -> bool {
+        is_ancestor(&self.root, path)
+    }
+
+    /// ```path``` resolved against the working directory of the filesystem
+    fn absolute(&self, path: &str) -> Result<String, Error> {
+        if path.starts_with('/') {
+            Ok(path.to_string())
+        } else if path.contains("://") {
+            Ok(strip_authority(path))
+        } else {
+            let cwd = strip_authority(&self.fs.working_directory()?);
+            Ok(join_path(&cwd, path.trim_start_matches("./")))
+        }
+    }
+
+    /// Delete ```path``` the way ```hadoop fs -rm``` does.
+    ///
+    /// If the trash is enabled and ```path``` is not in the trash already
+    /// it is moved to the trash and its new location is returned, otherwise
+    /// it is deleted and ```None``` is returned.
+    pub fn delete(&self, path: &str, recursive: bool) -> Result<Option<String>, Error> {
+        let path = self.absolute(path)?;
+        if !recursive && self.fs.get_file_status(&path)?.is_directory() {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("[{}] is a directory", path),
+            ));
+        }
+
+        if self.is_enabled() && !self.is_in_trash(&path) {
+            return self.move_to_trash(&path).map(Some);
+        }
+
+        self.fs.delete(&path, recursive)?;
+        Ok(None)
+    }
+
+    /// Move ```path``` to the trash, returning its location in the trash.
+    /// Relative paths are resolved against the working directory.
+    ///
+    /// If the trash already contains a path with the same name, current
+    /// time in milliseconds is appended to the name.
+    pub fn move_to_trash(&self, path: &str) -> Result<String, Error> {
+        if !self.is_enabled() {
+            return Err(Error::new(ErrorKind::Unsupported, "trash is disabled"));
+        }
+        let path = &self.absolute(path)?;
+        if path.trim_end_matches('/').is_empty() {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "cannot move root directory to trash",
+            ));
+        }
+        if self.is_in_trash(path) || is_ancestor(path, &self.root) {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("cannot move [{}] to trash as it contains the trash", path),
+            ));
+        }
+        self.fs.get_file_status(path)?;
+
+        let original = format!("{}{}", self.current(), path.trim_end_matches('/'));
+        let parent = &original[..original.len() - base_name(&original).len() - 1];
+        self.fs.mkdir(parent)?;
+
+        let mut trash_path = original.clone();
+        while self.fs.exist(&trash_path) {
+            trash_path = format!("{}{}", original, now_millis());
+        }
+
+        self.fs.rename(path, &trash_path)?;
+        info!("moved [{}] to trash at [{}]", path, trash_path);
+        Ok(trash_path)
+    }
+
+    /// Move content of ```Current``` to a new checkpoint directory named
+    /// after current UTC time as ```yyMMddHHmmss```. Returns ```None```
+    /// if there was nothing to checkpoint.
+    pub fn checkpoint(&self) -> Result<Option<String>, Error> {
+        let current = self.current();
+        if !self.fs.exist(&current) {
+            return Ok(None);
+        }
+
+        let name = format_checkpoint(SystemTime::now());
+        let mut checkpoint = join_path(&self.root, &name);
+        let mut attempt = 0;
+        while self.fs.exist(&checkpoint) {
+            attempt += 1;
+            checkpoint = join_path(&self.root, &format!("{}-{}", name, attempt));
+        }
+
+        self.fs.rename(&current, &checkpoint)?;
+        info!("created trash checkpoint [{}]", checkpoint);
+        Ok(Some(checkpoint))
+    }
+
+    /// Delete checkpoints older than trash interval, returns deleted checkpoints
+    pub fn delete_expired_checkpoints(&self) -> Result<Vec<String>, Error> {
+        let now = SystemTime::now();
+        let mut deleted = Vec::new();
+
+        for checkpoint in self.checkpoints()? {
+            let created = match parse_checkpoint(base_name(&checkpoint)) {
+                Some(created) => created,
+                None => {
+                    warn!("unexpected item in trash [{}], ignoring", checkpoint);
+                    continue;
+                }
+            };
+
+            if now.duration_since(created).unwrap_or_default() > self.interval {
+                self.fs.delete(&checkpoint, true)?;
+                debug!("deleted trash checkpoint [{}]", checkpoint);
+                deleted.push(checkpoint);
+            }
+        }
+
+        Ok(deleted)
+    }
+
+    /// Delete expired checkpoints and checkpoint current trash, as ```hadoop fs -expunge```
+    pub fn expunge(&self) -> Result<Vec<String>, Error> {
+        let deleted = self.delete_expired_checkpoints()?;
+        self.checkpoint()?;
+        Ok(deleted)
+    }
+
+    /// Restore ```path``` which was moved to trash back to its original location.
+    /// Relative paths are resolved against the working directory.
+    ///
+    /// ```Current``` is searched first, then checkpoints from the newest one.
+    /// Returns the location the path has been restored from.
+    pub fn restore(&self, path: &str) -> Result<String, Error> {
+        let path = &self.absolute(path)?;
+        if self.fs.exist(path) {
+            return Err(Error::new(
+                ErrorKind::AlreadyExists,
+                format!("[{}] already exists", path),
+            ));
+        }
+
+        let path = path.trim_end_matches('/');
+        let mut locations = vec![format!("{}{}", self.current(), path)];
+        let mut checkpoints = self.checkpoints()?;
+        checkpoints.sort();
+        locations.extend(checkpoints.iter().rev().map(|c| format!("{}{}", c, path)));
+
+        for location in locations {
+            if self.fs.exist(&location) {
+                let parent = &path[..path.len() - base_name(path).len()];
+                if !parent.is_empty() && !self.fs.exist(parent) {
+                    self.fs.mkdir(parent)?;
+                }
+                self.fs.rename(&location, path)?;
+                info!("restored [{}] from trash [{}]", path, location);
+                return Ok(location);
+            }
+        }
+
+        Err(Error::new(
+            ErrorKind::NotFound,
+            format!("[{}] not found in trash", path),
+        ))
+    }
+
+    /// All checkpoint directories, everything in trash root but ```Current```
+    fn checkpoints(&self) -> Result<Vec<String>, Error> {
+        if !self.fs.exist(&self.root) {
+            return Ok(Vec::new());
+        }
+
+        Ok(self
+            .fs
+            .list_status(&self.root)?
+            .iter()
+            .filter(|status| status.is_directory())
+            .map(|status| base_name(status.name()))
+            .filter(|name| *name != CURRENT)
+            .map(|name| join_path(&self.root, name))
+            .collect())
+    }
+}
+
+fn now_millis() -> u128 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis()
+}
+
+/// days since epoch to (year, month, day)
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let z = days + 719468;
+    let era = if z >= 0 { z } else { z - 146096 } / 146097;
+    let doe = z - era * 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+/// (year, month, day) to days since epoch
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = if year >= 0 { year } else { year - 399 } / 400;
+    let yoe = year - era * 400;
+    let mp = if month > 2 { month - 3 } else { month + 9 };
+    let doy = (153 * mp + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146097 + doe - 719468
+}
+
+fn format_checkpoint(time: SystemTime) -> String {
+    let secs = time
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs() as i64;
+    let (year, month, day) = civil_from_days(secs / 86400);
+    let secs = secs % 86400;
+    format!(
+        "{:02}{:02}{:02}{:02}{:02}{:02}",
+        year % 100,
+        month,
+        day,
+        secs / 3600,
+        secs % 3600 / 60,
+        secs % 60
+    )
+}
+
+/// parses ```yyMMddHHmmss```, ignoring collision suffix
+fn parse_checkpoint(name: &str) -> Option<SystemTime> {
+    let name = name.split('-').next()?;
+    if name.len() != 12 || !name.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let field = |idx: usize| name[idx..idx + 2].parse::<i64>().ok();
+    let days = days_from_civil(2000 + field(0)?, field(2)?, field(4)?);
+    let secs = days * 86400 + field(6)? * 3600 + field(8)? * 60 + field(10)?;
+
+    Some(UNIX_EPOCH + Duration::from_secs(secs.max(0) as u64))
+}
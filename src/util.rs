@@ -17,8 +17,16 @@
 
 use std::str;
 
-use crate::dfs::HdfsFs;
-use std::io::Error;
+use crate::dfs::{FileStatus, HdfsFile, HdfsFs};
+use crate::metadata::to_system_time;
+use crate::sync::{SyncEndpoint, SyncOptions, SyncReport};
+use log::{debug, warn};
+use std::fs::{FileTimes, OpenOptions};
+use std::io::{Error, ErrorKind, Read, Seek, SeekFrom, Write};
+use std::os::unix::fs::PermissionsExt;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::Duration;
 
 #[macro_export]
 macro_rules! to_raw {
@@ -52,28 +60,262 @@ macro_rules! b2i {
     }};
 }
 
+/// Returns last component of a path
+pub fn base_name(path: &str) -> &str {
+    let path = path.trim_end_matches('/');
+    match path.rfind('/') {
+        Some(idx) => &path[idx + 1..],
+        None => path,
+    }
+}
+
+/// path component of ```uri```, ```hdfs://host:port/a/b``` becomes ```/a/b```
+pub fn strip_authority(uri: &str) -> String {
+    match uri.find("://") {
+        Some(idx) => uri[idx + 3..]
+            .find('/')
+            .map_or("/".to_string(), |i| uri[idx + 3 + i..].to_string()),
+        None => uri.to_string(),
+    }
+}
+
+/// Joins child name to a parent path
+pub fn join_path(parent: &str, child: &str) -> String {
+    if parent.ends_with('/') {
+        format!("{}{}", parent, child)
+    } else {
+        format!("{}/{}", parent, child)
+    }
+}
+
+/// Checks if ```path``` is equal to ```ancestor``` or is located under it
+pub(crate) fn is_ancestor(ancestor: &str, path: &str) -> bool {
+    let ancestor = ancestor.trim_end_matches('/');
+    path == ancestor
+        || path
+            .strip_prefix(ancestor)
+            .is_some_and(|rest| rest.starts_with('/'))
+}
+
+/// Options used by [HdfsUtil::download_parallel]
+#[derive(Debug, Clone)]
+pub struct DownloadOptions {
+    /// Number of threads reading ranges concurrently
+    pub threads: usize,
+    /// How many times a failed range is retried before giving up
+    pub retries: usize,
+    /// Delay before the first retry of a range, doubled for each further retry
+    pub retry_delay: Duration,
+    /// Size of the buffer used to copy a single range
+    pub buf_size: usize,
+}
+
+impl Default for DownloadOptions {
+    fn default() -> Self {
+        Self {
+            threads: 4,
+            retries: 3,
+            retry_delay: Duration::from_millis(500),
+            buf_size: 1024 * 1024,
+        }
+    }
+}
+
+/// Options used by [HdfsUtil::cp_with_options] and [HdfsUtil::mv_with_options]
+#[derive(Debug, Clone)]
+pub struct CopyOptions {
+    /// Size of the buffer used to stream data between filesystems
+    pub buf_size: usize,
+    /// Overwrite destination files if they exist
+    pub overwrite: bool,
+    /// Copy directories and their content
+    pub recursive: bool,
+    /// Set permission of the source on the destination
+    pub preserve_permission: bool,
+    /// Set replication factor of the source on the destination files
+    pub preserve_replication: bool,
+    /// Set modification and access time of the source on the destination
+    pub preserve_times: bool,
+}
+
+impl Default for CopyOptions {
+    fn default() -> Self {
+        Self {
+            buf_size: 1024 * 1024,
+            overwrite: false,
+            recursive: false,
+            preserve_permission: false,
+            preserve_replication: false,
+            preserve_times: false,
+        }
+    }
+}
+
+/// What to do when a destination file already exists
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExistsPolicy {
+    /// Fail the transfer with [ErrorKind::AlreadyExists]
+    Fail,
+    /// Replace the existing file
+    Overwrite,
+    /// Keep the existing file and continue with the next one
+    Skip,
+}
+
+/// Options used by [HdfsUtil::copy_from_local] and [HdfsUtil::copy_to_local]
+#[derive(Debug, Clone)]
+pub struct TransferOptions {
+    /// Size of the buffer used to stream data
+    pub buf_size: usize,
+    /// What to do with existing destination files
+    pub exists: ExistsPolicy,
+    /// Set permission of the source on the destination
+    pub preserve_permission: bool,
+    /// Set modification and access time of the source on the destination
+    pub preserve_times: bool,
+}
+
+impl Default for TransferOptions {
+    fn default() -> Self {
+        Self {
+            buf_size: 1024 * 1024,
+            exists: ExistsPolicy::Fail,
+            preserve_permission: false,
+            preserve_times: false,
+        }
+    }
+}
+
+/// Summary of a transfer between local disk and HDFS
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TransferStats {
+    /// Number of files transferred
+    pub files: u64,
+    /// Number of bytes transferred
+    pub bytes: u64,
+    /// Number of files skipped as they already existed
+    pub skipped: u64,
+}
+
+/// Callback reporting progress of a transfer.
+///
+/// All methods have default implementations, so implementations
+/// need to override only what they are interested in.
+pub trait Progress {
+    /// Called after a chunk of ```bytes``` of file ```path``` has been transferred
+    fn bytes_transferred(&self, _path: &str, _bytes: u64) {}
+
+    /// Called after file ```path``` of length ```len``` has been transferred
+    fn file_transferred(&self, _path: &str, _len: u64) {}
+
+    /// Called before every chunk, returning ```true``` aborts the transfer
+    /// with [ErrorKind::Interrupted]
+    fn is_cancelled(&self) -> bool {
+        false
+    }
+}
+
+/// [Progress] which does not report anything
+pub struct NoProgress;
+
+impl Progress for NoProgress {}
+
+/// Byte range of a file, usually a single block
+#[derive(Debug, Clone, Copy)]
+struct Range {
+    offset: u64,
+    len: u64,
+}
+
 /// Hdfs Utility
 pub struct HdfsUtil;
 
 /// HDFS Utility
 impl HdfsUtil {
+    /// Get integer value of ```key``` from libhdfs3 client configuration,
+    /// ```None``` if libhdfs3 can not be loaded with ```dlopen``` feature
+    #[cfg(any(feature = "libhdfs3", feature = "dlopen"))]
+    pub fn get_conf_int(key: &str) -> Option<i32> {
+        #[cfg(feature = "dlopen")]
+        crate::dlopen::load_library().ok()?;
+        let mut value: i32 = 0;
+        if unsafe { crate::sys::hdfsConfGetInt(to_raw!(key), &mut value) } == 0 {
+            Some(value)
+        } else {
+            None
+        }
+    }
+
+    /// Get string value of ```key``` from libhdfs3 client configuration,
+    /// ```None``` if libhdfs3 can not be loaded with ```dlopen``` feature
+    #[cfg(any(feature = "libhdfs3", feature = "dlopen"))]
+    pub fn get_conf_str(key: &str) -> Option<String> {
+        #[cfg(feature = "dlopen")]
+        crate::dlopen::load_library().ok()?;
+        let mut value: *mut std::os::raw::c_char = std::ptr::null_mut();
+        if unsafe { crate::sys::hdfsConfGetStr(to_raw!(key), &mut value) } != 0 || value.is_null() {
+            return None;
+        }
+        let result = from_raw!(value).to_string();
+        unsafe { crate::sys::hdfsConfStrFree(value) };
+        Some(result)
+    }
+
+    /// libhdfs3 client configuration is not available without
+    /// ```libhdfs3``` or ```dlopen``` feature, so nothing is configured
+    #[cfg(not(any(feature = "libhdfs3", feature = "dlopen")))]
+    pub fn get_conf_int(_key: &str) -> Option<i32> {
+        None
+    }
+
+    /// See [HdfsUtil::get_conf_int]
+    #[cfg(not(any(feature = "libhdfs3", feature = "dlopen")))]
+    pub fn get_conf_str(_key: &str) -> Option<String> {
+        None
+    }
+
     /// Copy file from one filesystem to another.
     ///
+    /// Follows `hadoop fs -cp` semantics: if ```dst``` is an existing
+    /// directory the source is copied into it, an existing destination
+    /// file is not overwritten and directories are copied recursively.
+    ///
     /// #### Params
     /// * ```srcFS``` - The handle to source filesystem.
     /// * ```src``` - The path of source file.
     /// * ```dstFS``` - The handle to destination filesystem.
     /// * ```dst``` - The path of destination file.
-    pub fn cp(_src_fs: &HdfsFs, _src: &str, _dst_fs: &HdfsFs, _dst: &str) -> Result<bool, Error> {
-        unimplemented!("Not supported by the libhdfs3 library")
-        // let res = unsafe { crate::raw::hdfsCopy(_src_fs.raw(), to_raw!(_src), _dst_fs.raw(), to_raw!(_dst)) };
+    pub fn cp(src_fs: &HdfsFs, src: &str, dst_fs: &HdfsFs, dst: &str) -> Result<bool, Error> {
+        let opts = CopyOptions {
+            recursive: true,
+            ..Default::default()
+        };
+        Self::cp_with_options(src_fs, src, dst_fs, dst, &opts)
+    }
 
-        // if res == 0 {
-        //     Ok(true)
-        // } else {
-        //     println!("{:?}", crate::err::get_errno());
-        //     Err(HdfsErr::Unknown)
-        // }
+    /// Copy file or directory from one filesystem to another.
+    ///
+    /// Source and destination may be the same filesystem or filesystems
+    /// of different clusters, data is streamed through the client.
+    ///
+    /// #### Params
+    /// * ```srcFS``` - The handle to source filesystem.
+    /// * ```src``` - The path of source file.
+    /// * ```dstFS``` - The handle to destination filesystem.
+    /// * ```dst``` - The path of destination file.
+    /// * ```opts``` - Copy options.
+    pub fn cp_with_options(
+        src_fs: &HdfsFs,
+        src: &str,
+        dst_fs: &HdfsFs,
+        dst: &str,
+        opts: &CopyOptions,
+    ) -> Result<bool, Error> {
+        let status = src_fs.get_file_status(src)?;
+        let dst = Self::check_dest(Some(base_name(src)), dst_fs, dst, opts.overwrite)?;
+
+        Self::copy_status(src_fs, src, &status, dst_fs, &dst, opts)?;
+        Ok(true)
     }
 
     /// Move file from one filesystem to another.
@@ -83,14 +325,538 @@ impl HdfsUtil {
     /// * ```src``` - The path of source file.
     /// * ```dstFS``` - The handle to destination filesystem.
     /// * ```dst``` - The path of destination file.
-    pub fn mv(_src_fs: &HdfsFs, _src: &str, _dst_fs: &HdfsFs, _dst: &str) -> Result<bool, Error> {
-        unimplemented!("Not supported by the libhdfs3 library")
-        // let res = unsafe { hdfsMove(src_fs.raw(), to_raw!(src), dst_fs.raw(), to_raw!(dst)) };
-
-        // if res == 0 {
-        //     Ok(true)
-        // } else {
-        //     Err(HdfsErr::Unknown)
-        // }
+    pub fn mv(src_fs: &HdfsFs, src: &str, dst_fs: &HdfsFs, dst: &str) -> Result<bool, Error> {
+        let opts = CopyOptions {
+            recursive: true,
+            ..Default::default()
+        };
+        Self::mv_with_options(src_fs, src, dst_fs, dst, &opts)
+    }
+
+    /// Move file or directory from one filesystem to another.
+    ///
+    /// Moves within the same filesystem are done using rename, otherwise
+    /// the source is copied to the destination and deleted afterwards.
+    ///
+    /// #### Params
+    /// * ```srcFS``` - The handle to source filesystem.
+    /// * ```src``` - The path of source file.
+    /// * ```dstFS``` - The handle to destination filesystem.
+    /// * ```dst``` - The path of destination file.
+    /// * ```opts``` - Copy options, used when data has to be copied.
+    pub fn mv_with_options(
+        src_fs: &HdfsFs,
+        src: &str,
+        dst_fs: &HdfsFs,
+        dst: &str,
+        opts: &CopyOptions,
+    ) -> Result<bool, Error> {
+        if src_fs.url() != dst_fs.url() {
+            Self::cp_with_options(src_fs, src, dst_fs, dst, opts)?;
+            return src_fs.delete(src, true);
+        }
+
+        src_fs.get_file_status(src)?;
+        let dst = Self::check_dest(Some(base_name(src)), dst_fs, dst, opts.overwrite)?;
+        if opts.overwrite && dst_fs.exist(&dst) {
+            dst_fs.delete(&dst, false)?;
+        }
+        debug!("moving [{}] to [{}] using rename", src, dst);
+        src_fs.rename(src, &dst)
+    }
+
+    /// Resolves the final destination path of a copy, if destination
+    /// is an existing directory, source is going to be placed into it.
+    fn check_dest(
+        src_name: Option<&str>,
+        dst_fs: &HdfsFs,
+        dst: &str,
+        overwrite: bool,
+    ) -> Result<String, Error> {
+        match dst_fs.get_file_status(dst) {
+            Ok(status) if status.is_directory() => match src_name {
+                Some(src_name) => {
+                    Self::check_dest(None, dst_fs, &join_path(dst, src_name), overwrite)
+                }
+                None => Err(Error::new(
+                    ErrorKind::AlreadyExists,
+                    format!("destination [{}] is an existing directory", dst),
+                )),
+            },
+            Ok(_) if !overwrite => Err(Error::new(
+                ErrorKind::AlreadyExists,
+                format!("destination [{}] already exists", dst),
+            )),
+            Ok(_) => Ok(dst.to_string()),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(dst.to_string()),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn copy_status(
+        src_fs: &HdfsFs,
+        src: &str,
+        status: &FileStatus,
+        dst_fs: &HdfsFs,
+        dst: &str,
+        opts: &CopyOptions,
+    ) -> Result<(), Error> {
+        if status.is_directory() {
+            if !opts.recursive {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    format!("source [{}] is a directory", src),
+                ));
+            }
+            if src_fs.url() == dst_fs.url() && is_ancestor(src, dst) {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    format!("cannot copy [{}] to its subdirectory [{}]", src, dst),
+                ));
+            }
+
+            dst_fs.mkdir(dst)?;
+            for child in src_fs.list_status(src)? {
+                let name = base_name(child.name());
+                Self::copy_status(
+                    src_fs,
+                    &join_path(src, name),
+                    &child,
+                    dst_fs,
+                    &join_path(dst, name),
+                    opts,
+                )?;
+            }
+        } else {
+            debug!("copying file [{}] to [{}]", src, dst);
+            let src_file = src_fs.open(src)?;
+            let mut dst_file = dst_fs.create_with_overwrite(dst, opts.overwrite)?;
+            let copied = Self::copy_content(&src_file, &mut dst_file, opts.buf_size)
+                .and_then(|_| dst_file.close());
+            if let Err(e) = copied {
+                // partial destination is not left behind
+                let _ = dst_fs.delete(dst, false);
+                return Err(e);
+            }
+
+            if opts.preserve_replication {
+                dst_fs.set_replication(dst, status.replica_count())?;
+            }
+        }
+
+        if opts.preserve_permission {
+            dst_fs.try_chmod(dst, status.permission())?;
+        }
+
+        // directories are modified by copying their content, times go last
+        if opts.preserve_times {
+            dst_fs.set_times(
+                dst,
+                Some(to_system_time(status.last_modified())),
+                Some(to_system_time(status.last_accced())),
+            )?;
+        }
+
+        Ok(())
+    }
+
+    fn copy_content(src: &HdfsFile, dst: &mut HdfsFile, buf_size: usize) -> Result<(), Error> {
+        let mut buf = vec![0; buf_size.max(1)];
+        loop {
+            let read = src.read(&mut buf)?;
+            if read == 0 {
+                return Ok(());
+            }
+            dst.write_all(&buf[..read])?;
+        }
+    }
+
+    /// Synchronise ```dst``` directory with ```src``` directory.
+    ///
+    /// Files which are missing on destination, differ in size, or are newer
+    /// on source (or differ in content if ```opts.compare_content``` is set)
+    /// are copied. Source and destination may be any HDFS filesystem or local
+    /// directory.
+    ///
+    /// #### Params
+    /// * ```src``` - Source directory.
+    /// * ```dst``` - Destination directory, created if it does not exist.
+    /// * ```opts``` - Sync options.
+    pub fn sync(
+        src: SyncEndpoint,
+        dst: SyncEndpoint,
+        opts: &SyncOptions,
+    ) -> Result<SyncReport, Error> {
+        crate::sync::sync(src, dst, opts)
+    }
+
+    /// Download a file to local disk reading its blocks in parallel.
+    ///
+    /// The file is split on block boundaries, each range is read using its
+    /// own file handle and written to the local file at the same offset.
+    /// Failed ranges are retried up to ```opts.retries``` times, waiting
+    /// ```opts.retry_delay``` before the first retry and twice as long before
+    /// each next one. A range which ends before its expected length fails
+    /// with ```UnexpectedEof```.
+    ///
+    /// #### Params
+    /// * ```fs``` - The handle to source filesystem.
+    /// * ```path``` - The path of source file.
+    /// * ```local_path``` - The path of the local destination file.
+    /// * ```opts``` - Download options.
+    ///
+    /// Returns number of bytes downloaded.
+    pub fn download_parallel<P: AsRef<Path>>(
+        fs: &HdfsFs,
+        path: &str,
+        local_path: P,
+        opts: &DownloadOptions,
+    ) -> Result<u64, Error> {
+        let status = fs.get_file_status(path)?;
+        if !status.is_file() {
+            return Err(ErrorKind::InvalidInput.into());
+        }
+
+        let total_len = status.len() as u64;
+        let block_size = match status.block_size() {
+            0 => fs.default_block_size()?,
+            block_size => block_size,
+        } as u64;
+
+        let local_path = local_path.as_ref();
+        let local = OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(local_path)?;
+        local.set_len(total_len)?;
+        drop(local);
+
+        let mut ranges = Vec::new();
+        let mut offset = 0;
+        while offset < total_len {
+            let len = std::cmp::min(block_size, total_len - offset);
+            ranges.push(Range { offset, len });
+            offset += len;
+        }
+        debug!(
+            "download of [{}] split into {} ranges of up to {} bytes",
+            path,
+            ranges.len(),
+            block_size
+        );
+
+        let queue = Mutex::new(ranges);
+        let threads = opts.threads.max(1);
+        let results: Vec<Result<u64, Error>> = std::thread::scope(|scope| {
+            let handles: Vec<_> = (0..threads)
+                .map(|_| {
+                    scope.spawn(|| {
+                        let mut downloaded = 0;
+                        loop {
+                            let range = match queue.lock().unwrap().pop() {
+                                Some(range) => range,
+                                None => return Ok(downloaded),
+                            };
+                            downloaded +=
+                                Self::download_range_with_retry(fs, path, local_path, range, opts)?;
+                        }
+                    })
+                })
+                .collect();
+
+            handles
+                .into_iter()
+                .map(|handle| handle.join().unwrap())
+                .collect()
+        });
+
+        let mut downloaded = 0;
+        for result in results {
+            downloaded += result?;
+        }
+
+        Ok(downloaded)
+    }
+
+    fn download_range_with_retry(
+        fs: &HdfsFs,
+        path: &str,
+        local_path: &Path,
+        range: Range,
+        opts: &DownloadOptions,
+    ) -> Result<u64, Error> {
+        let mut attempt = 0;
+        let mut delay = opts.retry_delay;
+        loop {
+            match Self::download_range(fs, path, local_path, range, opts.buf_size) {
+                Ok(len) => return Ok(len),
+                Err(e) if attempt < opts.retries => {
+                    attempt += 1;
+                    warn!(
+                        "download of [{}] range {:?} failed ({}), retry {}/{} in {:?}",
+                        path, range, e, attempt, opts.retries, delay
+                    );
+                    std::thread::sleep(delay);
+                    delay = delay.saturating_mul(2);
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    fn download_range(
+        fs: &HdfsFs,
+        path: &str,
+        local_path: &Path,
+        range: Range,
+        buf_size: usize,
+    ) -> Result<u64, Error> {
+        let file = fs.open(path)?;
+        let mut local = OpenOptions::new().write(true).open(local_path)?;
+        local.seek(SeekFrom::Start(range.offset))?;
+
+        let mut buf = vec![0; buf_size.max(1)];
+        let mut copied = 0;
+        while copied < range.len {
+            let length = std::cmp::min(buf.len() as u64, range.len - copied) as usize;
+            let read =
+                file.read_with_pos_length((range.offset + copied) as i64, &mut buf, length)?;
+            if read == 0 {
+                return Err(ErrorKind::UnexpectedEof.into());
+            }
+            local.write_all(&buf[..read])?;
+            copied += read as u64;
+        }
+        local.flush()?;
+
+        Ok(copied)
+    }
+
+    /// Copy a local file or directory tree to HDFS.
+    ///
+    /// If ```dst``` is an existing directory the source is copied into it.
+    ///
+    /// #### Params
+    /// * ```local``` - The path of local source file or directory.
+    /// * ```fs``` - The handle to destination filesystem.
+    /// * ```dst``` - The path of destination file.
+    /// * ```opts``` - Transfer options.
+    /// * ```progress``` - Progress callback.
+    pub fn copy_from_local<P: AsRef<Path>>(
+        local: P,
+        fs: &HdfsFs,
+        dst: &str,
+        opts: &TransferOptions,
+        progress: &dyn Progress,
+    ) -> Result<TransferStats, Error> {
+        let local = local.as_ref();
+        let mut dst = dst.to_string();
+        if let Ok(status) = fs.get_file_status(&dst) {
+            if status.is_directory() {
+                let name = local
+                    .file_name()
+                    .ok_or_else(|| Error::from(ErrorKind::InvalidInput))?;
+                dst = join_path(&dst, &name.to_string_lossy());
+            }
+        }
+
+        let mut stats = TransferStats::default();
+        Self::upload_path(local, fs, &dst, opts, progress, &mut stats)?;
+        Ok(stats)
+    }
+
+    /// Copy a HDFS file or directory tree to local disk.
+    ///
+    /// If ```local``` is an existing directory the source is copied into it.
+    ///
+    /// #### Params
+    /// * ```fs``` - The handle to source filesystem.
+    /// * ```src``` - The path of source file.
+    /// * ```local``` - The path of local destination file or directory.
+    /// * ```opts``` - Transfer options.
+    /// * ```progress``` - Progress callback.
+    pub fn copy_to_local<P: AsRef<Path>>(
+        fs: &HdfsFs,
+        src: &str,
+        local: P,
+        opts: &TransferOptions,
+        progress: &dyn Progress,
+    ) -> Result<TransferStats, Error> {
+        let status = fs.get_file_status(src)?;
+        let mut local = local.as_ref().to_path_buf();
+        if local.is_dir() {
+            local.push(base_name(src));
+        }
+
+        let mut stats = TransferStats::default();
+        Self::download_status(fs, src, &status, &local, opts, progress, &mut stats)?;
+        Ok(stats)
+    }
+
+    fn upload_path(
+        local: &Path,
+        fs: &HdfsFs,
+        dst: &str,
+        opts: &TransferOptions,
+        progress: &dyn Progress,
+        stats: &mut TransferStats,
+    ) -> Result<(), Error> {
+        let metadata = std::fs::metadata(local)?;
+
+        if metadata.is_dir() {
+            if !fs.exist(dst) {
+                fs.mkdir(dst)?;
+            }
+            for entry in std::fs::read_dir(local)? {
+                let entry = entry?;
+                let dst = join_path(dst, &entry.file_name().to_string_lossy());
+                Self::upload_path(&entry.path(), fs, &dst, opts, progress, stats)?;
+            }
+        } else {
+            if fs.exist(dst) {
+                match opts.exists {
+                    ExistsPolicy::Fail => {
+                        return Err(Error::new(
+                            ErrorKind::AlreadyExists,
+                            format!("destination [{}] already exists", dst),
+                        ))
+                    }
+                    ExistsPolicy::Skip => {
+                        debug!("skipping upload of [{}], destination exists", dst);
+                        stats.skipped += 1;
+                        return Ok(());
+                    }
+                    ExistsPolicy::Overwrite => (),
+                }
+            }
+
+            debug!("uploading [{}] to [{}]", local.display(), dst);
+            let mut reader = std::fs::File::open(local)?;
+            let mut writer = fs.create_with_overwrite(dst, true)?;
+            let copied = Self::copy_stream(&mut reader, &mut writer, dst, opts, progress)
+                .and_then(|copied| writer.close().map(|_| copied));
+            let copied = match copied {
+                Ok(copied) => copied,
+                Err(e) => {
+                    let _ = fs.delete(dst, false);
+                    return Err(e);
+                }
+            };
+
+            stats.files += 1;
+            stats.bytes += copied;
+            progress.file_transferred(dst, copied);
+        }
+
+        if opts.preserve_permission {
+            let mode = (metadata.permissions().mode() & 0o7777) as i16;
+            fs.try_chmod(dst, mode)?;
+        }
+
+        if opts.preserve_times {
+            fs.set_times(dst, metadata.modified().ok(), metadata.accessed().ok())?;
+        }
+
+        Ok(())
+    }
+
+    fn download_status(
+        fs: &HdfsFs,
+        src: &str,
+        status: &FileStatus,
+        local: &Path,
+        opts: &TransferOptions,
+        progress: &dyn Progress,
+        stats: &mut TransferStats,
+    ) -> Result<(), Error> {
+        if status.is_directory() {
+            if !local.is_dir() {
+                std::fs::create_dir_all(local)?;
+            }
+            for child in fs.list_status(src)? {
+                let name = base_name(child.name());
+                let src = join_path(src, name);
+                Self::download_status(fs, &src, &child, &local.join(name), opts, progress, stats)?;
+            }
+        } else {
+            if local.exists() {
+                match opts.exists {
+                    ExistsPolicy::Fail => {
+                        return Err(Error::new(
+                            ErrorKind::AlreadyExists,
+                            format!("destination [{}] already exists", local.display()),
+                        ))
+                    }
+                    ExistsPolicy::Skip => {
+                        debug!("skipping download of [{}], destination exists", src);
+                        stats.skipped += 1;
+                        return Ok(());
+                    }
+                    ExistsPolicy::Overwrite => (),
+                }
+            }
+
+            debug!("downloading [{}] to [{}]", src, local.display());
+            let mut reader = fs.open(src)?;
+            let mut writer = std::fs::File::create(local)?;
+            let copied = Self::copy_stream(&mut reader, &mut writer, src, opts, progress)
+                .and_then(|copied| writer.sync_all().map(|_| copied));
+            drop(writer);
+            let copied = match copied {
+                Ok(copied) => copied,
+                Err(e) => {
+                    let _ = std::fs::remove_file(local);
+                    return Err(e);
+                }
+            };
+
+            stats.files += 1;
+            stats.bytes += copied;
+            progress.file_transferred(src, copied);
+        }
+
+        if opts.preserve_permission {
+            let mode = status.permission() as u32 & 0o7777;
+            std::fs::set_permissions(local, std::fs::Permissions::from_mode(mode))?;
+        }
+
+        if opts.preserve_times {
+            let times = FileTimes::new()
+                .set_modified(to_system_time(status.last_modified()))
+                .set_accessed(to_system_time(status.last_accced()));
+            std::fs::File::open(local)?.set_times(times)?;
+        }
+
+        Ok(())
+    }
+
+    fn copy_stream(
+        reader: &mut dyn Read,
+        writer: &mut dyn Write,
+        path: &str,
+        opts: &TransferOptions,
+        progress: &dyn Progress,
+    ) -> Result<u64, Error> {
+        let mut buf = vec![0; opts.buf_size.max(1)];
+        let mut copied = 0;
+        loop {
+            if progress.is_cancelled() {
+                return Err(Error::new(
+                    ErrorKind::Interrupted,
+                    format!("transfer of [{}] cancelled", path),
+                ));
+            }
+            let read = reader.read(&mut buf)?;
+            if read == 0 {
+                break;
+            }
+            writer.write_all(&buf[..read])?;
+            copied += read as u64;
+            progress.bytes_transferred(path, read as u64);
+        }
+        writer.flush()?;
+
+        Ok(copied)
     }
 }
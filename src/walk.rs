@@ -0,0 +1,345 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Recursive directory traversal
+
+use crate::dfs::{FileStatus, HdfsFs};
+use crate::util::{base_name, join_path};
+use log::{debug, warn};
+use std::fmt;
+use std::io::Error;
+use std::sync::Mutex;
+
+type Listing = Result<Vec<FileStatus>, Error>;
+
+/// Filter deciding if a directory should be descended into
+type DirFilter<'a> = Box<dyn FnMut(&str, &FileStatus) -> bool + 'a>;
+
+/// Entry discovered during traversal
+struct Entry {
+    path: String,
+    status: FileStatus,
+    depth: usize,
+    /// should walk descend into this directory
+    descend: bool,
+    /// listing fetched ahead of time in parallel mode
+    listing: Option<Listing>,
+}
+
+/// Directory which is being traversed
+struct Frame {
+    /// directory to be returned after its children (post order)
+    dir: Option<Entry>,
+    children: std::vec::IntoIter<Entry>,
+}
+
+/// Failure to stat or list ```path``` during a walk.
+///
+/// Errors returned by [Walk] keep the kind of the underlying error and
+/// wrap this type, use [error_path] to find out which path failed.
+#[derive(Debug)]
+pub struct WalkError {
+    path: String,
+    source: Error,
+}
+
+impl WalkError {
+    fn into_error(path: &str, source: Error) -> Error {
+        Error::new(
+            source.kind(),
+            WalkError {
+                path: path.to_string(),
+                source,
+            },
+        )
+    }
+
+    /// Path which could not be listed
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+}
+
+impl fmt::Display for WalkError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "failed to list [{}]: {}", self.path, self.source)
+    }
+}
+
+impl std::error::Error for WalkError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.source)
+    }
+}
+
+/// Path which failed if ```error``` has been returned by [Walk]
+pub fn error_path(error: &Error) -> Option<&str> {
+    error
+        .get_ref()
+        .and_then(|e| e.downcast_ref::<WalkError>())
+        .map(WalkError::path)
+}
+
+/// Iterator recursively walking a directory tree, created by [HdfsFs::walk].
+///
+/// Yields ```(path, FileStatus)``` of the root and all its descendants.
+/// Root has depth 0, its children depth 1 and so on.
+///
+/// ```ignore
+/// for entry in fs.walk("/data").max_depth(2).contents_first(true) {
+///     let (path, status) = entry?;
+/// }
+/// ```
+pub struct Walk<'a> {
+    fs: &'a HdfsFs,
+    root: Option<String>,
+    min_depth: usize,
+    max_depth: usize,
+    contents_first: bool,
+    continue_on_error: bool,
+    parallelism: usize,
+    filter: Option<DirFilter<'a>>,
+    stack: Vec<Frame>,
+    failed: bool,
+}
+
+impl<'a> Walk<'a> {
+    pub(crate) fn new(fs: &'a HdfsFs, root: &str) -> Walk<'a> {
+        Walk {
+            fs,
+            root: Some(root.to_string()),
+            min_depth: 0,
+            max_depth: usize::MAX,
+            contents_first: false,
+            continue_on_error: false,
+            parallelism: 1,
+            filter: None,
+            stack: Vec::new(),
+            failed: false,
+        }
+    }
+
+    /// Entries with depth lower than ```depth``` are not returned.
+    pub fn min_depth(mut self, depth: usize) -> Self {
+        self.min_depth = depth;
+        self
+    }
+
+    /// Entries with depth greater than ```depth``` are not returned.
+    pub fn max_depth(mut self, depth: usize) -> Self {
+        self.max_depth = depth;
+        self
+    }
+
+    /// Return directory content before the directory itself (post order).
+    /// By default directory is returned before its content (pre order).
+    pub fn contents_first(mut self, yes: bool) -> Self {
+        self.contents_first = yes;
+        self
+    }
+
+    /// Continue walking after an error has been returned.
+    /// By default iteration stops after first error.
+    pub fn continue_on_error(mut self, yes: bool) -> Self {
+        self.continue_on_error = yes;
+        self
+    }
+
+    /// List up to ```threads``` sibling directories concurrently.
+    /// Useful for very wide trees, memory usage grows with the width of a level.
+    pub fn parallelism(mut self, threads: usize) -> Self {
+        self.parallelism = threads.max(1);
+        self
+    }
+
+    /// Directories for which ```predicate``` returns ```false``` are skipped
+    /// together with their content.
+    pub fn filter_dir<P>(mut self, predicate: P) -> Self
+    where
+        P: FnMut(&str, &FileStatus) -> bool + 'a,
+    {
+        self.filter = Some(Box::new(predicate));
+        self
+    }
+
+    fn should_descend(&mut self, path: &str, status: &FileStatus, depth: usize) -> bool {
+        if !status.is_directory() || depth >= self.max_depth {
+            return false;
+        }
+        match self.filter.as_mut() {
+            Some(filter) => filter(path, status),
+            None => true,
+        }
+    }
+
+    fn entry(&mut self, path: String, status: FileStatus, depth: usize) -> Option<Entry> {
+        let descend = self.should_descend(&path, &status, depth);
+        if status.is_directory() && !descend && depth < self.max_depth {
+            debug!("walk pruning directory [{}]", path);
+            return None;
+        }
+        Some(Entry {
+            path,
+            status,
+            depth,
+            descend,
+            listing: None,
+        })
+    }
+
+    fn children(&mut self, parent: &str, depth: usize, listing: Vec<FileStatus>) -> Vec<Entry> {
+        let mut children: Vec<Entry> = listing
+            .into_iter()
+            .filter_map(|status| {
+                let path = join_path(parent, base_name(status.name()));
+                self.entry(path, status, depth)
+            })
+            .collect();
+
+        if self.parallelism > 1 {
+            self.prefetch(&mut children);
+        }
+
+        children
+    }
+
+    /// lists directories which are going to be descended into concurrently
+    fn prefetch(&self, children: &mut [Entry]) {
+        let queue: Mutex<Vec<(usize, &str)>> = Mutex::new(
+            children
+                .iter()
+                .enumerate()
+                .filter(|(_, entry)| entry.descend)
+                .map(|(idx, entry)| (idx, entry.path.as_str()))
+                .collect(),
+        );
+        let fs = self.fs;
+
+        let listings: Vec<(usize, Listing)> = std::thread::scope(|scope| {
+            let handles: Vec<_> = (0..self.parallelism)
+                .map(|_| {
+                    scope.spawn(|| {
+                        let mut listings = Vec::new();
+                        loop {
+                            let (idx, path) = match queue.lock().unwrap().pop() {
+                                Some(item) => item,
+                                None => return listings,
+                            };
+                            listings.push((idx, fs.list_status(path)));
+                        }
+                    })
+                })
+                .collect();
+
+            handles
+                .into_iter()
+                .flat_map(|handle| handle.join().unwrap())
+                .collect()
+        });
+
+        for (idx, listing) in listings {
+            children[idx].listing = Some(listing);
+        }
+    }
+
+    fn error(&mut self, path: &str, e: Error) -> Error {
+        warn!("walk failed to list [{}]: {}", path, e);
+        if !self.continue_on_error {
+            self.failed = true;
+        }
+        WalkError::into_error(path, e)
+    }
+
+    /// Returns entry if it should be yielded now
+    fn visit(&mut self, mut entry: Entry) -> Option<Result<(String, FileStatus), Error>> {
+        if entry.descend {
+            let listing = match entry.listing.take() {
+                Some(listing) => listing,
+                None => self.fs.list_status(&entry.path),
+            };
+            let listing = match listing {
+                Ok(listing) => listing,
+                Err(e) => return Some(Err(self.error(&entry.path, e))),
+            };
+
+            let children = self.children(&entry.path, entry.depth + 1, listing);
+            if self.contents_first {
+                self.stack.push(Frame {
+                    dir: Some(entry),
+                    children: children.into_iter(),
+                });
+                return None;
+            }
+            self.stack.push(Frame {
+                dir: None,
+                children: children.into_iter(),
+            });
+        }
+
+        if entry.depth >= self.min_depth {
+            Some(Ok((entry.path, entry.status)))
+        } else {
+            None
+        }
+    }
+}
+
+impl<'a> Iterator for Walk<'a> {
+    type Item = Result<(String, FileStatus), Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if self.failed {
+                return None;
+            }
+
+            if let Some(root) = self.root.take() {
+                let status = match self.fs.get_file_status(&root) {
+                    Ok(status) => status,
+                    Err(e) => {
+                        self.failed = true;
+                        return Some(Err(WalkError::into_error(&root, e)));
+                    }
+                };
+                match self.entry(root, status, 0) {
+                    Some(entry) => match self.visit(entry) {
+                        Some(item) => return Some(item),
+                        None => continue,
+                    },
+                    None => return None,
+                }
+            }
+
+            let frame = self.stack.last_mut()?;
+            match frame.children.next() {
+                Some(entry) => {
+                    if let Some(item) = self.visit(entry) {
+                        return Some(item);
+                    }
+                }
+                None => {
+                    let frame = self.stack.pop().unwrap();
+                    if let Some(dir) = frame.dir {
+                        if dir.depth >= self.min_depth {
+                            return Some(Ok((dir.path, dir.status)));
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
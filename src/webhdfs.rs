@@ -0,0 +1,773 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! WebHDFS backend of [crate::HdfsFs], enabled by ```webhdfs``` feature
+//!
+//! [crate::HdfsRegistry::get] serves ```webhdfs://host:port``` urls by the
+//! WebHDFS REST API of a name node, or of an HttpFS gateway, in pure rust
+//! without calling into libhdfs3, ```swebhdfs://``` uses https. User is taken
+//! from the url, as in ```webhdfs://user@host:port```, or from
+//! ```HADOOP_USER_NAME``` variable. Relative paths are resolved against the
+//! home directory of the user.
+//!
+//! Files opened for reading issue a new ```OPEN``` request whenever they are
+//! read after a seek. Files opened for writing are created when they are
+//! opened. Written data is buffered and sent by an ```APPEND``` request when
+//! the file is flushed or closed, or when [WRITE_BUFFER_SIZE] bytes are
+//! buffered. [crate::HdfsFs::list_status_iter] fetches directories page by
+//! page with ```LISTSTATUS_BATCH```.
+//!
+//! ```ignore
+//! let fs = HdfsRegistry::new().get("webhdfs://namenode:9870")?;
+//! fs.create("/data/file.txt")?.write_all(b"data")?;
+//! let mut content = String::new();
+//! fs.open("/data/file.txt")?.read_to_string(&mut content)?;
+//! ```
+
+use crate::dfs::{Backend, BlockLocation, FileBackend, FileStatus, OpenMode};
+use crate::metadata::FileKind;
+use crate::permission::FsPermission;
+use crate::util::{join_path, strip_authority};
+use libc::time_t;
+use log::debug;
+use serde_json::Value;
+use std::cell::{Cell, RefCell};
+use std::cmp::min;
+use std::fmt::{Debug, Formatter};
+use std::io::{Error, ErrorKind, Read};
+use std::sync::OnceLock;
+use std::time::Duration;
+use url::Url;
+
+/// Path of the REST API on the name node
+const API_PATH: &str = "webhdfs/v1";
+
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Written data buffered before it is sent by an ```APPEND``` request
+const WRITE_BUFFER_SIZE: usize = 8 * 1024 * 1024;
+
+type Body = Box<dyn Read + Send + Sync>;
+
+/// WebHDFS client behind [crate::HdfsFs]
+pub(crate) struct WebHdfsFs {
+    url: String,
+    /// http address of the REST API
+    base: Url,
+    user: Option<String>,
+    agent: ureq::Agent,
+    /// home directory of the user, relative paths are resolved against it
+    home: OnceLock<String>,
+}
+
+impl Debug for WebHdfsFs {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WebHdfsFs")
+            .field("url", &self.url)
+            .field("user", &self.user)
+            .finish()
+    }
+}
+
+impl WebHdfsFs {
+    /// Client of ```webhdfs://[user@]host:port``` or ```swebhdfs://[user@]host:port```
+    pub(crate) fn new(url: &str) -> Result<WebHdfsFs, Error> {
+        let parsed = Url::parse(url).map_err(|e| {
+            Error::new(
+                ErrorKind::InvalidInput,
+                format!("invalid url [{}]: {}", url, e),
+            )
+        })?;
+        let scheme = match parsed.scheme() {
+            "webhdfs" => "http",
+            "swebhdfs" => "https",
+            other => {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    format!(
+                        "unsupported scheme [{}], expected webhdfs or swebhdfs",
+                        other
+                    ),
+                ))
+            }
+        };
+        let (host, port) = match (parsed.host_str(), parsed.port()) {
+            (Some(host), Some(port)) => (host, port),
+            _ => {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    format!("host and port expected in [{}]", url),
+                ))
+            }
+        };
+        let base = Url::parse(&format!("{}://{}:{}/{}", scheme, host, port, API_PATH))
+            .map_err(|e| Error::new(ErrorKind::InvalidInput, e))?;
+        let user = match parsed.username() {
+            "" => std::env::var("HADOOP_USER_NAME").ok(),
+            user => Some(user.to_string()),
+        };
+        // redirects to data nodes are followed explicitly, as uploads
+        // are sent only to the data node
+        let agent = ureq::AgentBuilder::new()
+            .redirects(0)
+            .timeout_connect(CONNECT_TIMEOUT)
+            .build();
+
+        Ok(WebHdfsFs {
+            url: format!("{}://{}:{}", parsed.scheme(), host, port),
+            base,
+            user,
+            agent,
+            home: OnceLock::new(),
+        })
+    }
+
+    pub(crate) fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// User requests are made as, if any
+    pub(crate) fn user(&self) -> Option<&str> {
+        self.user.as_deref()
+    }
+
+    fn home(&self) -> Result<&str, Error> {
+        if let Some(home) = self.home.get() {
+            return Ok(home);
+        }
+        let json = self.json("GET", &self.op_url("/", "GETHOMEDIRECTORY", &[])?)?;
+        let home = json["Path"]
+            .as_str()
+            .ok_or_else(|| invalid_data("home directory expected".to_string()))?;
+        Ok(self.home.get_or_init(|| home.to_string()))
+    }
+
+    /// ```path``` without scheme and authority, resolved against
+    /// the home directory if it is relative
+    fn absolute(&self, path: &str) -> Result<String, Error> {
+        if path.starts_with('/') {
+            Ok(path.to_string())
+        } else if path.contains("://") {
+            Ok(strip_authority(path))
+        } else {
+            Ok(join_path(self.home()?, path.trim_start_matches("./")))
+        }
+    }
+
+    fn op_url(&self, path: &str, op: &str, params: &[(&str, &str)]) -> Result<Url, Error> {
+        let path = self.absolute(path)?;
+        let mut url = self.base.clone();
+        {
+            let mut segments = url.path_segments_mut().unwrap();
+            segments.extend(path.split('/').filter(|segment| !segment.is_empty()));
+            if path.ends_with('/') {
+                // root is addressed as /webhdfs/v1/
+                segments.push("");
+            }
+        }
+        {
+            let mut query = url.query_pairs_mut();
+            query.append_pair("op", op);
+            if let Some(user) = &self.user {
+                query.append_pair("user.name", user);
+            }
+            for (name, value) in params {
+                query.append_pair(name, value);
+            }
+        }
+        Ok(url)
+    }
+
+    /// Send request, responses with error status are converted to errors
+    fn call(
+        &self,
+        method: &str,
+        url: &Url,
+        data: Option<&mut dyn Read>,
+    ) -> Result<ureq::Response, Error> {
+        debug!("webhdfs {} {}", method, url);
+        let request = self.agent.request_url(method, url);
+        let result = match data {
+            Some(data) => request
+                .set("Content-Type", "application/octet-stream")
+                .send(data),
+            None => request.call(),
+        };
+        match result {
+            Ok(response) => Ok(response),
+            Err(ureq::Error::Status(status, response)) => Err(remote_error(status, response)),
+            Err(ureq::Error::Transport(e)) => Err(Error::other(e)),
+        }
+    }
+
+    /// Issue request to the name node and send ```data``` to the data node
+    /// it redirects to
+    fn upload(&self, method: &str, url: &Url, data: &mut dyn Read) -> Result<(), Error> {
+        let response = self.call(method, url, None)?;
+        let location = redirect(&response)?.ok_or_else(|| {
+            invalid_data(format!(
+                "redirect expected, got status {} from [{}]",
+                response.status(),
+                url
+            ))
+        })?;
+        self.call(method, &location, Some(data))?;
+        Ok(())
+    }
+
+    fn open_range(&self, path: &str, offset: u64, buf_size: i32) -> Result<Body, Error> {
+        let offset = offset.to_string();
+        let buf_size = buf_size.to_string();
+        let mut params = vec![("offset", offset.as_str())];
+        if buf_size != "0" {
+            params.push(("buffersize", &buf_size));
+        }
+        let url = self.op_url(path, "OPEN", &params)?;
+        let response = self.call("GET", &url, None)?;
+        let response = match redirect(&response)? {
+            Some(location) => self.call("GET", &location, None)?,
+            None => response,
+        };
+        Ok(response.into_reader())
+    }
+
+    fn json(&self, method: &str, url: &Url) -> Result<Value, Error> {
+        let response = self.call(method, url, None)?;
+        serde_json::from_reader(response.into_reader())
+            .map_err(|e| Error::new(ErrorKind::InvalidData, e))
+    }
+
+    /// Boolean result of an operation, ```false``` is returned as
+    /// an error of ```kind```
+    fn boolean(
+        &self,
+        method: &str,
+        url: &Url,
+        kind: ErrorKind,
+        failure: &str,
+    ) -> Result<(), Error> {
+        match self.json(method, url)?["boolean"].as_bool() {
+            Some(true) => Ok(()),
+            Some(false) => Err(Error::new(kind, failure.to_string())),
+            None => Err(invalid_data(format!("boolean expected from [{}]", url))),
+        }
+    }
+
+    /// Number ```field``` of an ```object``` returned by operation ```op```
+    /// of the filesystem
+    fn number(&self, op: &str, object: &str, field: &str) -> Result<usize, Error> {
+        let json = self.json("GET", &self.op_url("/", op, &[])?)?;
+        json[object][field]
+            .as_u64()
+            .map(|value| value as usize)
+            .ok_or_else(|| invalid_data(format!("{} expected from {}", field, op)))
+    }
+}
+
+impl Backend for WebHdfsFs {
+    fn open(&self, path: &str, mode: OpenMode) -> Result<Box<dyn FileBackend + '_>, Error> {
+        let path = self.absolute(path)?;
+        match mode {
+            OpenMode::Read { buf_size } => {
+                let status = self.get_file_status(&path)?;
+                if status.is_directory() {
+                    return Err(Error::new(
+                        ErrorKind::InvalidInput,
+                        format!("[{}] is a directory", path),
+                    ));
+                }
+                Ok(Box::new(WebHdfsReader {
+                    fs: self,
+                    path,
+                    len: status.len() as u64,
+                    buf_size,
+                    pos: Cell::new(0),
+                    body: RefCell::new(None),
+                }))
+            }
+            OpenMode::Write {
+                overwrite,
+                buf_size,
+                replication,
+                block_size,
+            } => {
+                let overwrite = overwrite.to_string();
+                let buf_size = buf_size.to_string();
+                let replication = replication.to_string();
+                let block_size = block_size.to_string();
+                let mut params = vec![("overwrite", overwrite.as_str())];
+                // zero uses defaults of the filesystem
+                for (name, value) in [
+                    ("buffersize", &buf_size),
+                    ("replication", &replication),
+                    ("blocksize", &block_size),
+                ] {
+                    if value != "0" {
+                        params.push((name, value));
+                    }
+                }
+                let url = self.op_url(&path, "CREATE", &params)?;
+                self.upload("PUT", &url, &mut std::io::empty())?;
+                Ok(Box::new(WebHdfsWriter::new(self, path, 0)))
+            }
+            OpenMode::Append => {
+                let len = self.get_file_status(&path)?.len() as u64;
+                Ok(Box::new(WebHdfsWriter::new(self, path, len)))
+            }
+        }
+    }
+
+    fn chmod(&self, path: &str, mode: i16) -> Result<(), Error> {
+        let permission = format!("{:o}", FsPermission::from(mode).mode());
+        let url = self.op_url(path, "SETPERMISSION", &[("permission", &permission)])?;
+        self.call("PUT", &url, None)?;
+        Ok(())
+    }
+
+    /// Change owner and group of ```path```, empty values are left unchanged
+    fn chown(&self, path: &str, owner: &str, group: &str) -> Result<(), Error> {
+        let params: Vec<(&str, &str)> = [("owner", owner), ("group", group)]
+            .into_iter()
+            .filter(|(_, value)| !value.is_empty())
+            .collect();
+        let url = self.op_url(path, "SETOWNER", &params)?;
+        self.call("PUT", &url, None)?;
+        Ok(())
+    }
+
+    fn default_block_size(&self) -> Result<usize, Error> {
+        self.number("GETSERVERDEFAULTS", "FsServerDefaults", "blockSize")
+    }
+
+    fn capacity(&self) -> Result<usize, Error> {
+        self.number("GETSTATUS", "FsStatus", "capacity")
+    }
+
+    fn used(&self) -> Result<usize, Error> {
+        self.number("GETSTATUS", "FsStatus", "used")
+    }
+
+    fn delete(&self, path: &str, recursive: bool) -> Result<(), Error> {
+        let recursive = recursive.to_string();
+        let url = self.op_url(path, "DELETE", &[("recursive", &recursive)])?;
+        // false is returned if path does not exist
+        let failure = format!("[{}] does not exist", path);
+        self.boolean("DELETE", &url, ErrorKind::NotFound, &failure)
+    }
+
+    fn exist(&self, path: &str) -> bool {
+        self.get_file_status(path).is_ok()
+    }
+
+    fn get_hosts(&self, path: &str, start: u64, length: u64) -> Result<Vec<Vec<String>>, Error> {
+        let locations = self.get_block_locations(path, start, length)?;
+        Ok(locations
+            .into_iter()
+            .map(|location| location.hosts)
+            .collect())
+    }
+
+    fn get_block_locations(
+        &self,
+        path: &str,
+        start: u64,
+        length: u64,
+    ) -> Result<Vec<BlockLocation>, Error> {
+        let offset = start.to_string();
+        let length = length.to_string();
+        let params = [("offset", offset.as_str()), ("length", length.as_str())];
+        let json = self.json("GET", &self.op_url(path, "GETFILEBLOCKLOCATIONS", &params)?)?;
+
+        // empty files have no blocks
+        let locations = match json["BlockLocations"]["BlockLocation"].as_array() {
+            Some(locations) => locations,
+            None => return Ok(Vec::new()),
+        };
+        Ok(locations.iter().map(block_location).collect())
+    }
+
+    fn mkdir(&self, path: &str) -> Result<(), Error> {
+        let url = self.op_url(path, "MKDIRS", &[])?;
+        let failure = format!("failed to create [{}]", path);
+        self.boolean("PUT", &url, ErrorKind::Other, &failure)
+    }
+
+    fn set_times(&self, path: &str, mtime: time_t, atime: time_t) -> Result<(), Error> {
+        // milliseconds are expected, -1 keeps the current value
+        let millis = |time: time_t| if time < 0 { -1 } else { time * 1000 }.to_string();
+        let mtime = millis(mtime);
+        let atime = millis(atime);
+        let params = [("modificationtime", mtime.as_str()), ("accesstime", &atime)];
+        self.call("PUT", &self.op_url(path, "SETTIMES", &params)?, None)?;
+        Ok(())
+    }
+
+    fn set_replication(&self, path: &str, num: i16) -> Result<(), Error> {
+        let num = num.to_string();
+        let url = self.op_url(path, "SETREPLICATION", &[("replication", &num)])?;
+        // false is returned for directories
+        let failure = format!("failed to set replication of [{}]", path);
+        self.boolean("PUT", &url, ErrorKind::InvalidInput, &failure)
+    }
+
+    fn rename(&self, old_path: &str, new_path: &str) -> Result<(), Error> {
+        let destination = self.absolute(new_path)?;
+        let url = self.op_url(old_path, "RENAME", &[("destination", &destination)])?;
+        let failure = format!("failed to rename [{}] to [{}]", old_path, new_path);
+        self.boolean("PUT", &url, ErrorKind::Other, &failure)
+    }
+
+    fn list_status(&self, path: &str) -> Result<Vec<FileStatus>, Error> {
+        let path = self.absolute(path)?;
+        let json = self.json("GET", &self.op_url(&path, "LISTSTATUS", &[])?)?;
+        listing(&path, &json["FileStatuses"]["FileStatus"])
+    }
+
+    fn list_status_batch(
+        &self,
+        path: &str,
+        start_after: Option<&str>,
+    ) -> Result<(Vec<FileStatus>, Option<String>), Error> {
+        let path = self.absolute(path)?;
+        let params: Vec<(&str, &str)> = start_after
+            .map(|name| ("startAfter", name))
+            .into_iter()
+            .collect();
+        let json = match self.json("GET", &self.op_url(&path, "LISTSTATUS_BATCH", &params)?) {
+            Ok(json) => json,
+            // HttpFS before Hadoop 3 does not know the operation
+            Err(e) if e.kind() == ErrorKind::InvalidInput && start_after.is_none() => {
+                return Ok((self.list_status(&path)?, None));
+            }
+            Err(e) => return Err(e),
+        };
+        let json = &json["DirectoryListing"];
+        let statuses = &json["partialListing"]["FileStatuses"]["FileStatus"];
+        let entries = listing(&path, statuses)?;
+
+        let next = match json["remainingEntries"].as_u64() {
+            Some(remaining) if remaining > 0 => statuses
+                .as_array()
+                .and_then(|statuses| statuses.last())
+                .and_then(|status| status["pathSuffix"].as_str())
+                .map(str::to_string),
+            _ => None,
+        };
+        Ok((entries, next))
+    }
+
+    fn working_directory(&self) -> Result<String, Error> {
+        self.home().map(str::to_string)
+    }
+
+    fn get_file_status(&self, path: &str) -> Result<FileStatus, Error> {
+        let path = self.absolute(path)?;
+        let json = self.json("GET", &self.op_url(&path, "GETFILESTATUS", &[])?)?;
+        file_status(&path, &json["FileStatus"])
+    }
+}
+
+/// File opened for reading.
+///
+/// Content is streamed by a single ```OPEN``` request until the file is
+/// seeked to a different position.
+struct WebHdfsReader<'a> {
+    fs: &'a WebHdfsFs,
+    path: String,
+    /// length of the file when it was opened
+    len: u64,
+    buf_size: i32,
+    pos: Cell<u64>,
+    body: RefCell<Option<Body>>,
+}
+
+impl FileBackend for WebHdfsReader<'_> {
+    fn available(&self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn close(&mut self) -> Result<(), Error> {
+        self.body.get_mut().take();
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<(), Error> {
+        Err(not_open_for("write"))
+    }
+
+    fn hflush(&mut self) -> Result<(), Error> {
+        Err(not_open_for("write"))
+    }
+
+    fn is_readable(&self) -> bool {
+        true
+    }
+
+    fn is_writable(&self) -> bool {
+        false
+    }
+
+    fn pos(&self) -> Result<u64, Error> {
+        Ok(self.pos.get())
+    }
+
+    fn read(&self, buf: &mut [u8]) -> Result<usize, Error> {
+        let pos = self.pos.get();
+        if buf.is_empty() || pos >= self.len {
+            return Ok(0);
+        }
+        let mut body = self.body.borrow_mut();
+        let body = match &mut *body {
+            Some(body) => body,
+            None => body.insert(self.fs.open_range(&self.path, pos, self.buf_size)?),
+        };
+        let read = body.read(buf)?;
+        self.pos.set(pos + read as u64);
+        Ok(read)
+    }
+
+    fn seek(&self, offset: u64) -> Result<(), Error> {
+        if offset > self.len {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("seek past the end of [{}]", self.path),
+            ));
+        }
+        if offset != self.pos.get() {
+            self.body.borrow_mut().take();
+            self.pos.set(offset);
+        }
+        Ok(())
+    }
+
+    fn write(&mut self, _buf: &[u8]) -> Result<usize, Error> {
+        Err(not_open_for("write"))
+    }
+
+    fn sync(&mut self) -> Result<(), Error> {
+        Err(not_open_for("write"))
+    }
+}
+
+/// File opened for writing, which exists already.
+///
+/// Written data is buffered and appended to the file on flush.
+struct WebHdfsWriter<'a> {
+    fs: &'a WebHdfsFs,
+    path: String,
+    /// length of the file including buffered data
+    pos: u64,
+    buf: Vec<u8>,
+}
+
+impl<'a> WebHdfsWriter<'a> {
+    fn new(fs: &'a WebHdfsFs, path: String, pos: u64) -> WebHdfsWriter<'a> {
+        WebHdfsWriter {
+            fs,
+            path,
+            pos,
+            buf: Vec::new(),
+        }
+    }
+
+    /// Append buffered data to the file
+    fn send(&mut self) -> Result<(), Error> {
+        if self.buf.is_empty() {
+            return Ok(());
+        }
+        let url = self.fs.op_url(&self.path, "APPEND", &[])?;
+        self.fs.upload("POST", &url, &mut self.buf.as_slice())?;
+        self.buf.clear();
+        Ok(())
+    }
+}
+
+impl FileBackend for WebHdfsWriter<'_> {
+    fn available(&self) -> Result<(), Error> {
+        Err(not_open_for("read"))
+    }
+
+    fn close(&mut self) -> Result<(), Error> {
+        self.send()
+    }
+
+    fn flush(&mut self) -> Result<(), Error> {
+        self.send()
+    }
+
+    fn hflush(&mut self) -> Result<(), Error> {
+        self.send()
+    }
+
+    fn is_readable(&self) -> bool {
+        false
+    }
+
+    fn is_writable(&self) -> bool {
+        true
+    }
+
+    fn pos(&self) -> Result<u64, Error> {
+        Ok(self.pos)
+    }
+
+    fn read(&self, _buf: &mut [u8]) -> Result<usize, Error> {
+        Err(not_open_for("read"))
+    }
+
+    fn seek(&self, _offset: u64) -> Result<(), Error> {
+        Err(not_open_for("read"))
+    }
+
+    fn write(&mut self, buf: &[u8]) -> Result<usize, Error> {
+        // large slices are appended in chunks, so the buffer stays bounded
+        let mut written = 0;
+        while written < buf.len() {
+            if self.buf.len() >= WRITE_BUFFER_SIZE {
+                match self.send() {
+                    Ok(()) => {}
+                    Err(e) if written == 0 => return Err(e),
+                    // accepted data is reported, the error is returned by the next call
+                    Err(_) => break,
+                }
+            }
+            let len = min(WRITE_BUFFER_SIZE - self.buf.len(), buf.len() - written);
+            self.buf.extend_from_slice(&buf[written..written + len]);
+            written += len;
+        }
+        self.pos += written as u64;
+        Ok(written)
+    }
+
+    fn sync(&mut self) -> Result<(), Error> {
+        self.send()
+    }
+}
+
+fn not_open_for(mode: &str) -> Error {
+    Error::new(
+        ErrorKind::Unsupported,
+        format!("file is not open for {}", mode),
+    )
+}
+
+/// Location of a redirect response
+fn redirect(response: &ureq::Response) -> Result<Option<Url>, Error> {
+    if !(300..400).contains(&response.status()) {
+        return Ok(None);
+    }
+    let location = response
+        .header("Location")
+        .ok_or_else(|| invalid_data("redirect without location".to_string()))?;
+    Url::parse(location)
+        .map(Some)
+        .map_err(|e| invalid_data(format!("invalid redirect [{}]: {}", location, e)))
+}
+
+/// Converts ```RemoteException``` returned by the server
+fn remote_error(status: u16, response: ureq::Response) -> Error {
+    let json: Value = serde_json::from_reader(response.into_reader()).unwrap_or(Value::Null);
+    let exception = &json["RemoteException"];
+    let message = exception["message"]
+        .as_str()
+        .map(str::to_string)
+        .unwrap_or_else(|| format!("webhdfs request failed with status {}", status));
+
+    let kind = match (exception["exception"].as_str().unwrap_or(""), status) {
+        ("FileNotFoundException", _) | (_, 404) => ErrorKind::NotFound,
+        ("FileAlreadyExistsException", _) => ErrorKind::AlreadyExists,
+        ("AccessControlException", _) | ("SecurityException", _) | (_, 401) | (_, 403) => {
+            ErrorKind::PermissionDenied
+        }
+        ("IllegalArgumentException", _) | ("InvalidPathException", _) | (_, 400) => {
+            ErrorKind::InvalidInput
+        }
+        _ => ErrorKind::Other,
+    };
+    Error::new(kind, message)
+}
+
+/// Entries of ```path``` listed by ```LISTSTATUS``` or ```LISTSTATUS_BATCH```
+fn listing(path: &str, statuses: &Value) -> Result<Vec<FileStatus>, Error> {
+    let statuses = statuses
+        .as_array()
+        .ok_or_else(|| invalid_data(format!("unexpected listing of [{}]", path)))?;
+
+    statuses
+        .iter()
+        .map(|status| match status["pathSuffix"].as_str() {
+            // listing of a file returns the file itself
+            None | Some("") => file_status(path, status),
+            Some(name) => file_status(&join_path(path, name), status),
+        })
+        .collect()
+}
+
+fn file_status(path: &str, status: &Value) -> Result<FileStatus, Error> {
+    let kind = match status["type"].as_str() {
+        Some("DIRECTORY") => FileKind::Directory,
+        Some("FILE") | Some("SYMLINK") => FileKind::File,
+        _ => return Err(invalid_data(format!("unexpected status of [{}]", path))),
+    };
+    let permission = status["permission"].as_str().unwrap_or("0");
+    let permission = u16::from_str_radix(permission, 8)
+        .map_err(|_| invalid_data(format!("invalid permission [{}]", permission)))?;
+    let text = |field: &str| status[field].as_str().unwrap_or("").to_string();
+    let number = |field: &str| status[field].as_u64().unwrap_or(0);
+    // times are in milliseconds
+    let seconds = |field: &str| (number(field) / 1000) as time_t;
+
+    Ok(FileStatus {
+        name: path.to_string(),
+        kind,
+        owner: text("owner"),
+        group: text("group"),
+        permission: FsPermission::from_mode(permission).into(),
+        len: number("length") as usize,
+        block_size: number("blockSize") as usize,
+        replication: number("replication") as i16,
+        last_modified: seconds("modificationTime"),
+        last_access: seconds("accessTime"),
+    })
+}
+
+fn block_location(location: &Value) -> BlockLocation {
+    let strings = |field: &str| {
+        location[field]
+            .as_array()
+            .map(|values| {
+                values
+                    .iter()
+                    .filter_map(|value| value.as_str().map(str::to_string))
+                    .collect()
+            })
+            .unwrap_or_default()
+    };
+
+    BlockLocation {
+        offset: location["offset"].as_u64().unwrap_or(0),
+        length: location["length"].as_u64().unwrap_or(0),
+        hosts: strings("hosts"),
+        names: strings("names"),
+        topology_paths: strings("topologyPaths"),
+        corrupt: location["corrupt"].as_bool().unwrap_or(false),
+    }
+}
+
+fn invalid_data(message: String) -> Error {
+    Error::new(ErrorKind::InvalidData, message)
+}
@@ -0,0 +1,188 @@
+mod common;
+
+#[cfg(all(test, feature = "async"))]
+mod e2e {
+
+    use std::io::SeekFrom;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use crate::common::*;
+    use futures::{StreamExt, TryStreamExt};
+    use hdfs_native::{AsyncHdfsFs, BlockingPool, HdfsRegistry};
+    use log::info;
+    use tokio::io::{AsyncReadExt, AsyncSeekExt, AsyncWriteExt};
+
+    fn async_fs(pool_size: usize) -> AsyncHdfsFs {
+        let hdfs_server_url = generate_hdfs_url();
+        info!("HDFS Name node to be used: [{}]", hdfs_server_url);
+
+        let fs = HdfsRegistry::new()
+            .get(&hdfs_server_url)
+            .expect("creation of registry");
+        AsyncHdfsFs::new(fs, pool_size).expect("pool started")
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn should_write_read_and_seek() {
+        let fs = async_fs(2);
+        let test_file = format!("/{}", generate_unique_name());
+
+        let mut file = fs.create(&test_file).await.expect("file created");
+        file.write_all(DATA.as_bytes()).await.expect("data written");
+        file.write_all(b" more").await.expect("data written");
+        assert_eq!(
+            (DATA.len() + 5) as u64,
+            file.stream_position().await.expect("position")
+        );
+        file.shutdown().await.expect("file closed");
+        assert!(file.write_all(b"closed").await.is_err());
+
+        let expected = format!("{} more", DATA);
+        let mut file = fs.open(&test_file).await.expect("file opened");
+        let mut content = String::new();
+        file.read_to_string(&mut content).await.expect("data read");
+        assert_eq!(expected, content);
+
+        let pos = file.seek(SeekFrom::Start(3)).await.expect("seek");
+        assert_eq!(3, pos);
+        let mut buf = [0u8; 4];
+        file.read_exact(&mut buf).await.expect("data read");
+        assert_eq!(&expected.as_bytes()[3..7], &buf);
+
+        let pos = file.seek(SeekFrom::Current(-2)).await.expect("seek");
+        assert_eq!(5, pos);
+        let pos = file.seek(SeekFrom::End(-4)).await.expect("seek");
+        assert_eq!((expected.len() - 4) as u64, pos);
+        let mut tail = Vec::new();
+        file.read_to_end(&mut tail).await.expect("data read");
+        assert_eq!(b"more", tail.as_slice());
+        assert!(file.seek(SeekFrom::Current(-1000)).await.is_err());
+
+        let mut file = fs.append(&test_file).await.expect("file opened");
+        assert_eq!(
+            expected.len() as u64,
+            file.stream_position().await.expect("position")
+        );
+        file.write_all(b"!").await.expect("data written");
+        file.shutdown().await.expect("file closed");
+
+        let status = fs.get_file_status(&test_file).await.expect("file status");
+        assert_eq!(expected.len() + 1, status.len());
+
+        assert!(fs.open("/missing/file").await.is_err());
+        assert!(fs.delete(&test_file, false).await.expect("file deleted"));
+        assert!(!fs.exist(&test_file).await.expect("exists"));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn should_run_metadata_operations() {
+        let fs = async_fs(2);
+        let test_dir = format!("/{}", generate_unique_name());
+
+        assert!(fs.mkdir(&format!("{}/a", test_dir)).await.expect("mkdir"));
+        let mut file = fs
+            .create(&format!("{}/b.txt", test_dir))
+            .await
+            .expect("file created");
+        file.write_all(DATA.as_bytes()).await.expect("data written");
+        file.shutdown().await.expect("file closed");
+
+        let listing = fs.list_status(&test_dir).await.expect("listed");
+        assert_eq!(2, listing.len());
+        let mut names: Vec<String> = fs
+            .list_status_stream(&test_dir)
+            .map_ok(|status| status.name().to_string())
+            .try_collect()
+            .await
+            .expect("streamed");
+        names.sort();
+        assert!(names[0].ends_with("/a") && names[1].ends_with("/b.txt"));
+        let missing = format!("{}/missing", test_dir);
+        assert!(fs
+            .list_status_stream(&missing)
+            .next()
+            .await
+            .unwrap()
+            .is_err());
+        assert_eq!(
+            1,
+            fs.glob(&format!("{}/*.txt", test_dir)).await.unwrap().len()
+        );
+
+        assert!(fs
+            .rename(
+                &format!("{}/b.txt", test_dir),
+                &format!("{}/c.txt", test_dir)
+            )
+            .await
+            .expect("renamed"));
+        assert!(fs
+            .chmod(&format!("{}/c.txt", test_dir), 0o600)
+            .await
+            .unwrap());
+        let status = fs
+            .get_file_status(&format!("{}/c.txt", test_dir))
+            .await
+            .expect("status");
+        assert_eq!(0o600, status.permission());
+        let err = fs
+            .chmod(&format!("{}/missing", test_dir), 0o600)
+            .await
+            .expect_err("missing path");
+        assert_eq!(std::io::ErrorKind::NotFound, err.kind());
+
+        let summary = fs.content_summary(&test_dir).await.expect("summary");
+        assert_eq!(DATA.len() as u64, summary.length);
+        assert_eq!(1, summary.file_count);
+
+        // any blocking operation can be executed on the pool
+        let walked = fs
+            .run({
+                let test_dir = test_dir.clone();
+                move |fs| Ok(fs.walk(&test_dir).count())
+            })
+            .await
+            .expect("walked");
+        assert_eq!(3, walked);
+
+        assert!(fs.delete(&test_dir, true).await.expect("deleted"));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn should_cap_concurrent_calls() {
+        let pool = Arc::new(BlockingPool::new(2).expect("pool started"));
+        assert_eq!(2, pool.size());
+
+        let running = Arc::new(AtomicUsize::new(0));
+        let max = Arc::new(AtomicUsize::new(0));
+        let calls: Vec<_> = (0..8)
+            .map(|_| {
+                let (running, max) = (running.clone(), max.clone());
+                pool.spawn(move || {
+                    let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+                    max.fetch_max(now, Ordering::SeqCst);
+                    std::thread::sleep(Duration::from_millis(20));
+                    running.fetch_sub(1, Ordering::SeqCst);
+                    Ok(())
+                })
+            })
+            .collect();
+        for call in calls {
+            call.await.expect("call finished");
+        }
+        assert_eq!(2, max.load(Ordering::SeqCst));
+
+        let panicked = pool.spawn(|| -> Result<(), std::io::Error> { panic!("failed call") });
+        assert!(panicked.await.is_err());
+        // pool survives panics
+        assert_eq!(1, pool.spawn(|| Ok(1)).await.expect("call finished"));
+
+        let fs = HdfsRegistry::new()
+            .get(&generate_hdfs_url())
+            .expect("creation of registry");
+        let fs = AsyncHdfsFs::with_pool(fs, pool);
+        assert!(fs.exist("/").await.expect("exists"));
+    }
+}
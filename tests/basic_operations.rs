@@ -247,4 +247,25 @@ mod e2e {
 
         fs.delete(&test_file, false).expect("file to be deleted");
     }
+
+    #[test]
+    fn should_list_empty_directory() {
+        let fs_registry = HdfsRegistry::new();
+        let hdfs_server_url = generate_hdfs_url();
+
+        let fs = fs_registry
+            .get(&hdfs_server_url)
+            .expect("creation of registry");
+
+        let test_dir = format!("/{}", generate_unique_name());
+        fs.mkdir(&test_dir).expect("root dir created");
+
+        let list = fs.list_status(&test_dir).expect("empty directory listed");
+        assert!(list.is_empty());
+
+        let missing = format!("{}/{}", test_dir, generate_unique_name());
+        assert!(fs.list_status(&missing).is_err());
+
+        fs.delete(&test_dir, true).expect("to be deleted");
+    }
 }
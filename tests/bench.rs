@@ -0,0 +1,81 @@
+mod common;
+
+#[cfg(test)]
+mod e2e {
+
+    use std::process::{Command, Output};
+
+    use crate::common::*;
+    use log::info;
+
+    fn bench(args: &[&str]) -> Output {
+        let output = Command::new(env!("CARGO_BIN_EXE_hdfs-native-bench"))
+            .arg("-fs")
+            .arg(generate_hdfs_url())
+            .args(args)
+            .output()
+            .expect("benchmark executed");
+        info!("hdfs-native-bench {:?} -> {:?}", args, output.status.code());
+        output
+    }
+
+    #[test]
+    fn should_report_throughput_as_json() {
+        let test_dir = format!("/{}", generate_unique_name());
+        let output = bench(&[
+            "-write",
+            "-read",
+            "-pread",
+            "-list",
+            "-nrFiles",
+            "3",
+            "-fileSize",
+            "64KB",
+            "-bufferSize",
+            "16KB",
+            "-threads",
+            "2",
+            "-dir",
+            &test_dir,
+            "-json",
+            "-clean",
+        ]);
+        assert!(output.status.success());
+
+        let reports: serde_json::Value =
+            serde_json::from_slice(&output.stdout).expect("valid json");
+        let reports = reports.as_array().expect("array of reports");
+        let workloads: Vec<&str> = reports
+            .iter()
+            .map(|r| r["workload"].as_str().unwrap())
+            .collect();
+        assert_eq!(vec!["write", "read", "pread", "list"], workloads);
+
+        for report in &reports[..3] {
+            assert_eq!(3 * 64 * 1024, report["bytes"].as_u64().unwrap());
+            assert_eq!(0, report["errors"].as_u64().unwrap());
+            assert!(report["latency_ms"]["p99"].as_f64().unwrap() >= 0.0);
+        }
+        // four writes and close of each file
+        assert_eq!(15, reports[0]["operations"].as_u64().unwrap());
+        assert_eq!(3, reports[3]["operations"].as_u64().unwrap());
+
+        let fs = hdfs_native::HdfsRegistry::new()
+            .get(&generate_hdfs_url())
+            .expect("creation of registry");
+        assert!(!fs.exist(&test_dir));
+    }
+
+    #[test]
+    fn should_report_errors() {
+        let test_dir = format!("/{}", generate_unique_name());
+        let output = bench(&["-read", "-nrFiles", "2", "-dir", &test_dir]);
+        assert_eq!(Some(1), output.status.code());
+        let report = String::from_utf8_lossy(&output.stdout);
+        assert!(report.contains("----- hdfs-native-bench ----- : read"));
+        assert!(report.contains("Errors: 2"));
+
+        let output = bench(&["-threads", "0", "-write"]);
+        assert_eq!(Some(255), output.status.code());
+    }
+}
@@ -0,0 +1,76 @@
+mod common;
+
+#[cfg(test)]
+mod e2e {
+
+    use std::io::Write;
+
+    use crate::common::*;
+    use hdfs_native::HdfsRegistry;
+    use log::info;
+
+    #[test]
+    fn should_return_block_locations() {
+        let fs_registry = HdfsRegistry::new();
+        let hdfs_server_url = generate_hdfs_url();
+
+        info!("HDFS Name node to be used: [{}]", hdfs_server_url);
+
+        let fs = fs_registry
+            .get(&hdfs_server_url)
+            .expect("creation of registry");
+
+        let test_file = format!("/{}", generate_unique_name());
+        let mut f = fs.create(&test_file).expect("file created");
+        f.write_all(DATA.as_bytes()).expect("data written");
+        drop(f);
+
+        let locations = fs
+            .get_block_locations(&test_file, 0, DATA.len() as u64)
+            .expect("block locations");
+
+        assert_eq!(1, locations.len());
+        let location = &locations[0];
+        assert_eq!(0, location.offset);
+        assert_eq!(DATA.len() as u64, location.length);
+        assert!(!location.hosts.is_empty());
+        assert_eq!(location.hosts.len(), location.names.len());
+        assert!(!location.corrupt);
+
+        let hosts = fs
+            .get_hosts(&test_file, 0, DATA.len())
+            .expect("block hosts");
+        assert_eq!(1, hosts.len());
+        assert_eq!(location.hosts, hosts.get(0).expect("first block hosts"));
+        assert!(hosts.get(1).is_none());
+        for block in &hosts {
+            assert!(!block.is_empty());
+        }
+
+        assert!(fs.delete(&test_file, false).expect("file deleted"));
+    }
+
+    #[test]
+    fn should_handle_empty_and_missing_files() {
+        let fs_registry = HdfsRegistry::new();
+        let hdfs_server_url = generate_hdfs_url();
+
+        let fs = fs_registry
+            .get(&hdfs_server_url)
+            .expect("creation of registry");
+
+        let test_file = format!("/{}", generate_unique_name());
+        let f = fs.create(&test_file).expect("file created");
+        drop(f);
+
+        let locations = fs
+            .get_block_locations(&test_file, 0, 1024)
+            .expect("block locations");
+        assert!(locations.is_empty());
+
+        let missing = format!("/{}", generate_unique_name());
+        assert!(fs.get_block_locations(&missing, 0, 1024).is_err());
+
+        assert!(fs.delete(&test_file, false).expect("file deleted"));
+    }
+}
@@ -0,0 +1,229 @@
+mod common;
+
+#[cfg(test)]
+mod e2e {
+
+    use std::process::{Command, Output};
+
+    use crate::common::*;
+    use log::info;
+
+    fn hdfs(args: &[&str]) -> Output {
+        let output = Command::new(env!("CARGO_BIN_EXE_hdfs-native"))
+            .arg("-fs")
+            .arg(generate_hdfs_url())
+            .args(args)
+            .output()
+            .expect("command executed");
+        info!("hdfs-native {:?} -> {:?}", args, output.status.code());
+        output
+    }
+
+    fn stdout(output: &Output) -> String {
+        String::from_utf8_lossy(&output.stdout).to_string()
+    }
+
+    fn stderr(output: &Output) -> String {
+        String::from_utf8_lossy(&output.stderr).to_string()
+    }
+
+    #[test]
+    fn should_manage_files_like_hadoop_fs() {
+        let test_dir = format!("/{}", generate_unique_name());
+        let test_file = format!("{}/data.txt", test_dir);
+
+        let local = std::env::temp_dir().join(generate_unique_name());
+        std::fs::write(&local, DATA).expect("local file written");
+
+        assert!(hdfs(&["-mkdir", &format!("{}/a/b", test_dir)])
+            .status
+            .code()
+            .is_some_and(|c| c == 1));
+        assert!(hdfs(&["-mkdir", "-p", &format!("{}/a/b", test_dir)])
+            .status
+            .success());
+        assert!(hdfs(&["-put", local.to_str().unwrap(), &test_file])
+            .status
+            .success());
+
+        let output = hdfs(&["-put", local.to_str().unwrap(), &test_file]);
+        assert_eq!(Some(1), output.status.code());
+        assert!(stderr(&output).contains("File exists"));
+
+        let output = hdfs(&["-cat", &test_file]);
+        assert!(output.status.success());
+        assert_eq!(DATA, stdout(&output));
+
+        let output = hdfs(&["-ls", &test_dir]);
+        assert!(output.status.success());
+        let listing = stdout(&output);
+        let lines: Vec<&str> = listing.lines().collect();
+        assert_eq!("Found 2 items", lines[0]);
+        assert!(lines[1].starts_with("drwx"));
+        assert!(lines[1].ends_with(&format!("{}/a", test_dir)));
+        assert!(lines[2].starts_with("-rw"));
+        assert!(lines[2].ends_with(&test_file));
+
+        let output = hdfs(&["-ls", "-R", "-C", &test_dir]);
+        assert_eq!(
+            vec![
+                format!("{}/a", test_dir),
+                format!("{}/a/b", test_dir),
+                test_file.clone()
+            ],
+            stdout(&output).lines().collect::<Vec<_>>()
+        );
+
+        let output = hdfs(&["-stat", "%b %F %n", &test_file]);
+        assert_eq!(
+            format!("{} regular file data.txt\n", DATA.len()),
+            stdout(&output)
+        );
+
+        let output = hdfs(&["-du", "-s", &test_dir]);
+        assert!(stdout(&output).starts_with(&DATA.len().to_string()));
+
+        assert!(hdfs(&["-chmod", "go-r", &test_file]).status.success());
+        let output = hdfs(&["-stat", "%a", &test_file]);
+        assert_eq!("600\n", stdout(&output));
+
+        assert!(hdfs(&["-chmod", "-R", "g+w", &test_dir]).status.success());
+        let output = hdfs(&["-stat", "%a", &test_file]);
+        assert_eq!("620\n", stdout(&output));
+        let output = hdfs(&["-setrep", "2", &test_dir]);
+        assert!(output.status.success());
+        assert_eq!(
+            format!("Replication 2 set: {}\n", test_file),
+            stdout(&output)
+        );
+        let output = hdfs(&["-chown", "-R", "bob", &format!("{}/missing", test_dir)]);
+        assert_eq!(Some(1), output.status.code());
+        assert!(stderr(&output).contains("No such file or directory"));
+
+        assert!(hdfs(&["-test", "-e", &test_file]).status.success());
+        assert!(hdfs(&["-test", "-f", &test_file]).status.success());
+        assert_eq!(Some(1), hdfs(&["-test", "-d", &test_file]).status.code());
+
+        let empty = format!("{}/empty", test_dir);
+        assert!(hdfs(&["-touchz", &empty]).status.success());
+        assert!(hdfs(&["-test", "-z", &empty]).status.success());
+        let output = hdfs(&["-touchz", &test_file]);
+        assert_eq!(Some(1), output.status.code());
+        assert!(stderr(&output).contains("Not a zero-length file"));
+
+        let copy = format!("{}/copy.txt", test_dir);
+        assert!(hdfs(&["-cp", &test_file, &copy]).status.success());
+        let moved = format!("{}/a/moved.txt", test_dir);
+        assert!(hdfs(&["-mv", &copy, &moved]).status.success());
+        assert_eq!(DATA, stdout(&hdfs(&["-cat", &moved])));
+
+        let downloaded = std::env::temp_dir().join(generate_unique_name());
+        assert!(hdfs(&["-get", &moved, downloaded.to_str().unwrap()])
+            .status
+            .success());
+        assert_eq!(
+            DATA,
+            std::fs::read_to_string(&downloaded).expect("downloaded file read")
+        );
+
+        let output = hdfs(&["-rm", &test_dir]);
+        assert_eq!(Some(1), output.status.code());
+        assert!(stderr(&output).contains("Is a directory"));
+
+        let output = hdfs(&["-rm", "-r", "-skipTrash", &test_dir]);
+        assert!(output.status.success());
+        assert_eq!(format!("Deleted {}\n", test_dir), stdout(&output));
+
+        let output = hdfs(&["-ls", &test_dir]);
+        assert_eq!(Some(1), output.status.code());
+        assert_eq!(
+            format!("ls: `{}': No such file or directory\n", test_dir),
+            stderr(&output)
+        );
+        assert!(hdfs(&["-rm", "-f", &test_dir]).status.success());
+
+        std::fs::remove_file(&local).expect("local file removed");
+        std::fs::remove_file(&downloaded).expect("local file removed");
+    }
+
+    #[test]
+    fn should_report_usage_errors() {
+        let output = hdfs(&["-unknown"]);
+        assert_eq!(Some(255), output.status.code());
+        assert!(stderr(&output).contains("-unknown: Unknown command"));
+
+        let output = hdfs(&["-ls", "-Q", "/"]);
+        assert_eq!(Some(255), output.status.code());
+        assert!(stderr(&output).contains("-ls: Illegal option -Q"));
+
+        let output = hdfs(&["-chmod", "u+q", "/"]);
+        assert_eq!(Some(255), output.status.code());
+
+        let output = hdfs(&["-df"]);
+        assert!(output.status.success());
+        assert!(stdout(&output).starts_with("Filesystem"));
+    }
+
+    #[cfg(feature = "shell")]
+    #[test]
+    fn should_run_shell_commands() {
+        use std::io::Write;
+        use std::process::Stdio;
+
+        let test_dir = format!("/{}", generate_unique_name());
+        let script = format!(
+            "mkdir -p {dir}/a\n\
+             cd {dir}\n\
+             pwd\n\
+             touchz a/empty\n\
+             ls -C a\n\
+             cd missing\n\
+             -ls -C a | tr a-z A-Z\n\
+             ls missing | cat\n\
+             connect other {url}\n\
+             connections\n\
+             ls -C a | exit 3\n\
+             exit\n",
+            dir = test_dir,
+            url = generate_hdfs_url()
+        );
+
+        let home = std::env::temp_dir().join(generate_unique_name());
+        std::fs::create_dir(&home).expect("home created");
+        let mut child = Command::new(env!("CARGO_BIN_EXE_hdfs-native"))
+            .arg("-fs")
+            .arg(generate_hdfs_url())
+            .arg("-shell")
+            .env("HOME", &home)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .expect("shell started");
+        child
+            .stdin
+            .take()
+            .unwrap()
+            .write_all(script.as_bytes())
+            .expect("script written");
+        let output = child.wait_with_output().expect("shell finished");
+        // exit code of the last command
+        assert_eq!(Some(3), output.status.code());
+
+        let lines: Vec<String> = stdout(&output).lines().map(|l| l.to_string()).collect();
+        assert!(lines[0].ends_with(&test_dir));
+        assert_eq!("a/empty", lines[1]);
+        assert_eq!("A/EMPTY", lines[2]);
+        assert!(lines[3].starts_with("  default"));
+        assert!(lines[4].starts_with("* other"));
+        assert!(stderr(&output).contains("cd: `missing': No such file or directory"));
+        assert!(stderr(&output).contains("ls: `missing': No such file or directory"));
+        assert!(stderr(&output).contains("sh: `exit 3': exit status: 3"));
+        assert!(home.join(".hdfs_native_history").exists());
+
+        assert!(hdfs(&["-rm", "-r", "-skipTrash", &test_dir])
+            .status
+            .success());
+        std::fs::remove_dir_all(&home).expect("home removed");
+    }
+}
@@ -0,0 +1,76 @@
+mod common;
+
+#[cfg(test)]
+mod e2e {
+
+    use std::io::Write;
+
+    use crate::common::*;
+    use hdfs_native::{HdfsFs, HdfsRegistry};
+    use log::info;
+
+    fn create_file(fs: &HdfsFs, path: &str, len: usize) {
+        let mut f = fs.create(path).expect("file created");
+        let data: Vec<u8> = DATA.bytes().cycle().take(len).collect();
+        f.write_all(&data).expect("data written");
+    }
+
+    #[test]
+    fn should_summarise_directory_tree() {
+        let fs_registry = HdfsRegistry::new();
+        let hdfs_server_url = generate_hdfs_url();
+
+        info!("HDFS Name node to be used: [{}]", hdfs_server_url);
+
+        let fs = fs_registry
+            .get(&hdfs_server_url)
+            .expect("creation of registry");
+
+        let test_dir = format!("/{}", generate_unique_name());
+        fs.mkdir(&format!("{}/a/b", test_dir))
+            .expect("dirs created");
+        fs.mkdir(&format!("{}/empty", test_dir))
+            .expect("dir created");
+        create_file(&fs, &format!("{}/a/one", test_dir), 100);
+        create_file(&fs, &format!("{}/a/b/two", test_dir), 200);
+        create_file(&fs, &format!("{}/three", test_dir), 50);
+
+        let replication = fs
+            .get_file_status(&format!("{}/three", test_dir))
+            .expect("file status")
+            .replica_count() as u64;
+
+        let summary = fs.content_summary(&test_dir).expect("content summary");
+        assert_eq!(350, summary.length);
+        assert_eq!(3, summary.file_count);
+        assert_eq!(4, summary.directory_count);
+        assert_eq!(350 * replication, summary.space_consumed);
+
+        let summary = fs
+            .content_summary(&format!("{}/three", test_dir))
+            .expect("content summary");
+        assert_eq!(50, summary.length);
+        assert_eq!(1, summary.file_count);
+        assert_eq!(0, summary.directory_count);
+
+        let du = fs.du(&test_dir).expect("du");
+        let du: Vec<(String, u64, u64)> = du
+            .into_iter()
+            .map(|(path, s)| (path, s.length, s.file_count))
+            .collect();
+        assert_eq!(
+            vec![
+                (format!("{}/a", test_dir), 300, 2),
+                (format!("{}/empty", test_dir), 0, 0),
+                (format!("{}/three", test_dir), 50, 1),
+            ],
+            du
+        );
+
+        let missing = format!("/{}", generate_unique_name());
+        assert!(fs.content_summary(&missing).is_err());
+        assert!(fs.du(&missing).is_err());
+
+        assert!(fs.delete(&test_dir, true).expect("dir deleted"));
+    }
+}
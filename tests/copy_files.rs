@@ -4,12 +4,16 @@ mod common;
 
 mod copy {
 
+    use std::io::{Read, Write};
+
     use crate::common::*;
-    use hdfs_native::{util::HdfsUtil, HdfsRegistry};
+    use hdfs_native::{
+        util::{CopyOptions, HdfsUtil},
+        HdfsRegistry,
+    };
     use log::info;
 
     #[test]
-    #[ignore = "libhdfs3 does not support this operation"]
     fn should_copy_files() {
         let fs_registry = HdfsRegistry::new();
         let hdfs_server_url = generate_hdfs_url();
@@ -19,17 +23,79 @@ mod copy {
             .get(&hdfs_server_url)
             .expect("creation of registry");
 
-        assert!(fs.exist("/sbs_original.csv"));
-        assert!(!fs.exist("/sbs_copied.csv"));
+        let test_dir = format!("/{}", generate_unique_name());
+        let original = format!("{}/original.csv", test_dir);
+        let copied = format!("{}/copied.csv", test_dir);
+
+        fs.mkdir(&test_dir).expect("root dir created");
+        let mut f = fs.create(&original).expect("file open for writing");
+        f.write_all(DATA.as_bytes()).expect("data to be written");
+        drop(f);
+
+        assert!(fs.exist(&original));
+        assert!(!fs.exist(&copied));
+
+        let result = HdfsUtil::cp(&fs, &original, &fs, &copied).expect("file to be copied");
+
+        assert!(result);
+        assert!(fs.exist(&original));
+
+        let mut f = fs.open(&copied).expect("file open");
+        let mut content = String::new();
+        f.read_to_string(&mut content).expect("to be read");
+        assert_eq!(DATA, content);
+
+        HdfsUtil::cp(&fs, &original, &fs, &copied).expect_err("file not to be overwritten");
+
+        fs.delete(&test_dir, true).expect("directory to be deleted");
+    }
+
+    #[test]
+    fn should_copy_directories() {
+        let fs_registry = HdfsRegistry::new();
+        let hdfs_server_url = generate_hdfs_url();
 
-        let copied = HdfsUtil::cp(&fs, "/sbs_original.csv", &fs, "/sbs_copied.csv")
+        let fs = fs_registry
+            .get(&hdfs_server_url)
+            .expect("creation of registry");
+
+        let test_dir = format!("/{}", generate_unique_name());
+        let src_dir = format!("{}/src", test_dir);
+        let dst_dir = format!("{}/dst", test_dir);
+
+        fs.mkdir(&format!("{}/nested", src_dir))
+            .expect("src dir created");
+        fs.mkdir(&dst_dir).expect("dst dir created");
+        let mut f = fs
+            .create(&format!("{}/nested/file", src_dir))
+            .expect("file open for writing");
+        f.write_all(DATA.as_bytes()).expect("data to be written");
+        drop(f);
+        fs.chmod(&src_dir, 0o700);
+
+        let opts = CopyOptions {
+            recursive: true,
+            preserve_permission: true,
+            ..Default::default()
+        };
+        HdfsUtil::cp_with_options(&fs, &src_dir, &fs, &dst_dir, &opts)
+            .expect("directory to be copied");
+
+        // destination exists, source is copied into it
+        let copied = fs
+            .get_file_status(&format!("{}/src/nested/file", dst_dir))
             .expect("file to be copied");
+        assert_eq!(DATA.len(), copied.len());
+
+        let status = fs
+            .get_file_status(&format!("{}/src", dst_dir))
+            .expect("directory to be copied");
+        assert_eq!(0o700, status.permission());
 
-        assert!(copied)
+        fs.delete(&test_dir, true).expect("directory to be deleted");
     }
 
     #[test]
-    #[ignore = "libhdfs3 does not support this operation"]
     fn should_move_files() {
         let fs_registry = HdfsRegistry::new();
         let hdfs_server_url = generate_hdfs_url();
@@ -39,11 +105,24 @@ mod copy {
             .get(&hdfs_server_url)
             .expect("creation of registry");
 
-        assert!(fs.exist("/sbs_original.csv"));
-        assert!(!fs.exist("/sbs_moved.csv"));
+        let test_dir = format!("/{}", generate_unique_name());
+        let original = format!("{}/original.csv", test_dir);
+        let moved = format!("{}/moved.csv", test_dir);
+
+        fs.mkdir(&test_dir).expect("root dir created");
+        let mut f = fs.create(&original).expect("file open for writing");
+        f.write_all(DATA.as_bytes()).expect("data to be written");
+        drop(f);
+
+        assert!(fs.exist(&original));
+        assert!(!fs.exist(&moved));
+
+        let result = HdfsUtil::mv(&fs, &original, &fs, &moved).expect("file to be moved");
+
+        assert!(result);
+        assert!(!fs.exist(&original));
+        assert!(fs.exist(&moved));
 
-        let moved = HdfsUtil::mv(&fs, "/sbs_original.csv", &fs, "/sbs_moved.csv")
-            .expect("file to be moved");
-        assert!(moved)
+        fs.delete(&test_dir, true).expect("directory to be deleted");
     }
 }
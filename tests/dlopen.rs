@@ -0,0 +1,134 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+#[cfg(all(test, feature = "dlopen"))]
+mod e2e {
+
+    use std::env::consts::{DLL_PREFIX, DLL_SUFFIX};
+    use std::io::ErrorKind;
+    use std::path::PathBuf;
+    use std::process::Command;
+
+    use hdfs_native::dlopen::{is_loaded, set_library_path, LIBRARY_ENV};
+    use hdfs_native::{HdfsBuilder, HdfsFs, HdfsRegistry, HdfsUtil};
+
+    const STUB_URL: &str = "hdfs://stub:8020";
+
+    /// Builds tests/dlopen/libhdfs3_stub.rs as a shared library
+    fn build_stub() -> PathBuf {
+        let source = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/dlopen/libhdfs3_stub.rs");
+        let library = PathBuf::from(env!("CARGO_TARGET_TMPDIR"))
+            .join(format!("{}hdfs3_stub{}", DLL_PREFIX, DLL_SUFFIX));
+        let rustc = std::env::var("RUSTC").unwrap_or_else(|_| "rustc".to_string());
+
+        let status = Command::new(rustc)
+            .args(["--edition", "2021", "--crate-type", "cdylib"])
+            .args(["--crate-name", "hdfs3_stub", "-o"])
+            .arg(&library)
+            .arg(source)
+            .status()
+            .expect("rustc started");
+        assert!(status.success(), "stub library built");
+
+        library
+    }
+
+    /// Nothing may panic while the library can not be loaded
+    fn assert_not_loaded(kind: ErrorKind, messages: &[&str]) {
+        let err = HdfsRegistry::new().get(STUB_URL).unwrap_err();
+        assert_eq!(kind, err.kind());
+        for message in messages {
+            assert!(err.to_string().contains(message), "{} in {}", message, err);
+        }
+
+        let mut builder = HdfsBuilder::builder();
+        builder.set_name_node_port("stub", 8020);
+        assert_eq!(kind, builder.connect().unwrap_err().kind());
+        assert_eq!(
+            kind,
+            HdfsBuilder::connect_name_node("stub", 8020)
+                .unwrap_err()
+                .kind()
+        );
+
+        assert_eq!(None, HdfsUtil::get_conf_str("fs.defaultFS"));
+        assert_eq!(None, HdfsUtil::get_conf_int("dfs.replication"));
+        assert_eq!("", HdfsFs::get_last_error());
+        assert!(!is_loaded());
+    }
+
+    // loading is process wide, so steps run in a single test
+    #[test]
+    fn should_load_library_on_first_use() {
+        let stub = build_stub();
+
+        set_library_path(Some("/nonexistent/libhdfs3.so"));
+        assert_not_loaded(
+            ErrorKind::NotFound,
+            &["/nonexistent/libhdfs3.so", LIBRARY_ENV],
+        );
+
+        // a library which does not provide libhdfs3 functions
+        #[cfg(target_os = "linux")]
+        {
+            set_library_path(Some("libc.so.6"));
+            assert_not_loaded(ErrorKind::InvalidData, &["does not provide hdfs"]);
+        }
+
+        set_library_path(Some(stub.to_str().unwrap()));
+        assert_eq!(
+            Some(STUB_URL.to_string()),
+            HdfsUtil::get_conf_str("fs.defaultFS")
+        );
+        assert!(is_loaded());
+        assert_eq!(Some(2), HdfsUtil::get_conf_int("dfs.replication"));
+        assert_eq!(None, HdfsUtil::get_conf_int("dfs.blocksize"));
+
+        let fs = HdfsRegistry::new().get(STUB_URL).expect("stub connected");
+        assert_eq!(42, fs.default_block_size().unwrap());
+        assert_eq!(1234, fs.capacity().unwrap());
+        assert_eq!(56, fs.used().unwrap());
+        assert_eq!(2, fs.status().unwrap().default_replication);
+        // handle of the loaded library is exposed along with linked libhdfs3
+        #[cfg(feature = "libhdfs3")]
+        assert!(!fs.raw().is_null());
+
+        assert!(!fs.exist("/dlopen"));
+        assert!(fs.mkdir("/dlopen").expect("directory created"));
+        assert!(fs.exist("/dlopen"));
+        assert!(fs.delete("/dlopen", true).expect("directory deleted"));
+        assert!(!fs.exist("/dlopen"));
+
+        assert!(fs.open("/dlopen").is_err());
+        assert_eq!("stub: not implemented", HdfsFs::get_last_error());
+
+        // builder is dispatched to the stub as well
+        let mut builder = HdfsBuilder::builder();
+        builder.set_name_node("stub");
+        builder.set_name_port(8020);
+        assert_eq!(42, builder.connect().unwrap().default_block_size().unwrap());
+        let mut builder = HdfsBuilder::builder();
+        builder.set_name_node_port("unknown", 8020);
+        assert!(builder.connect().is_err());
+        assert!(HdfsBuilder::connect_name_node("stub", 8020).is_ok());
+        assert!(HdfsBuilder::connect_name_node_as_user("stub", 8020, "hdfs").is_ok());
+
+        // once loaded the path is not used anymore
+        set_library_path(Some("/nonexistent/libhdfs3.so"));
+        assert!(HdfsRegistry::new().get(STUB_URL).is_ok());
+    }
+}
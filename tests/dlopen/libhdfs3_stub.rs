@@ -0,0 +1,231 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Stub of libhdfs3 loaded by tests/dlopen.rs, built as a ```cdylib```
+//! with ```rustc``` so it has no dependencies.
+//!
+//! It exports every function the crate resolves. Directories are kept
+//! in memory, statistics and configuration return fixed values, the
+//! remaining functions fail.
+
+#![allow(non_snake_case, clippy::missing_safety_doc)]
+
+use std::ffi::{c_char, c_int, c_short, c_void, CStr, CString};
+use std::ptr::null_mut;
+use std::sync::Mutex;
+
+const DEFAULT_BLOCK_SIZE: i64 = 42;
+const CAPACITY: i64 = 1234;
+const USED: i64 = 56;
+
+/// Directories created with ```hdfsCreateDirectory```
+static DIRECTORIES: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+/// Handle of the only filesystem
+static FS: u8 = 0;
+
+struct Builder {
+    nn: String,
+    port: u16,
+}
+
+fn fs() -> *mut c_void {
+    &FS as *const u8 as *mut c_void
+}
+
+/// Only ```stub:8020``` name node is served, with or without scheme
+fn connect(nn: &str, port: u16) -> *mut c_void {
+    if nn.trim_start_matches("hdfs://") == "stub" && port == 8020 {
+        fs()
+    } else {
+        null_mut()
+    }
+}
+
+unsafe fn string(value: *const c_char) -> String {
+    CStr::from_ptr(value).to_string_lossy().into_owned()
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn hdfsNewBuilder() -> *mut c_void {
+    Box::into_raw(Box::new(Builder {
+        nn: String::new(),
+        port: 0,
+    })) as *mut c_void
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn hdfsBuilderSetNameNode(bld: *mut c_void, nn: *const c_char) {
+    (*(bld as *mut Builder)).nn = string(nn);
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn hdfsBuilderSetNameNodePort(bld: *mut c_void, port: u16) {
+    (*(bld as *mut Builder)).port = port;
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn hdfsBuilderConnect(bld: *mut c_void) -> *mut c_void {
+    let builder = &*(bld as *mut Builder);
+    connect(&builder.nn, builder.port)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn hdfsFreeBuilder(bld: *mut c_void) {
+    drop(Box::from_raw(bld as *mut Builder));
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn hdfsConnectNewInstance(nn: *const c_char, port: u16) -> *mut c_void {
+    connect(&string(nn), port)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn hdfsConnectAsUserNewInstance(
+    nn: *const c_char,
+    port: u16,
+    _user: *const c_char,
+) -> *mut c_void {
+    hdfsConnectNewInstance(nn, port)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn hdfsDisconnect(_fs: *mut c_void) -> c_int {
+    0
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn hdfsGetLastError() -> *const c_char {
+    b"stub: not implemented\0".as_ptr() as *const c_char
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn hdfsConfGetInt(key: *const c_char, val: *mut i32) -> c_int {
+    match string(key).as_str() {
+        "dfs.replication" => {
+            *val = 2;
+            0
+        }
+        _ => -1,
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn hdfsConfGetStr(key: *const c_char, val: *mut *mut c_char) -> c_int {
+    match string(key).as_str() {
+        "fs.defaultFS" => {
+            *val = CString::new("hdfs://stub:8020").unwrap().into_raw();
+            0
+        }
+        _ => -1,
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn hdfsConfStrFree(val: *mut c_char) {
+    drop(CString::from_raw(val));
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn hdfsCreateDirectory(_fs: *mut c_void, path: *const c_char) -> c_int {
+    DIRECTORIES.lock().unwrap().push(string(path));
+    0
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn hdfsExists(_fs: *mut c_void, path: *const c_char) -> c_int {
+    let path = string(path);
+    if DIRECTORIES.lock().unwrap().contains(&path) {
+        0
+    } else {
+        -1
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn hdfsDelete(
+    _fs: *mut c_void,
+    path: *const c_char,
+    _recursive: c_int,
+) -> c_int {
+    let path = string(path);
+    let mut directories = DIRECTORIES.lock().unwrap();
+    let len = directories.len();
+    directories.retain(|directory| directory != &path);
+    if directories.len() < len {
+        0
+    } else {
+        -1
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn hdfsGetDefaultBlockSize(_fs: *mut c_void) -> i64 {
+    DEFAULT_BLOCK_SIZE
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn hdfsGetCapacity(_fs: *mut c_void) -> i64 {
+    CAPACITY
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn hdfsGetUsed(_fs: *mut c_void) -> i64 {
+    USED
+}
+
+/// Functions the tests do not call, all of them fail
+macro_rules! failing {
+    ($(fn $name:ident($($ty:ty),*) $(-> $ret:ty)? = $value:expr;)*) => {
+        $(
+            #[no_mangle]
+            pub unsafe extern "C" fn $name($(_: $ty),*) $(-> $ret)? {
+                $value
+            }
+        )*
+    };
+}
+
+failing! {
+    fn hdfsAvailable(*mut c_void, *mut c_void) -> c_int = -1;
+    fn hdfsChmod(*mut c_void, *const c_char, c_short) -> c_int = -1;
+    fn hdfsChown(*mut c_void, *const c_char, *const c_char, *const c_char) -> c_int = -1;
+    fn hdfsCloseFile(*mut c_void, *mut c_void) -> c_int = -1;
+    fn hdfsFileIsOpenForRead(*mut c_void) -> c_int = 0;
+    fn hdfsFileIsOpenForWrite(*mut c_void) -> c_int = 0;
+    fn hdfsFlush(*mut c_void, *mut c_void) -> c_int = -1;
+    fn hdfsFreeFileBlockLocations(*mut c_void, c_int) = ();
+    fn hdfsFreeFileInfo(*mut c_void, c_int) = ();
+    fn hdfsFreeHosts(*mut *mut *mut c_char) = ();
+    fn hdfsGetFileBlockLocations(*mut c_void, *const c_char, i64, i64, *mut c_int)
+        -> *mut c_void = null_mut();
+    fn hdfsGetHosts(*mut c_void, *const c_char, i64, i64) -> *mut *mut *mut c_char = null_mut();
+    fn hdfsGetPathInfo(*mut c_void, *const c_char) -> *mut c_void = null_mut();
+    fn hdfsGetWorkingDirectory(*mut c_void, *mut c_char, usize) -> *mut c_char = null_mut();
+    fn hdfsHFlush(*mut c_void, *mut c_void) -> c_int = -1;
+    fn hdfsListDirectory(*mut c_void, *const c_char, *mut c_int) -> *mut c_void = null_mut();
+    fn hdfsOpenFile(*mut c_void, *const c_char, c_int, c_int, c_short, i64)
+        -> *mut c_void = null_mut();
+    fn hdfsRead(*mut c_void, *mut c_void, *mut c_void, i32) -> i32 = -1;
+    fn hdfsRename(*mut c_void, *const c_char, *const c_char) -> c_int = -1;
+    fn hdfsSeek(*mut c_void, *mut c_void, i64) -> c_int = -1;
+    fn hdfsSetReplication(*mut c_void, *const c_char, i16) -> c_int = -1;
+    fn hdfsSync(*mut c_void, *mut c_void) -> c_int = -1;
+    fn hdfsTell(*mut c_void, *mut c_void) -> i64 = -1;
+    fn hdfsUtime(*mut c_void, *const c_char, i64, i64) -> c_int = -1;
+    fn hdfsWrite(*mut c_void, *mut c_void, *const c_void, i32) -> i32 = -1;
+}
@@ -0,0 +1,213 @@
+mod common;
+
+#[cfg(test)]
+mod e2e {
+
+    use std::io::Write;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    use crate::common::*;
+    use hdfs_native::{FollowEvent, FollowFrom, FollowOptions, HdfsRegistry};
+    use log::info;
+
+    fn options(from: FollowFrom) -> FollowOptions {
+        FollowOptions {
+            from,
+            poll_interval: Duration::from_millis(10),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn should_follow_appended_data() {
+        let fs_registry = HdfsRegistry::new();
+        let hdfs_server_url = generate_hdfs_url();
+
+        info!("HDFS Name node to be used: [{}]", hdfs_server_url);
+
+        let fs = fs_registry
+            .get(&hdfs_server_url)
+            .expect("creation of registry");
+
+        let test_file = format!("/{}", generate_unique_name());
+        let mut f = fs.create(&test_file).expect("file created");
+        f.write_all(DATA.as_bytes()).expect("data written");
+        drop(f);
+
+        let mut follower = fs
+            .follow(&test_file, &options(FollowFrom::End))
+            .expect("follower created");
+        assert_eq!(DATA.len() as u64, follower.offset());
+        assert_eq!(None, follower.poll().expect("polled"));
+
+        let mut f = fs.append(&test_file).expect("file opened for append");
+        f.write_all(b"appended").expect("data written");
+        assert!(f.hflush());
+        drop(f);
+
+        assert_eq!(
+            Some(FollowEvent::Data(b"appended".to_vec())),
+            follower.poll().expect("polled")
+        );
+        assert_eq!(None, follower.poll().expect("polled"));
+
+        let follower = fs
+            .follow(&test_file, &options(FollowFrom::LastBytes(8)))
+            .expect("follower created");
+        assert_eq!(
+            FollowEvent::Data(b"appended".to_vec()),
+            follower.into_iter().next().unwrap().expect("event")
+        );
+
+        assert!(fs.delete(&test_file, false).expect("file deleted"));
+    }
+
+    #[test]
+    fn should_detect_truncation_and_deletion() {
+        let fs_registry = HdfsRegistry::new();
+        let hdfs_server_url = generate_hdfs_url();
+
+        let fs = fs_registry
+            .get(&hdfs_server_url)
+            .expect("creation of registry");
+
+        let test_file = format!("/{}", generate_unique_name());
+        let mut f = fs.create(&test_file).expect("file created");
+        f.write_all(DATA.as_bytes()).expect("data written");
+        drop(f);
+
+        let mut follower = fs
+            .follow(&test_file, &options(FollowFrom::Start))
+            .expect("follower created");
+        assert_eq!(
+            Some(FollowEvent::Data(DATA.as_bytes().to_vec())),
+            follower.poll().expect("polled")
+        );
+
+        // rotated, new file is shorter than data already seen
+        let mut f = fs
+            .create_with_overwrite(&test_file, true)
+            .expect("file created");
+        f.write_all(b"new").expect("data written");
+        drop(f);
+
+        assert_eq!(
+            Some(FollowEvent::Truncated),
+            follower.poll().expect("polled")
+        );
+        assert_eq!(
+            Some(FollowEvent::Data(b"new".to_vec())),
+            follower.poll().expect("polled")
+        );
+
+        assert!(fs.delete(&test_file, false).expect("file deleted"));
+        assert_eq!(Some(FollowEvent::Deleted), follower.poll().expect("polled"));
+        assert_eq!(None, follower.poll().expect("polled"));
+
+        let mut f = fs.create(&test_file).expect("file created");
+        f.write_all(b"recreated").expect("data written");
+        drop(f);
+
+        assert_eq!(
+            Some(FollowEvent::Recreated),
+            follower.poll().expect("polled")
+        );
+        assert_eq!(
+            Some(FollowEvent::Data(b"recreated".to_vec())),
+            follower.poll().expect("polled")
+        );
+
+        // replaced by an older file, which is longer than data already seen
+        let older = format!("/{}", generate_unique_name());
+        let mut f = fs.create(&older).expect("file created");
+        f.write_all(b"older and longer").expect("data written");
+        f.close().expect("file closed");
+        let past = UNIX_EPOCH + Duration::from_secs(1_000_000);
+        fs.set_times(&older, Some(past), None).expect("times set");
+        assert!(fs.delete(&test_file, false).expect("file deleted"));
+        assert!(fs.rename(&older, &test_file).expect("file renamed"));
+
+        assert_eq!(
+            Some(FollowEvent::Recreated),
+            follower.poll().expect("polled")
+        );
+        assert_eq!(
+            Some(FollowEvent::Data(b"older and longer".to_vec())),
+            follower.poll().expect("polled")
+        );
+        assert_eq!(None, follower.poll().expect("polled"));
+
+        let missing = format!("/{}", generate_unique_name());
+        assert!(fs.follow(&missing, &options(FollowFrom::End)).is_err());
+
+        assert!(fs.delete(&test_file, false).expect("file deleted"));
+    }
+
+    #[test]
+    fn should_follow_lines() {
+        let fs_registry = HdfsRegistry::new();
+        let hdfs_server_url = generate_hdfs_url();
+
+        let fs = fs_registry
+            .get(&hdfs_server_url)
+            .expect("creation of registry");
+
+        let test_file = format!("/{}", generate_unique_name());
+        let mut f = fs.create(&test_file).expect("file created");
+        f.write_all(b"first\nsecond\r\npartial")
+            .expect("data written");
+        drop(f);
+
+        let mut lines = fs
+            .follow(&test_file, &options(FollowFrom::Start))
+            .expect("follower created")
+            .lines();
+        assert_eq!("first", lines.next().unwrap().expect("line"));
+        assert_eq!("second", lines.next().unwrap().expect("line"));
+
+        let mut f = fs.append(&test_file).expect("file opened for append");
+        f.write_all(b" line\n").expect("data written");
+        drop(f);
+
+        assert_eq!("partial line", lines.next().unwrap().expect("line"));
+
+        let directory = format!("/{}", generate_unique_name());
+        fs.mkdir(&directory).expect("dir created");
+        assert!(fs.follow(&directory, &options(FollowFrom::End)).is_err());
+
+        assert!(fs.delete(&test_file, false).expect("file deleted"));
+        assert!(fs.delete(&directory, true).expect("dir deleted"));
+    }
+
+    #[test]
+    fn should_limit_data_per_event() {
+        let fs_registry = HdfsRegistry::new();
+        let hdfs_server_url = generate_hdfs_url();
+
+        let fs = fs_registry
+            .get(&hdfs_server_url)
+            .expect("creation of registry");
+
+        let test_file = format!("/{}", generate_unique_name());
+        let mut f = fs.create(&test_file).expect("file created");
+        f.write_all(DATA.as_bytes()).expect("data written");
+        drop(f);
+
+        let opts = FollowOptions {
+            buf_size: 4,
+            ..options(FollowFrom::Start)
+        };
+        let mut follower = fs.follow(&test_file, &opts).expect("follower created");
+
+        for chunk in DATA.as_bytes().chunks(4) {
+            assert_eq!(
+                Some(FollowEvent::Data(chunk.to_vec())),
+                follower.poll().expect("polled")
+            );
+        }
+        assert_eq!(DATA.len() as u64, follower.offset());
+        assert_eq!(None, follower.poll().expect("polled"));
+
+        assert!(fs.delete(&test_file, false).expect("file deleted"));
+    }
+}
@@ -0,0 +1,56 @@
+mod common;
+
+#[cfg(test)]
+mod e2e {
+
+    use crate::common::*;
+    use hdfs_native::{FsStatus, HdfsRegistry, UsageLevel, UsageThresholds};
+    use log::info;
+
+    #[test]
+    fn should_return_fs_status() {
+        let fs_registry = HdfsRegistry::new();
+        let hdfs_server_url = generate_hdfs_url();
+
+        info!("HDFS Name node to be used: [{}]", hdfs_server_url);
+
+        let fs = fs_registry
+            .get(&hdfs_server_url)
+            .expect("creation of registry");
+
+        let status = fs.status().expect("fs status");
+        assert!(status.capacity > 0);
+        assert_eq!(status.capacity - status.used, status.remaining);
+        assert_eq!(
+            fs.default_block_size().expect("block size") as u64,
+            status.default_block_size
+        );
+        assert!(status.default_replication > 0);
+    }
+
+    #[test]
+    fn should_check_usage_thresholds() {
+        let status = |used: u64| FsStatus {
+            capacity: 1000,
+            used,
+            remaining: 1000 - used,
+            default_block_size: 128,
+            default_replication: 3,
+        };
+        let thresholds = UsageThresholds::default();
+
+        assert_eq!(UsageLevel::Ok, status(500).check(&thresholds));
+        assert_eq!(UsageLevel::Warning, status(800).check(&thresholds));
+        assert_eq!(UsageLevel::Critical, status(950).check(&thresholds));
+        assert!(status(0).check(&thresholds).is_ok());
+
+        let thresholds = UsageThresholds {
+            warning_remaining: Some(600),
+            critical_remaining: Some(300),
+            ..Default::default()
+        };
+        assert_eq!(UsageLevel::Warning, status(500).check(&thresholds));
+        assert_eq!(UsageLevel::Critical, status(750).check(&thresholds));
+        assert!(UsageLevel::Critical > UsageLevel::Warning);
+    }
+}
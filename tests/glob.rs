@@ -0,0 +1,88 @@
+mod common;
+
+#[cfg(test)]
+mod e2e {
+
+    use crate::common::*;
+    use hdfs_native::{glob::GlobPattern, HdfsRegistry};
+    use log::info;
+
+    #[test]
+    fn should_match_glob_patterns() {
+        let matches = |pattern: &str, name: &str| {
+            GlobPattern::new(pattern)
+                .expect("valid pattern")
+                .matches(name)
+        };
+
+        assert!(matches("part-*.parquet", "part-0001.parquet"));
+        assert!(!matches("part-*.parquet", "part-0001.csv"));
+        assert!(matches("a?c", "abc"));
+        assert!(!matches("a?c", "ac"));
+        assert!(matches("2023-0[1-6]-*", "2023-03-01"));
+        assert!(!matches("2023-0[1-6]-*", "2023-07-01"));
+        assert!(matches("[^a-c]x", "dx"));
+        assert!(!matches("[^a-c]x", "bx"));
+        assert!(matches("{events,clicks}", "clicks"));
+        assert!(!matches("{events,clicks}", "views"));
+        assert!(matches("{a{b,c},d}e", "ace"));
+        assert!(matches("\\*", "*"));
+        assert!(!matches("\\*", "a"));
+
+        assert!(!GlobPattern::new("literal").unwrap().has_wildcard());
+        assert!(GlobPattern::new("[a-").is_err());
+        assert!(GlobPattern::new("{a,b").is_err());
+    }
+
+    #[test]
+    fn should_expand_globs() {
+        let fs_registry = HdfsRegistry::new();
+        let hdfs_server_url = generate_hdfs_url();
+
+        info!("HDFS Name node to be used: [{}]", hdfs_server_url);
+
+        let fs = fs_registry
+            .get(&hdfs_server_url)
+            .expect("creation of registry");
+
+        let test_dir = format!("/{}", generate_unique_name());
+        for dir in ["2023-01-01", "2023-05-02", "2023-08-01"] {
+            for kind in ["events", "clicks", "views"] {
+                fs.mkdir(&format!("{}/{}/{}", test_dir, dir, kind))
+                    .expect("dir created");
+                fs.create(&format!("{}/{}/{}/part-0.parquet", test_dir, dir, kind))
+                    .expect("file created");
+            }
+        }
+
+        let result = fs
+            .glob(&format!(
+                "{}/2023-0[1-6]-*/{{events,clicks}}/part-*.parquet",
+                test_dir
+            ))
+            .expect("glob to be expanded");
+        assert_eq!(4, result.len());
+        assert!(result.iter().all(|s| s.is_file()));
+
+        let names: Vec<&str> = result.iter().map(|s| s.name()).collect();
+        let mut sorted = names.clone();
+        sorted.sort();
+        assert_eq!(sorted, names);
+
+        let result = fs
+            .glob(&format!("{}/{{2023-01-01/events,2023-08-01}}", test_dir))
+            .expect("glob to be expanded");
+        assert_eq!(2, result.len());
+
+        let result = fs
+            .glob(&format!("{}/2024-*", test_dir))
+            .expect("glob to be expanded");
+        assert!(result.is_empty());
+
+        let result = fs.glob(&test_dir).expect("glob to be expanded");
+        assert_eq!(1, result.len());
+        assert!(result[0].is_directory());
+
+        fs.delete(&test_dir, true).expect("directory to be deleted");
+    }
+}
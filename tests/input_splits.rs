@@ -0,0 +1,134 @@
+mod common;
+
+#[cfg(test)]
+mod e2e {
+
+    use std::io::Write;
+
+    use crate::common::*;
+    use hdfs_native::{HdfsFs, HdfsRegistry, InputSplit, InputSplits};
+    use log::info;
+
+    fn create_file(fs: &HdfsFs, path: &str, len: usize) {
+        let mut f = fs.create(path).expect("file created");
+        let data: Vec<u8> = DATA.bytes().cycle().take(len).collect();
+        f.write_all(&data).expect("data written");
+    }
+
+    fn split(path: &str, length: u64, hosts: &[&str]) -> InputSplit {
+        InputSplit {
+            path: path.to_string(),
+            start: 0,
+            length,
+            hosts: hosts.iter().map(|h| h.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn should_split_files() {
+        let fs_registry = HdfsRegistry::new();
+        let hdfs_server_url = generate_hdfs_url();
+
+        info!("HDFS Name node to be used: [{}]", hdfs_server_url);
+
+        let fs = fs_registry
+            .get(&hdfs_server_url)
+            .expect("creation of registry");
+
+        let test_dir = format!("/{}", generate_unique_name());
+        fs.mkdir(&test_dir).expect("dir created");
+        create_file(&fs, &format!("{}/part-0", test_dir), 1000);
+        create_file(&fs, &format!("{}/part-1", test_dir), 320);
+        create_file(&fs, &format!("{}/part-2", test_dir), 0);
+        create_file(&fs, &format!("{}/_SUCCESS", test_dir), 10);
+
+        let splits = fs
+            .input_splits()
+            .split_size(300)
+            .plan(&[&test_dir])
+            .expect("splits planned");
+
+        let mut splits: Vec<(String, u64, u64)> = splits
+            .into_iter()
+            .inspect(|s| assert!(s.length == 0 || !s.hosts.is_empty()))
+            .map(|s| {
+                (
+                    s.path[s.path.rfind('/').unwrap() + 1..].to_string(),
+                    s.start,
+                    s.length,
+                )
+            })
+            .collect();
+        splits.sort();
+
+        assert_eq!(
+            vec![
+                ("part-0".to_string(), 0, 300),
+                ("part-0".to_string(), 300, 300),
+                ("part-0".to_string(), 600, 300),
+                ("part-0".to_string(), 900, 100),
+                ("part-1".to_string(), 0, 320),
+                ("part-2".to_string(), 0, 0),
+            ],
+            splits
+        );
+
+        let splits = fs
+            .input_splits()
+            .split_size(100)
+            .min_size(500)
+            .plan(&[&format!("{}/part-0", test_dir)])
+            .expect("splits planned");
+        assert_eq!(2, splits.len());
+
+        let splits = fs
+            .input_splits()
+            .max_size(250)
+            .plan(&[&format!("{}/part-[0-1]", test_dir)])
+            .expect("splits planned");
+        assert_eq!(6, splits.len());
+        assert!(splits.iter().all(|s| s.length <= 250));
+
+        let err = fs
+            .input_splits()
+            .split_size(0)
+            .plan(&[&format!("{}/part-0", test_dir)])
+            .unwrap_err();
+        assert_eq!(std::io::ErrorKind::InvalidInput, err.kind());
+
+        assert!(fs
+            .input_splits()
+            .plan(&[&format!("{}/missing-*", test_dir)])
+            .is_err());
+        assert!(fs
+            .input_splits()
+            .plan(&[&format!("{}/missing", test_dir)])
+            .is_err());
+
+        assert!(fs.delete(&test_dir, true).expect("dir deleted"));
+    }
+
+    #[test]
+    fn should_assign_splits_to_local_workers() {
+        let splits = vec![
+            split("/a", 100, &["host1", "host2"]),
+            split("/b", 100, &["host2", "host3"]),
+            split("/c", 100, &["host3", "host1"]),
+            split("/d", 100, &["host1", "host2"]),
+            split("/e", 100, &["remote"]),
+        ];
+
+        let assignments = InputSplits::assign(splits, &["host1", "host2", "host3"]);
+
+        assert_eq!(3, assignments.len());
+        assert_eq!("host1", assignments[0].worker);
+
+        let total: u64 = assignments.iter().map(|a| a.bytes()).sum();
+        let local: u64 = assignments.iter().map(|a| a.local_bytes()).sum();
+        assert_eq!(500, total);
+        assert_eq!(400, local);
+        assert!(assignments.iter().all(|a| a.bytes() <= 200));
+
+        assert!(InputSplits::assign(vec![split("/a", 1, &[])], &[]).is_empty());
+    }
+}
@@ -0,0 +1,81 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+mod common;
+
+#[cfg(test)]
+mod e2e {
+
+    use crate::common::*;
+    use hdfs_native::HdfsRegistry;
+    use log::info;
+
+    #[test]
+    fn should_list_lazily_in_batches() {
+        let fs_registry = HdfsRegistry::new();
+        let hdfs_server_url = generate_hdfs_url();
+
+        info!("HDFS Name node to be used: [{}]", hdfs_server_url);
+
+        let fs = fs_registry
+            .get(&hdfs_server_url)
+            .expect("creation of registry");
+
+        let test_dir = format!("/{}", generate_unique_name());
+        for idx in 0..5 {
+            assert!(fs.mkdir(&format!("{}/dir{}", test_dir, idx)).unwrap());
+        }
+
+        let mut names: Vec<String> = fs
+            .list_status_iter(&test_dir)
+            .map(|status| status.expect("listed").name().to_string())
+            .collect();
+        names.sort();
+        let mut expected: Vec<String> = fs
+            .list_status(&test_dir)
+            .expect("listed")
+            .iter()
+            .map(|status| status.name().to_string())
+            .collect();
+        expected.sort();
+        assert_eq!(5, names.len());
+        assert_eq!(expected, names);
+
+        let sizes: Vec<usize> = fs
+            .list_status_iter(&test_dir)
+            .batches(2)
+            .map(|batch| batch.expect("listed").len())
+            .collect();
+        assert_eq!(vec![2, 2, 1], sizes);
+
+        let mut iter = fs.list_status_iter(&test_dir);
+        assert_eq!(test_dir, iter.path());
+        assert!(iter.next().unwrap().is_ok());
+        assert_eq!((4, Some(4)), iter.size_hint());
+
+        assert!(fs.mkdir(&format!("{}/empty", test_dir)).unwrap());
+        let empty = format!("{}/empty", test_dir);
+        assert!(fs.list_status_iter(&empty).next().is_none());
+        assert!(fs.list_status_iter(&empty).batches(2).next().is_none());
+
+        let missing = format!("{}/missing", test_dir);
+        let mut iter = fs.list_status_iter(&missing);
+        assert!(iter.next().unwrap().is_err());
+        assert!(iter.next().is_none());
+
+        assert!(fs.delete(&test_dir, true).expect("directory deleted"));
+    }
+}
@@ -0,0 +1,140 @@
+mod common;
+
+#[cfg(test)]
+mod e2e {
+
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    use crate::common::*;
+    use hdfs_native::{
+        util::{ExistsPolicy, HdfsUtil, NoProgress, Progress, TransferOptions},
+        HdfsRegistry,
+    };
+    use log::info;
+
+    #[derive(Default)]
+    struct CountingProgress {
+        bytes: AtomicU64,
+        files: AtomicU64,
+    }
+
+    impl Progress for CountingProgress {
+        fn bytes_transferred(&self, _path: &str, bytes: u64) {
+            self.bytes.fetch_add(bytes, Ordering::SeqCst);
+        }
+
+        fn file_transferred(&self, _path: &str, _len: u64) {
+            self.files.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    struct CancelledProgress;
+
+    impl Progress for CancelledProgress {
+        fn is_cancelled(&self) -> bool {
+            true
+        }
+    }
+
+    #[test]
+    fn should_copy_directory_from_and_to_local() {
+        let fs_registry = HdfsRegistry::new();
+        let hdfs_server_url = generate_hdfs_url();
+
+        info!("HDFS Name node to be used: [{}]", hdfs_server_url);
+
+        let fs = fs_registry
+            .get(&hdfs_server_url)
+            .expect("creation of registry");
+
+        let local_src = std::env::temp_dir().join(generate_unique_name());
+        std::fs::create_dir_all(local_src.join("nested")).expect("local dir created");
+        std::fs::write(local_src.join("a.txt"), DATA).expect("local file written");
+        std::fs::write(local_src.join("nested").join("b.txt"), DATA).expect("local file written");
+
+        let test_dir = format!("/{}", generate_unique_name());
+        let progress = CountingProgress::default();
+        let stats = HdfsUtil::copy_from_local(
+            &local_src,
+            &fs,
+            &test_dir,
+            &TransferOptions::default(),
+            &progress,
+        )
+        .expect("directory to be uploaded");
+
+        assert_eq!(2, stats.files);
+        assert_eq!(2 * DATA.len() as u64, stats.bytes);
+        assert_eq!(2, progress.files.load(Ordering::SeqCst));
+        assert_eq!(stats.bytes, progress.bytes.load(Ordering::SeqCst));
+        assert!(fs.exist(&format!("{}/nested/b.txt", test_dir)));
+
+        let opts = TransferOptions {
+            exists: ExistsPolicy::Skip,
+            ..Default::default()
+        };
+        let stats = HdfsUtil::copy_from_local(&local_src, &fs, &test_dir, &opts, &NoProgress)
+            .expect("upload to be skipped");
+        // destination is a directory now, source is placed into it
+        assert_eq!(2, stats.files);
+
+        let stats = HdfsUtil::copy_from_local(
+            local_src.join("a.txt"),
+            &fs,
+            &format!("{}/a.txt", test_dir),
+            &opts,
+            &NoProgress,
+        )
+        .expect("upload to be skipped");
+        assert_eq!(1, stats.skipped);
+
+        let local_dst = std::env::temp_dir().join(generate_unique_name());
+        let stats = HdfsUtil::copy_to_local(
+            &fs,
+            &test_dir,
+            &local_dst,
+            &TransferOptions::default(),
+            &NoProgress,
+        )
+        .expect("directory to be downloaded");
+
+        assert_eq!(4, stats.files);
+        let content = std::fs::read_to_string(local_dst.join("nested").join("b.txt"))
+            .expect("local file to be read");
+        assert_eq!(DATA, content);
+
+        std::fs::remove_dir_all(&local_src).expect("local dir to be deleted");
+        std::fs::remove_dir_all(&local_dst).expect("local dir to be deleted");
+        fs.delete(&test_dir, true).expect("directory to be deleted");
+    }
+
+    #[test]
+    fn should_cancel_transfer() {
+        let fs_registry = HdfsRegistry::new();
+        let hdfs_server_url = generate_hdfs_url();
+
+        let fs = fs_registry
+            .get(&hdfs_server_url)
+            .expect("creation of registry");
+
+        let local_src = std::env::temp_dir().join(generate_unique_name());
+        std::fs::write(&local_src, DATA).expect("local file written");
+
+        let test_file = format!("/{}", generate_unique_name());
+        let result = HdfsUtil::copy_from_local(
+            &local_src,
+            &fs,
+            &test_file,
+            &TransferOptions::default(),
+            &CancelledProgress,
+        );
+
+        assert_eq!(
+            std::io::ErrorKind::Interrupted,
+            result.expect_err("transfer to be cancelled").kind()
+        );
+        assert!(!fs.exist(&test_file));
+
+        std::fs::remove_file(&local_src).expect("local file to be deleted");
+    }
+}
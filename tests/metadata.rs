@@ -0,0 +1,55 @@
+mod common;
+
+#[cfg(test)]
+mod e2e {
+
+    use std::io::Write;
+
+    use crate::common::*;
+    use hdfs_native::{FileKind, FileMetadata, HdfsRegistry};
+    use log::info;
+
+    #[test]
+    fn should_create_owned_metadata() {
+        let fs_registry = HdfsRegistry::new();
+        let hdfs_server_url = generate_hdfs_url();
+
+        info!("HDFS Name node to be used: [{}]", hdfs_server_url);
+
+        let fs = fs_registry
+            .get(&hdfs_server_url)
+            .expect("creation of registry");
+
+        let test_file = format!("/{}", generate_unique_name());
+        let mut f = fs.create(&test_file).expect("file created");
+        f.write_all(DATA.as_bytes()).expect("data written");
+        drop(f);
+        fs.chmod(&test_file, 0o640);
+
+        let metadata = {
+            let status = fs.get_file_status(&test_file).expect("file status");
+            FileMetadata::from(&status)
+        };
+
+        assert!(metadata.path.ends_with(&test_file));
+        assert_eq!(FileKind::File, metadata.kind);
+        assert_eq!(DATA.len() as u64, metadata.len);
+        assert_eq!(0o640, metadata.permission.mode());
+        assert!(metadata.modified > std::time::UNIX_EPOCH);
+
+        let cloned = metadata.clone();
+        assert_eq!(metadata, cloned);
+
+        let status = fs.get_file_status(&test_file).expect("file status");
+        assert_eq!(metadata, status.to_metadata());
+
+        #[cfg(feature = "serde")]
+        {
+            let json = serde_json::to_string(&metadata).expect("serialized");
+            let deserialized: FileMetadata = serde_json::from_str(&json).expect("deserialized");
+            assert_eq!(metadata, deserialized);
+        }
+
+        fs.delete(&test_file, false).expect("file to be deleted");
+    }
+}
@@ -0,0 +1,250 @@
+mod common;
+
+#[cfg(all(test, feature = "object_store"))]
+mod e2e {
+
+    use std::io::Write;
+
+    use crate::common::*;
+    use futures::TryStreamExt;
+    use hdfs_native::{HdfsObjectStore, HdfsRegistry};
+    use log::info;
+    use object_store::path::Path;
+    use object_store::{GetOptions, GetRange, ObjectStore, PutMode, PutPayload};
+
+    fn store(root: &str) -> HdfsObjectStore {
+        let hdfs_server_url = generate_hdfs_url();
+        info!("HDFS Name node to be used: [{}]", hdfs_server_url);
+
+        let fs = HdfsRegistry::new()
+            .get(&hdfs_server_url)
+            .expect("creation of registry");
+        HdfsObjectStore::new_with_root(fs, root)
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn should_put_get_and_delete_objects() {
+        let root = format!("/{}", generate_unique_name());
+        let store = store(&root);
+        let location = Path::from("data/file.txt");
+
+        let put = store
+            .put(&location, PutPayload::from(DATA))
+            .await
+            .expect("object put");
+        assert!(put.e_tag.is_some());
+
+        let fs = HdfsRegistry::new().get(&generate_hdfs_url()).unwrap();
+        assert!(fs.exist(&format!("{}/data/file.txt", root)));
+
+        let bytes = store
+            .get(&location)
+            .await
+            .expect("object get")
+            .bytes()
+            .await
+            .expect("object read");
+        assert_eq!(DATA.as_bytes(), bytes.as_ref());
+
+        let meta = store.head(&location).await.expect("object head");
+        assert_eq!(DATA.len() as u64, meta.size);
+        assert_eq!(put.e_tag, meta.e_tag);
+        assert_eq!(location, meta.location);
+
+        let range = store.get_range(&location, 2..6).await.expect("range read");
+        assert_eq!(&DATA.as_bytes()[2..6], range.as_ref());
+
+        let options = GetOptions {
+            range: Some(GetRange::Suffix(3)),
+            ..Default::default()
+        };
+        let result = store.get_opts(&location, options).await.expect("suffix");
+        assert_eq!((DATA.len() - 3) as u64..DATA.len() as u64, result.range);
+
+        let options = GetOptions {
+            if_match: Some("other".to_string()),
+            ..Default::default()
+        };
+        assert!(matches!(
+            store.get_opts(&location, options).await,
+            Err(object_store::Error::Precondition { .. })
+        ));
+
+        let create = store
+            .put_opts(&location, PutPayload::from("new"), PutMode::Create.into())
+            .await;
+        assert!(matches!(
+            create,
+            Err(object_store::Error::AlreadyExists { .. })
+        ));
+        store
+            .put(&location, PutPayload::from("new"))
+            .await
+            .expect("object overwritten");
+        let bytes = store.get(&location).await.unwrap().bytes().await.unwrap();
+        assert_eq!(b"new", bytes.as_ref());
+
+        // directories are not objects
+        assert!(matches!(
+            store.head(&Path::from("data")).await,
+            Err(object_store::Error::NotFound { .. })
+        ));
+
+        store.delete(&location).await.expect("object deleted");
+        assert!(matches!(
+            store.get(&location).await,
+            Err(object_store::Error::NotFound { .. })
+        ));
+        assert!(store.delete(&location).await.is_err());
+
+        assert!(fs.delete(&root, true).expect("root deleted"));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn should_list_copy_and_rename_objects() {
+        let root = format!("/{}", generate_unique_name());
+        let store = store(&root);
+        for name in ["a/1.txt", "a/b/2.txt", "a/b/3.txt", "c.txt"] {
+            store
+                .put(&Path::from(name), PutPayload::from(DATA))
+                .await
+                .expect("object put");
+        }
+
+        // staged files of writes in progress are not listed
+        let fs = HdfsRegistry::new().get(&generate_hdfs_url()).unwrap();
+        let mut staged = fs
+            .create(&format!("{}/a/1.txt#1-0", root))
+            .expect("staged created");
+        staged.write_all(DATA.as_bytes()).unwrap();
+        drop(staged);
+
+        let mut all: Vec<String> = store
+            .list(None)
+            .map_ok(|meta| meta.location.to_string())
+            .try_collect()
+            .await
+            .expect("listed");
+        all.sort();
+        assert_eq!(vec!["a/1.txt", "a/b/2.txt", "a/b/3.txt", "c.txt"], all);
+
+        let mut prefixed: Vec<String> = store
+            .list(Some(&Path::from("a/b")))
+            .map_ok(|meta| meta.location.to_string())
+            .try_collect()
+            .await
+            .expect("listed");
+        prefixed.sort();
+        assert_eq!(vec!["a/b/2.txt", "a/b/3.txt"], prefixed);
+
+        let missing: Vec<_> = store
+            .list(Some(&Path::from("missing")))
+            .try_collect()
+            .await
+            .expect("listed");
+        assert!(missing.is_empty());
+
+        let listing = store
+            .list_with_delimiter(Some(&Path::from("a")))
+            .await
+            .expect("listed");
+        assert_eq!(vec![Path::from("a/b")], listing.common_prefixes);
+        assert_eq!(1, listing.objects.len());
+        assert_eq!(Path::from("a/1.txt"), listing.objects[0].location);
+
+        store
+            .copy(&Path::from("c.txt"), &Path::from("d/c.txt"))
+            .await
+            .expect("object copied");
+        assert!(matches!(
+            store
+                .copy_if_not_exists(&Path::from("c.txt"), &Path::from("d/c.txt"))
+                .await,
+            Err(object_store::Error::AlreadyExists { .. })
+        ));
+        let bytes = store
+            .get(&Path::from("d/c.txt"))
+            .await
+            .unwrap()
+            .bytes()
+            .await
+            .unwrap();
+        assert_eq!(DATA.as_bytes(), bytes.as_ref());
+
+        assert!(matches!(
+            store
+                .rename_if_not_exists(&Path::from("c.txt"), &Path::from("a/1.txt"))
+                .await,
+            Err(object_store::Error::AlreadyExists { .. })
+        ));
+        store
+            .rename(&Path::from("c.txt"), &Path::from("a/1.txt"))
+            .await
+            .expect("object renamed");
+        store
+            .rename_if_not_exists(&Path::from("a/1.txt"), &Path::from("e/1.txt"))
+            .await
+            .expect("object renamed");
+        assert!(store.head(&Path::from("c.txt")).await.is_err());
+        assert!(store.head(&Path::from("e/1.txt")).await.is_ok());
+
+        assert!(fs.delete(&root, true).expect("root deleted"));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn should_upload_parts_in_order() {
+        let root = format!("/{}", generate_unique_name());
+        let store = store(&root);
+        let location = Path::from("upload.bin");
+
+        let mut upload = store.put_multipart(&location).await.expect("upload");
+        let first = upload.put_part(PutPayload::from("first "));
+        let second = upload.put_part(PutPayload::from("second "));
+        let third = upload.put_part(PutPayload::from("third"));
+        // parts complete out of order
+        futures::future::try_join3(third, second, first)
+            .await
+            .expect("parts written");
+        assert!(store.head(&location).await.is_err());
+        upload.complete().await.expect("upload completed");
+
+        let bytes = store.get(&location).await.unwrap().bytes().await.unwrap();
+        assert_eq!(b"first second third", bytes.as_ref());
+
+        let mut aborted = store
+            .put_multipart(&Path::from("aborted.bin"))
+            .await
+            .expect("upload");
+        aborted
+            .put_part(PutPayload::from(DATA))
+            .await
+            .expect("part written");
+        aborted.abort().await.expect("upload aborted");
+
+        let all: Vec<_> = store.list(None).try_collect().await.expect("listed");
+        assert_eq!(1, all.len());
+
+        let fs = HdfsRegistry::new().get(&generate_hdfs_url()).unwrap();
+        assert_eq!(1, fs.list_status(&root).expect("listed").len());
+        assert!(fs.delete(&root, true).expect("root deleted"));
+    }
+
+    #[test]
+    fn should_work_outside_of_runtime() {
+        let root = format!("/{}", generate_unique_name());
+        let store = store(&root);
+        let location = Path::from("file.txt");
+
+        futures::executor::block_on(async {
+            store
+                .put(&location, PutPayload::from(DATA))
+                .await
+                .expect("object put");
+            let bytes = store.get(&location).await.unwrap().bytes().await.unwrap();
+            assert_eq!(DATA.as_bytes(), bytes.as_ref());
+        });
+
+        let fs = HdfsRegistry::new().get(&generate_hdfs_url()).unwrap();
+        assert!(fs.delete(&root, true).expect("root deleted"));
+    }
+}
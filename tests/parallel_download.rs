@@ -0,0 +1,61 @@
+mod common;
+
+#[cfg(test)]
+mod e2e {
+
+    use std::io::Write;
+
+    use crate::common::*;
+    use hdfs_native::{util::DownloadOptions, util::HdfsUtil, HdfsRegistry};
+    use log::info;
+
+    #[test]
+    fn should_download_file_in_parallel() {
+        let fs_registry = HdfsRegistry::new();
+        let hdfs_server_url = generate_hdfs_url();
+
+        info!("HDFS Name node to be used: [{}]", hdfs_server_url);
+
+        let fs = fs_registry
+            .get(&hdfs_server_url)
+            .expect("creation of registry");
+
+        let test_file = format!("/{}", generate_unique_name());
+
+        // small blocks make the download span multiple workers and the
+        // last block is only partially filled
+        let block_size = 1024 * 1024;
+        let total_len = 10 * block_size + block_size / 2;
+        let data: Vec<u8> = (0..total_len).map(|i| (i % 251) as u8).collect();
+
+        let mut file = fs
+            .create_with_block_size(&test_file, false, block_size)
+            .expect("file to be created");
+        for chunk in data.chunks(64 * 1024) {
+            file.write_all(chunk).expect("data written");
+        }
+        drop(file);
+
+        let blocks = fs
+            .get_block_locations(&test_file, 0, total_len as u64)
+            .expect("block locations");
+        assert_eq!(11, blocks.len());
+
+        let local_file = std::env::temp_dir().join(generate_unique_name());
+        let opts = DownloadOptions {
+            buf_size: 64 * 1024,
+            ..Default::default()
+        };
+
+        let downloaded = HdfsUtil::download_parallel(&fs, &test_file, &local_file, &opts)
+            .expect("file to be downloaded");
+
+        assert_eq!(total_len as u64, downloaded);
+        let content = std::fs::read(&local_file).expect("local file to be read");
+        assert_eq!(data.len(), content.len());
+        assert!(data == content, "downloaded content differs");
+
+        std::fs::remove_file(&local_file).expect("local file to be deleted");
+        fs.delete(&test_file, false).expect("file to be deleted");
+    }
+}
@@ -0,0 +1,157 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+mod common;
+
+#[cfg(all(test, feature = "parquet"))]
+mod e2e {
+
+    use std::io::Write;
+    use std::sync::Arc;
+
+    use crate::common::*;
+    use arrow_array::{Array, Int64Array, RecordBatch, StringArray};
+    use futures::TryStreamExt;
+    use hdfs_native::{
+        AsyncHdfsFs, AsyncHdfsParquetReader, HdfsFs, HdfsParquetReader, HdfsRegistry,
+        ParquetReaderOptions,
+    };
+    use log::info;
+    use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+    use parquet::arrow::async_reader::AsyncFileReader;
+    use parquet::arrow::{ArrowWriter, ParquetRecordBatchStreamBuilder};
+    use parquet::file::properties::WriterProperties;
+
+    const ROWS: usize = 1000;
+
+    fn hdfs() -> Arc<HdfsFs> {
+        let hdfs_server_url = generate_hdfs_url();
+        info!("HDFS Name node to be used: [{}]", hdfs_server_url);
+
+        HdfsRegistry::new()
+            .get(&hdfs_server_url)
+            .expect("creation of registry")
+    }
+
+    /// writes a file of several row groups and returns its path
+    fn write_parquet(fs: &HdfsFs) -> String {
+        let ids = Int64Array::from_iter_values(0..ROWS as i64);
+        let names = StringArray::from_iter_values((0..ROWS).map(|idx| format!("name-{}", idx)));
+        let batch = RecordBatch::try_from_iter(vec![
+            ("id", Arc::new(ids) as Arc<dyn Array>),
+            ("name", Arc::new(names) as Arc<dyn Array>),
+        ])
+        .unwrap();
+
+        let props = WriterProperties::builder()
+            .set_max_row_group_size(ROWS / 4)
+            .build();
+        let mut data = Vec::new();
+        let mut writer = ArrowWriter::try_new(&mut data, batch.schema(), Some(props)).unwrap();
+        writer.write(&batch).unwrap();
+        writer.close().unwrap();
+
+        let path = format!("/{}.parquet", generate_unique_name());
+        let mut file = fs.create(&path).expect("file created");
+        file.write_all(&data).expect("data written");
+        path
+    }
+
+    fn ids(batches: &[RecordBatch]) -> Vec<i64> {
+        batches
+            .iter()
+            .flat_map(|batch| {
+                let ids = batch
+                    .column(0)
+                    .as_any()
+                    .downcast_ref::<Int64Array>()
+                    .unwrap();
+                ids.values().to_vec()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn should_read_record_batches() {
+        let fs = hdfs();
+        let path = write_parquet(&fs);
+
+        // footer does not fit into the prefetched tail
+        let opts = ParquetReaderOptions {
+            footer_prefetch: 16,
+            ..Default::default()
+        };
+        let reader = HdfsParquetReader::open(fs.clone(), &path, &opts).expect("reader opened");
+        let metadata = reader.metadata().expect("metadata");
+        assert_eq!(4, metadata.num_row_groups());
+        assert!(Arc::ptr_eq(&metadata, &reader.metadata().unwrap()));
+
+        let batches: Vec<RecordBatch> = ParquetRecordBatchReaderBuilder::try_new(reader)
+            .expect("builder")
+            .with_batch_size(100)
+            .build()
+            .expect("reader")
+            .collect::<Result<_, _>>()
+            .expect("batches read");
+        assert_eq!((0..ROWS as i64).collect::<Vec<_>>(), ids(&batches));
+
+        let missing = format!("{}.missing", path);
+        assert!(HdfsParquetReader::open(fs.clone(), &missing, &opts).is_err());
+
+        assert!(fs.delete(&path, false).expect("file deleted"));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn should_stream_record_batches() {
+        let fs = AsyncHdfsFs::new(hdfs(), 2).expect("pool started");
+        let path = write_parquet(fs.fs());
+
+        let mut reader = AsyncHdfsParquetReader::open(&fs, &path, &Default::default())
+            .await
+            .expect("reader opened");
+
+        let ranges = vec![0..4, 100..200, 150..160, 3..10];
+        let bytes = reader.get_byte_ranges(ranges.clone()).await.expect("read");
+        for (range, bytes) in ranges.into_iter().zip(bytes) {
+            let expected = reader.get_bytes(range.clone()).await.expect("read");
+            assert_eq!(range.len(), bytes.len());
+            assert_eq!(expected, bytes);
+        }
+        assert_eq!(b"PAR1", &reader.get_bytes(0..4).await.unwrap()[..]);
+        let past_end = 0..usize::MAX / 2;
+        assert!(reader.get_bytes(past_end).await.is_err());
+
+        let builder = ParquetRecordBatchStreamBuilder::new(reader.clone())
+            .await
+            .expect("builder");
+        let row_groups = vec![1, 3];
+        let batches: Vec<RecordBatch> = builder
+            .with_row_groups(row_groups)
+            .build()
+            .expect("stream")
+            .try_collect()
+            .await
+            .expect("batches read");
+
+        let quarter = (ROWS / 4) as i64;
+        let expected: Vec<i64> = (quarter..2 * quarter)
+            .chain(3 * quarter..4 * quarter)
+            .collect();
+        assert_eq!(expected, ids(&batches));
+
+        assert!(fs.delete(&path, false).await.expect("file deleted"));
+    }
+}
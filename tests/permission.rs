@@ -0,0 +1,121 @@
+mod common;
+
+#[cfg(test)]
+mod e2e {
+
+    use std::io::Write;
+
+    use crate::common::*;
+    use hdfs_native::{FileKind, FsAction, FsPermission, HdfsRegistry, SymbolicMode};
+    use log::info;
+
+    #[test]
+    fn should_parse_octal_and_ls_permission() {
+        let permission: FsPermission = "755".parse().expect("octal parsed");
+        assert_eq!(0o755, permission.mode());
+        assert_eq!(FsAction::All, permission.user_action());
+        assert_eq!(FsAction::ReadExecute, permission.group_action());
+        assert_eq!(FsAction::ReadExecute, permission.other_action());
+        assert!(!permission.sticky_bit());
+
+        let sticky = FsPermission::from_octal("1777").expect("octal parsed");
+        assert!(sticky.sticky_bit());
+        assert_eq!("rwxrwxrwt", sticky.to_string());
+        assert_eq!(
+            sticky,
+            "drwxrwxrwt".parse::<FsPermission>().expect("ls parsed")
+        );
+
+        assert_eq!(
+            0o640,
+            "rw-r-----"
+                .parse::<FsPermission>()
+                .expect("ls parsed")
+                .mode()
+        );
+        assert_eq!(
+            "drwxr-xr-x",
+            FsPermission::from_mode(0o755).to_ls_string(FileKind::Directory)
+        );
+        assert_eq!(
+            "-rw-r--r-T",
+            FsPermission::from_mode(0o1644).to_ls_string(FileKind::File)
+        );
+
+        assert!(FsPermission::from_octal("0855").is_err());
+        assert!(FsPermission::from_octal("").is_err());
+        assert!("rwxr-xr-q".parse::<FsPermission>().is_err());
+    }
+
+    #[test]
+    fn should_apply_symbolic_mode() {
+        let base = FsPermission::from_mode(0o644);
+
+        let mode = SymbolicMode::parse("u+x,g-r,o=").expect("mode parsed");
+        assert_eq!(0o700, mode.apply(base, false).mode());
+
+        assert_eq!(0o666, base.apply_mode("a+w", false).unwrap().mode());
+        assert_eq!(0o666, base.apply_mode("+w", false).unwrap().mode());
+        assert_eq!(0o755, base.apply_mode("a+X", true).unwrap().mode());
+        assert_eq!(0o644, base.apply_mode("a+X", false).unwrap().mode());
+        assert_eq!(0o1644, base.apply_mode("+t", false).unwrap().mode());
+        assert_eq!(
+            0o750,
+            base.apply_mode("u=rwx,g=rx,o-rwx", false).unwrap().mode()
+        );
+        assert_eq!(0o600, base.apply_mode("go=", false).unwrap().mode());
+        assert_eq!(0o711, base.apply_mode("711", false).unwrap().mode());
+
+        assert!(SymbolicMode::parse("u").is_err());
+        assert!(SymbolicMode::parse("u+q").is_err());
+        assert!(SymbolicMode::parse("z+r").is_err());
+    }
+
+    #[test]
+    fn should_apply_umask() {
+        let umask = FsPermission::from_mode(0o022);
+        assert_eq!(
+            0o644,
+            FsPermission::file_default().apply_umask(umask).mode()
+        );
+        assert_eq!(0o755, FsPermission::dir_default().apply_umask(umask).mode());
+
+        assert_eq!(FsAction::ReadWrite, FsAction::Read.or(FsAction::Write));
+        assert_eq!(
+            FsAction::Read,
+            FsAction::ReadExecute.and(FsAction::ReadWrite)
+        );
+        assert_eq!(FsAction::WriteExecute, FsAction::Read.not());
+        assert!(FsAction::All.implies(FsAction::ReadExecute));
+        assert!(!FsAction::Read.implies(FsAction::Write));
+    }
+
+    #[test]
+    fn should_set_typed_permission() {
+        let fs_registry = HdfsRegistry::new();
+        let hdfs_server_url = generate_hdfs_url();
+
+        info!("HDFS Name node to be used: [{}]", hdfs_server_url);
+
+        let fs = fs_registry
+            .get(&hdfs_server_url)
+            .expect("creation of registry");
+
+        let test_file = format!("/{}", generate_unique_name());
+        let mut f = fs.create(&test_file).expect("file created");
+        f.write_all(DATA.as_bytes()).expect("data written");
+        drop(f);
+
+        let status = fs.get_file_status(&test_file).expect("file status");
+        let permission = status
+            .fs_permission()
+            .apply_mode("u=rw,go=r", status.is_directory())
+            .expect("mode applied");
+
+        assert!(fs.set_permission(&test_file, permission));
+        let status = fs.get_file_status(&test_file).expect("file status");
+        assert_eq!("rw-r--r--", status.fs_permission().to_string());
+
+        assert!(fs.delete(&test_file, false).expect("file deleted"));
+    }
+}
@@ -0,0 +1,98 @@
+mod common;
+
+#[cfg(test)]
+mod e2e {
+
+    use crate::common::*;
+    use hdfs_native::{recursive::RecursiveOptions, HdfsRegistry};
+    use log::info;
+
+    #[test]
+    fn should_chmod_and_chown_recursively() {
+        let fs_registry = HdfsRegistry::new();
+        let hdfs_server_url = generate_hdfs_url();
+
+        info!("HDFS Name node to be used: [{}]", hdfs_server_url);
+
+        let fs = fs_registry
+            .get(&hdfs_server_url)
+            .expect("creation of registry");
+
+        let test_dir = format!("/{}", generate_unique_name());
+        fs.mkdir(&format!("{}/a/b", test_dir)).expect("dir created");
+        for file in ["a/file", "a/b/file", "a/b/other"] {
+            fs.create(&format!("{}/{}", test_dir, file))
+                .expect("file created");
+        }
+
+        let dry_run = RecursiveOptions {
+            dry_run: true,
+            ..Default::default()
+        };
+        let report = fs.chmod_recursive(&test_dir, 0o700, &dry_run);
+        // root, 2 directories and 3 files
+        assert_eq!(6, report.succeeded);
+        let status = fs.get_file_status(&test_dir).expect("status");
+        assert_ne!(0o700, status.permission());
+
+        let only_files = |_: &str, status: &hdfs_native::FileStatus| status.is_file();
+        let opts = RecursiveOptions {
+            parallelism: 4,
+            filter: Some(&only_files),
+            ..Default::default()
+        };
+        let report = fs.chmod_recursive(&test_dir, 0o600, &opts);
+        assert!(report.is_success());
+        assert_eq!(3, report.succeeded);
+        assert_eq!(3, report.skipped);
+        let status = fs
+            .get_file_status(&format!("{}/a/b/other", test_dir))
+            .expect("status");
+        assert_eq!(0o600, status.permission());
+
+        // symbolic mode is applied to the permission of each path
+        let report = fs
+            .chmod_mode_recursive(&test_dir, "u-w,g+X", &RecursiveOptions::default())
+            .expect("valid mode");
+        assert!(report.is_success());
+        assert_eq!(6, report.succeeded);
+        let status = fs
+            .get_file_status(&format!("{}/a/b/other", test_dir))
+            .expect("status");
+        assert_eq!(0o400, status.permission());
+        let status = fs.get_file_status(&test_dir).expect("status");
+        assert_eq!(0o010, status.permission() & 0o210);
+        assert!(fs
+            .chmod_mode_recursive(&test_dir, "u+q", &RecursiveOptions::default())
+            .is_err());
+
+        let report = fs.chown_recursive(&test_dir, "", "staff", &RecursiveOptions::default());
+        assert!(report.is_success());
+
+        let report = fs.set_replication_recursive(&test_dir, 1, &RecursiveOptions::default());
+        assert!(report.is_success());
+        assert_eq!(3, report.succeeded);
+        let status = fs
+            .get_file_status(&format!("{}/a/file", test_dir))
+            .expect("status");
+        assert_eq!(1, status.replica_count());
+
+        fs.delete(&test_dir, true).expect("directory to be deleted");
+    }
+
+    #[test]
+    fn should_report_failures() {
+        let fs_registry = HdfsRegistry::new();
+        let hdfs_server_url = generate_hdfs_url();
+
+        let fs = fs_registry
+            .get(&hdfs_server_url)
+            .expect("creation of registry");
+
+        let missing = format!("/{}", generate_unique_name());
+        let report = fs.chmod_recursive(&missing, 0o700, &RecursiveOptions::default());
+
+        assert!(!report.is_success());
+        assert_eq!(missing, report.failures[0].0);
+    }
+}
@@ -0,0 +1,174 @@
+mod common;
+
+#[cfg(test)]
+mod e2e {
+
+    use std::io::Write;
+    use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+    use crate::common::*;
+    use hdfs_native::{
+        sync::{SyncEndpoint, SyncOptions},
+        util::{CopyOptions, HdfsUtil, NoProgress, TransferOptions},
+        HdfsRegistry,
+    };
+    use log::info;
+
+    const MTIME: u64 = 1_600_000_000;
+    const ATIME: u64 = 1_600_000_100;
+
+    #[test]
+    fn should_set_times() {
+        let fs_registry = HdfsRegistry::new();
+        let hdfs_server_url = generate_hdfs_url();
+
+        info!("HDFS Name node to be used: [{}]", hdfs_server_url);
+
+        let fs = fs_registry
+            .get(&hdfs_server_url)
+            .expect("creation of registry");
+
+        let test_file = format!("/{}", generate_unique_name());
+        let mut f = fs.create(&test_file).expect("file created");
+        f.write_all(DATA.as_bytes()).expect("data written");
+        drop(f);
+
+        let mtime = UNIX_EPOCH + Duration::from_secs(MTIME);
+        let atime = UNIX_EPOCH + Duration::from_secs(ATIME);
+        assert!(fs
+            .set_times(&test_file, Some(mtime), Some(atime))
+            .expect("times set"));
+
+        let status = fs.get_file_status(&test_file).expect("file status");
+        assert_eq!(MTIME as i64, status.last_modified() as i64);
+        assert_eq!(ATIME as i64, status.last_accced() as i64);
+
+        // access time only, modification time is kept
+        let atime = UNIX_EPOCH + Duration::from_secs(ATIME + 100);
+        fs.set_times(&test_file, None, Some(atime))
+            .expect("times set");
+        let status = fs.get_file_status(&test_file).expect("file status");
+        assert_eq!(MTIME as i64, status.last_modified() as i64);
+        assert_eq!((ATIME + 100) as i64, status.last_accced() as i64);
+
+        let missing = format!("/{}", generate_unique_name());
+        assert!(fs.set_times(&missing, Some(mtime), None).is_err());
+
+        assert!(fs.delete(&test_file, false).expect("file deleted"));
+    }
+
+    #[test]
+    fn should_preserve_times_when_copying() {
+        let fs_registry = HdfsRegistry::new();
+        let hdfs_server_url = generate_hdfs_url();
+
+        let fs = fs_registry
+            .get(&hdfs_server_url)
+            .expect("creation of registry");
+
+        let test_dir = format!("/{}", generate_unique_name());
+        fs.mkdir(&test_dir).expect("dir created");
+        let src = format!("{}/src", test_dir);
+        let mut f = fs.create(&src).expect("file created");
+        f.write_all(DATA.as_bytes()).expect("data written");
+        drop(f);
+
+        let mtime = UNIX_EPOCH + Duration::from_secs(MTIME);
+        fs.set_times(&src, Some(mtime), Some(mtime))
+            .expect("times set");
+
+        let dst = format!("{}/dst", test_dir);
+        let opts = CopyOptions {
+            preserve_times: true,
+            ..Default::default()
+        };
+        HdfsUtil::cp_with_options(&fs, &src, &fs, &dst, &opts).expect("file copied");
+        let status = fs.get_file_status(&dst).expect("file status");
+        assert_eq!(MTIME as i64, status.last_modified() as i64);
+
+        let local = std::env::temp_dir().join(generate_unique_name());
+        let opts = TransferOptions {
+            preserve_times: true,
+            ..Default::default()
+        };
+        HdfsUtil::copy_to_local(&fs, &src, &local, &opts, &NoProgress).expect("file downloaded");
+        let modified = std::fs::metadata(&local)
+            .and_then(|m| m.modified())
+            .expect("local mtime");
+        assert_eq!(mtime, modified);
+
+        let uploaded = format!("{}/uploaded", test_dir);
+        HdfsUtil::copy_from_local(&local, &fs, &uploaded, &opts, &NoProgress)
+            .expect("file uploaded");
+        let status = fs.get_file_status(&uploaded).expect("file status");
+        assert_eq!(MTIME as i64, status.last_modified() as i64);
+
+        std::fs::remove_file(&local).expect("local file removed");
+        assert!(fs.delete(&test_dir, true).expect("dir deleted"));
+    }
+
+    #[test]
+    fn should_not_copy_again_when_sync_preserves_times() {
+        let fs_registry = HdfsRegistry::new();
+        let hdfs_server_url = generate_hdfs_url();
+
+        let fs = fs_registry
+            .get(&hdfs_server_url)
+            .expect("creation of registry");
+
+        let local_src = std::env::temp_dir().join(generate_unique_name());
+        std::fs::create_dir_all(&local_src).expect("local dir created");
+        std::fs::write(local_src.join("a.txt"), DATA).expect("local file written");
+        // copies made now would be newer than the source, unless times are preserved
+        let old = SystemTime::now() - Duration::from_secs(3600);
+        std::fs::File::open(local_src.join("a.txt"))
+            .and_then(|f| f.set_modified(old))
+            .expect("local mtime set");
+
+        let test_dir = format!("/{}", generate_unique_name());
+        let opts = SyncOptions {
+            preserve_times: true,
+            ..Default::default()
+        };
+
+        let report = HdfsUtil::sync(
+            SyncEndpoint::Local(&local_src),
+            SyncEndpoint::Hdfs(&fs, &test_dir),
+            &opts,
+        )
+        .expect("synced");
+        assert_eq!(vec!["a.txt".to_string()], report.copied);
+        let status = fs
+            .get_file_status(&format!("{}/a.txt", test_dir))
+            .expect("file status");
+        let old_secs = old.duration_since(UNIX_EPOCH).unwrap().as_secs();
+        assert_eq!(old_secs as i64, status.last_modified() as i64);
+
+        let back = std::env::temp_dir().join(generate_unique_name());
+        let report = HdfsUtil::sync(
+            SyncEndpoint::Hdfs(&fs, &test_dir),
+            SyncEndpoint::Local(&back),
+            &opts,
+        )
+        .expect("synced");
+        assert_eq!(1, report.copied.len());
+
+        // touching the source makes it newer than the copy
+        std::fs::File::options()
+            .write(true)
+            .open(local_src.join("a.txt"))
+            .and_then(|f| f.set_modified(SystemTime::now()))
+            .expect("local mtime set");
+        let report = HdfsUtil::sync(
+            SyncEndpoint::Local(&local_src),
+            SyncEndpoint::Hdfs(&fs, &test_dir),
+            &opts,
+        )
+        .expect("synced");
+        assert_eq!(vec!["a.txt".to_string()], report.copied);
+
+        std::fs::remove_dir_all(&local_src).expect("local dir removed");
+        std::fs::remove_dir_all(&back).expect("local dir removed");
+        assert!(fs.delete(&test_dir, true).expect("dir deleted"));
+    }
+}
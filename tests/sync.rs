@@ -0,0 +1,126 @@
+mod common;
+
+#[cfg(test)]
+mod e2e {
+
+    use std::io::Write;
+
+    use crate::common::*;
+    use hdfs_native::{
+        sync::{SyncEndpoint, SyncOptions},
+        HdfsRegistry, HdfsUtil,
+    };
+    use log::info;
+
+    #[test]
+    fn should_sync_local_directory_to_hdfs_and_back() {
+        let fs_registry = HdfsRegistry::new();
+        let hdfs_server_url = generate_hdfs_url();
+
+        info!("HDFS Name node to be used: [{}]", hdfs_server_url);
+
+        let fs = fs_registry
+            .get(&hdfs_server_url)
+            .expect("creation of registry");
+
+        let local_src = std::env::temp_dir().join(generate_unique_name());
+        std::fs::create_dir_all(local_src.join("nested")).expect("local dir created");
+        std::fs::write(local_src.join("a.txt"), DATA).expect("local file written");
+        std::fs::write(local_src.join("nested").join("b.txt"), DATA).expect("local file written");
+
+        let test_dir = format!("/{}", generate_unique_name());
+        let src = SyncEndpoint::Local(&local_src);
+        let dst = SyncEndpoint::Hdfs(&fs, &test_dir);
+
+        let dry_run = SyncOptions {
+            dry_run: true,
+            ..Default::default()
+        };
+        let report = HdfsUtil::sync(src, dst, &dry_run).expect("dry run");
+        assert_eq!(vec!["a.txt", "nested/b.txt"], report.copied);
+        assert!(!fs.exist(&test_dir));
+
+        let report = HdfsUtil::sync(src, dst, &SyncOptions::default()).expect("sync");
+        assert_eq!(2, report.copied.len());
+        assert_eq!(vec!["nested"], report.created);
+        assert_eq!(2 * DATA.len() as u64, report.bytes);
+
+        // nothing changed, as destination is newer
+        let report = HdfsUtil::sync(src, dst, &SyncOptions::default()).expect("sync");
+        assert!(report.copied.is_empty());
+        assert_eq!(2, report.unchanged);
+
+        // same size, different content
+        let mut f = fs
+            .create_with_overwrite(&format!("{}/a.txt", test_dir), true)
+            .expect("file created");
+        f.write_all(DATA.to_lowercase().as_bytes())
+            .expect("data written");
+        drop(f);
+        fs.create(&format!("{}/extra.txt", test_dir))
+            .expect("file created");
+
+        let opts = SyncOptions {
+            compare_content: true,
+            delete: true,
+            ..Default::default()
+        };
+        let report = HdfsUtil::sync(src, dst, &opts).expect("sync");
+        assert_eq!(vec!["a.txt"], report.copied);
+        assert_eq!(vec!["extra.txt"], report.deleted);
+        assert!(!fs.exist(&format!("{}/extra.txt", test_dir)));
+
+        let local_dst = std::env::temp_dir().join(generate_unique_name());
+        let report = HdfsUtil::sync(
+            dst,
+            SyncEndpoint::Local(&local_dst),
+            &SyncOptions::default(),
+        )
+        .expect("sync");
+        assert_eq!(2, report.copied.len());
+        let content = std::fs::read_to_string(local_dst.join("a.txt")).expect("file read");
+        assert_eq!(DATA, content);
+
+        std::fs::remove_dir_all(&local_src).expect("local dir to be deleted");
+        std::fs::remove_dir_all(&local_dst).expect("local dir to be deleted");
+        fs.delete(&test_dir, true).expect("directory to be deleted");
+    }
+
+    #[test]
+    fn should_delete_directories_sharing_name_prefix() {
+        let fs_registry = HdfsRegistry::new();
+        let hdfs_server_url = generate_hdfs_url();
+
+        let fs = fs_registry
+            .get(&hdfs_server_url)
+            .expect("creation of registry");
+
+        let local_src = std::env::temp_dir().join(generate_unique_name());
+        std::fs::create_dir_all(&local_src).expect("local dir created");
+        std::fs::write(local_src.join("keep.txt"), DATA).expect("local file written");
+
+        // "dir.1" sorts between "dir" and "dir/x"
+        let test_dir = format!("/{}", generate_unique_name());
+        for file in ["dir/x", "dir/sub/y", "dir.1/z", "keep.txt"] {
+            fs.create(&format!("{}/{}", test_dir, file))
+                .expect("file created");
+        }
+
+        let opts = SyncOptions {
+            delete: true,
+            ..Default::default()
+        };
+        let report = HdfsUtil::sync(
+            SyncEndpoint::Local(&local_src),
+            SyncEndpoint::Hdfs(&fs, &test_dir),
+            &opts,
+        )
+        .expect("sync");
+        assert_eq!(vec!["dir", "dir.1"], report.deleted);
+        assert!(!fs.exist(&format!("{}/dir", test_dir)));
+        assert!(!fs.exist(&format!("{}/dir.1", test_dir)));
+
+        std::fs::remove_dir_all(&local_src).expect("local dir to be deleted");
+        fs.delete(&test_dir, true).expect("directory to be deleted");
+    }
+}
@@ -0,0 +1,191 @@
+mod common;
+
+#[cfg(test)]
+mod e2e {
+
+    use std::time::Duration;
+
+    use crate::common::*;
+    use hdfs_native::{trash::Trash, HdfsRegistry};
+    use log::info;
+
+    #[test]
+    fn should_move_to_trash_and_restore() {
+        let fs_registry = HdfsRegistry::new();
+        let hdfs_server_url = generate_hdfs_url();
+
+        info!("HDFS Name node to be used: [{}]", hdfs_server_url);
+
+        let fs = fs_registry
+            .get(&hdfs_server_url)
+            .expect("creation of registry");
+
+        let user = generate_unique_name();
+        let trash = Trash::for_user(&fs, &user).with_interval(Duration::from_secs(3600));
+        assert_eq!(format!("/user/{}/.Trash", user), trash.root());
+
+        let test_dir = format!("/{}", generate_unique_name());
+        let test_file = format!("{}/file", test_dir);
+        fs.mkdir(&test_dir).expect("dir created");
+
+        fs.create(&test_file).expect("file created");
+        let first = trash.move_to_trash(&test_file).expect("moved to trash");
+        assert_eq!(format!("{}{}", trash.current(), test_file), first);
+        assert!(!fs.exist(&test_file));
+
+        // name collision within trash gets a suffix
+        fs.create(&test_file).expect("file created");
+        let second = trash.move_to_trash(&test_file).expect("moved to trash");
+        assert_ne!(first, second);
+        assert!(second.starts_with(&first));
+
+        let restored = trash.restore(&test_file).expect("restored");
+        assert_eq!(first, restored);
+        assert!(fs.exist(&test_file));
+
+        // restored location is free again
+        let third = trash.move_to_trash(&test_file).expect("moved to trash");
+        assert_eq!(first, third);
+
+        fs.delete(&test_dir, true).expect("directory to be deleted");
+        fs.delete(&format!("/user/{}", user), true)
+            .expect("directory to be deleted");
+    }
+
+    #[test]
+    fn should_checkpoint_and_expunge() {
+        let fs_registry = HdfsRegistry::new();
+        let hdfs_server_url = generate_hdfs_url();
+
+        let fs = fs_registry
+            .get(&hdfs_server_url)
+            .expect("creation of registry");
+
+        let user = generate_unique_name();
+        let trash = Trash::for_user(&fs, &user).with_interval(Duration::from_secs(1));
+
+        let test_file = format!("/{}", generate_unique_name());
+        fs.create(&test_file).expect("file created");
+        trash.move_to_trash(&test_file).expect("moved to trash");
+
+        let checkpoint = trash
+            .checkpoint()
+            .expect("checkpoint")
+            .expect("checkpoint created");
+        assert!(!fs.exist(&trash.current()));
+        assert!(fs.exist(&format!("{}{}", checkpoint, test_file)));
+        assert!(trash.checkpoint().expect("checkpoint").is_none());
+
+        std::thread::sleep(Duration::from_millis(2100));
+        let deleted = trash.expunge().expect("expunge");
+        assert_eq!(vec![checkpoint.clone()], deleted);
+        assert!(!fs.exist(&checkpoint));
+
+        fs.delete(&format!("/user/{}", user), true)
+            .expect("directory to be deleted");
+    }
+
+    #[test]
+    fn should_delete_without_trash_when_disabled() {
+        let fs_registry = HdfsRegistry::new();
+        let hdfs_server_url = generate_hdfs_url();
+
+        let fs = fs_registry
+            .get(&hdfs_server_url)
+            .expect("creation of registry");
+
+        let user = generate_unique_name();
+        let trash = Trash::for_user(&fs, &user).with_interval(Duration::ZERO);
+        assert!(!trash.is_enabled());
+
+        let test_dir = format!("/{}", generate_unique_name());
+        let test_file = format!("{}/file", test_dir);
+        fs.mkdir(&test_dir).expect("dir created");
+        fs.create(&test_file).expect("file created");
+
+        trash
+            .delete(&test_dir, false)
+            .expect_err("directory requires recursive delete");
+        assert_eq!(None, trash.delete(&test_file, false).expect("deleted"));
+        assert!(!fs.exist(&test_file));
+        assert!(!fs.exist(trash.root()));
+
+        // trash is disabled in the client configuration of tests
+        fs.delete_with_trash(&test_dir, false, false)
+            .expect_err("directory requires recursive delete");
+        fs.delete_with_trash(&test_dir, true, false)
+            .expect("directory deleted");
+        assert!(!fs.exist(&test_dir));
+    }
+
+    #[test]
+    fn should_move_to_trash_on_delete_when_enabled() {
+        let fs_registry = HdfsRegistry::new();
+        let hdfs_server_url = generate_hdfs_url();
+
+        let fs = fs_registry
+            .get(&hdfs_server_url)
+            .expect("creation of registry");
+
+        let user = generate_unique_name();
+        let trash = Trash::for_user(&fs, &user).with_interval(Duration::from_secs(3600));
+
+        let test_dir = format!("/{}", generate_unique_name());
+        fs.mkdir(&test_dir).expect("dir created");
+        fs.create(&format!("{}/file", test_dir))
+            .expect("file created");
+
+        trash
+            .delete(&test_dir, false)
+            .expect_err("directory requires recursive delete");
+        let location = trash
+            .delete(&test_dir, true)
+            .expect("deleted")
+            .expect("moved to trash");
+        assert_eq!(format!("{}{}", trash.current(), test_dir), location);
+        assert!(!fs.exist(&test_dir));
+        assert!(fs.exist(&format!("{}/file", location)));
+
+        // paths in the trash are deleted for good
+        assert_eq!(None, trash.delete(&location, true).expect("deleted"));
+        assert!(!fs.exist(&location));
+
+        fs.delete(&format!("/user/{}", user), true)
+            .expect("directory to be deleted");
+    }
+
+    #[test]
+    fn should_resolve_relative_paths() {
+        let fs_registry = HdfsRegistry::new();
+        let hdfs_server_url = generate_hdfs_url();
+
+        let fs = fs_registry
+            .get(&hdfs_server_url)
+            .expect("creation of registry");
+
+        let user = generate_unique_name();
+        let trash = Trash::for_user(&fs, &user).with_interval(Duration::from_secs(3600));
+
+        let name = generate_unique_name();
+        fs.create(&name).expect("file created");
+
+        let location = trash.move_to_trash(&name).expect("moved to trash");
+        assert!(location.starts_with(&trash.current()));
+        assert!(location.ends_with(&format!("/{}", name)));
+        assert!(!fs.exist(&name));
+
+        let restored = trash.restore(&format!("./{}", name)).expect("restored");
+        assert_eq!(location, restored);
+        assert!(fs.exist(&name));
+
+        let location = trash
+            .delete(&name, false)
+            .expect("deleted")
+            .expect("moved to trash");
+        assert_eq!(restored, location);
+        assert!(!fs.exist(&name));
+
+        fs.delete(&format!("/user/{}", user), true)
+            .expect("directory to be deleted");
+    }
+}
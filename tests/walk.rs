@@ -0,0 +1,125 @@
+mod common;
+
+#[cfg(test)]
+mod e2e {
+
+    use crate::common::*;
+    use hdfs_native::{walk::error_path, HdfsFs, HdfsRegistry};
+    use log::info;
+
+    /// creates following tree under ```root```
+    ///
+    /// ```text
+    /// root/a/file
+    /// root/a/b/file
+    /// root/c/file
+    /// ```
+    fn create_tree(fs: &HdfsFs, root: &str) {
+        fs.mkdir(&format!("{}/a/b", root)).expect("dir created");
+        fs.mkdir(&format!("{}/c", root)).expect("dir created");
+        for dir in ["a", "a/b", "c"] {
+            fs.create(&format!("{}/{}/file", root, dir))
+                .expect("file created");
+        }
+    }
+
+    #[test]
+    fn should_walk_directory_tree() {
+        let fs_registry = HdfsRegistry::new();
+        let hdfs_server_url = generate_hdfs_url();
+
+        info!("HDFS Name node to be used: [{}]", hdfs_server_url);
+
+        let fs = fs_registry
+            .get(&hdfs_server_url)
+            .expect("creation of registry");
+
+        let test_dir = format!("/{}", generate_unique_name());
+        create_tree(&fs, &test_dir);
+
+        let all: Vec<String> = fs.walk(&test_dir).map(|e| e.expect("entry").0).collect();
+        // root, 3 directories and 3 files
+        assert_eq!(7, all.len());
+        assert_eq!(test_dir, all[0]);
+
+        let post_order: Vec<String> = fs
+            .walk(&test_dir)
+            .contents_first(true)
+            .map(|e| e.expect("entry").0)
+            .collect();
+        assert_eq!(test_dir, post_order[post_order.len() - 1]);
+
+        let first_level: Vec<String> = fs
+            .walk(&test_dir)
+            .min_depth(1)
+            .max_depth(1)
+            .map(|e| e.expect("entry").0)
+            .collect();
+        assert_eq!(2, first_level.len());
+
+        let pruned: Vec<String> = fs
+            .walk(&test_dir)
+            .filter_dir(|path, _| !path.ends_with("/a"))
+            .map(|e| e.expect("entry").0)
+            .collect();
+        // root, c and c/file
+        assert_eq!(3, pruned.len());
+
+        let parallel = fs
+            .walk(&test_dir)
+            .parallelism(4)
+            .filter(|e| e.as_ref().expect("entry").1.is_file())
+            .count();
+        assert_eq!(3, parallel);
+
+        fs.delete(&test_dir, true).expect("directory to be deleted");
+    }
+
+    #[test]
+    fn should_return_error_for_missing_root() {
+        let fs_registry = HdfsRegistry::new();
+        let hdfs_server_url = generate_hdfs_url();
+
+        let fs = fs_registry
+            .get(&hdfs_server_url)
+            .expect("creation of registry");
+
+        let missing = format!("/{}", generate_unique_name());
+        let mut walk = fs.walk(&missing);
+        let error = walk.next().expect("error").expect_err("walk error");
+        assert_eq!(Some(missing.as_str()), error_path(&error));
+        assert!(walk.next().is_none());
+    }
+
+    #[test]
+    fn should_report_path_which_failed() {
+        let fs_registry = HdfsRegistry::new();
+        let hdfs_server_url = generate_hdfs_url();
+
+        let fs = fs_registry
+            .get(&hdfs_server_url)
+            .expect("creation of registry");
+
+        let test_dir = format!("/{}", generate_unique_name());
+        create_tree(&fs, &test_dir);
+        let removed = format!("{}/a/b", test_dir);
+
+        // directory disappears after its parent has been listed
+        let errors: Vec<_> = fs
+            .walk(&test_dir)
+            .continue_on_error(true)
+            .filter_dir(|path, _| {
+                if path == removed {
+                    fs.delete(path, true).expect("dir deleted");
+                }
+                true
+            })
+            .filter_map(|e| e.err())
+            .collect();
+
+        assert_eq!(1, errors.len());
+        assert_eq!(Some(removed.as_str()), error_path(&errors[0]));
+
+        fs.delete(&test_dir, true).expect("dir deleted");
+    }
+}
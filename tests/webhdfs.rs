@@ -0,0 +1,738 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+#[cfg(all(test, feature = "webhdfs"))]
+mod e2e {
+
+    use std::collections::BTreeMap;
+    use std::io::{BufRead, BufReader, ErrorKind, Read, Write};
+    use std::net::{TcpListener, TcpStream};
+    use std::sync::{Arc, Mutex};
+    use std::time::{Duration, UNIX_EPOCH};
+
+    use hdfs_native::HdfsRegistry;
+    use url::Url;
+
+    #[derive(Clone)]
+    struct Node {
+        data: Option<Vec<u8>>,
+        permission: u16,
+        owner: String,
+        group: String,
+        replication: u16,
+        block_size: u64,
+        /// modification and access time in milliseconds
+        times: (i64, i64),
+    }
+
+    #[derive(Default)]
+    struct State {
+        nodes: BTreeMap<String, Node>,
+        /// ```op user.name``` of requests served by the data node
+        data_node: Vec<String>,
+        /// ```startAfter``` of ```LISTSTATUS_BATCH``` requests
+        batches: Vec<String>,
+        users: Vec<String>,
+    }
+
+    struct Request {
+        method: String,
+        url: Url,
+        body: Vec<u8>,
+    }
+
+    struct Response {
+        status: u16,
+        headers: Vec<(String, String)>,
+        body: Vec<u8>,
+    }
+
+    impl Response {
+        fn json(status: u16, body: String) -> Response {
+            Response {
+                status,
+                headers: vec![("Content-Type".into(), "application/json".into())],
+                body: body.into_bytes(),
+            }
+        }
+
+        fn boolean(value: bool) -> Response {
+            Response::json(200, format!("{{\"boolean\":{}}}", value))
+        }
+
+        fn error(status: u16, exception: &str, message: &str) -> Response {
+            Response::json(
+                status,
+                serde_json::json!({"RemoteException": {"exception": exception, "message": message}})
+                    .to_string(),
+            )
+        }
+
+        fn empty(status: u16) -> Response {
+            Response {
+                status,
+                headers: vec![],
+                body: vec![],
+            }
+        }
+    }
+
+    /// Minimal in memory WebHDFS name node which redirects data
+    /// requests to itself acting as a data node
+    struct MockWebHdfs {
+        port: u16,
+        state: Arc<Mutex<State>>,
+    }
+
+    impl MockWebHdfs {
+        fn start() -> MockWebHdfs {
+            let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+            let port = listener.local_addr().unwrap().port();
+            let state = Arc::new(Mutex::new(State::default()));
+            state
+                .lock()
+                .unwrap()
+                .nodes
+                .insert("/".to_string(), directory("hdfs"));
+
+            let server_state = state.clone();
+            std::thread::spawn(move || {
+                for stream in listener.incoming() {
+                    let state = server_state.clone();
+                    std::thread::spawn(move || serve(stream.unwrap(), port, state));
+                }
+            });
+            MockWebHdfs { port, state }
+        }
+
+        fn url(&self) -> String {
+            format!("webhdfs://127.0.0.1:{}", self.port)
+        }
+    }
+
+    fn directory(owner: &str) -> Node {
+        Node {
+            data: None,
+            permission: 0o755,
+            owner: owner.to_string(),
+            group: "supergroup".to_string(),
+            replication: 0,
+            block_size: 0,
+            times: (1_600_000_001_000, 1_600_000_000_000),
+        }
+    }
+
+    fn serve(stream: TcpStream, port: u16, state: Arc<Mutex<State>>) {
+        let mut reader = BufReader::new(stream.try_clone().unwrap());
+        let mut writer = stream;
+        while let Some(request) = read_request(&mut reader, port) {
+            let response = handle(&request, port, &mut state.lock().unwrap());
+            let mut head = format!(
+                "HTTP/1.1 {} Mock\r\nContent-Length: {}\r\n",
+                response.status,
+                response.body.len()
+            );
+            for (name, value) in &response.headers {
+                head.push_str(&format!("{}: {}\r\n", name, value));
+            }
+            head.push_str("\r\n");
+            writer.write_all(head.as_bytes()).unwrap();
+            writer.write_all(&response.body).unwrap();
+        }
+    }
+
+    fn read_request(reader: &mut BufReader<TcpStream>, port: u16) -> Option<Request> {
+        let mut line = String::new();
+        if reader.read_line(&mut line).ok()? == 0 {
+            return None;
+        }
+        let mut parts = line.split_whitespace();
+        let method = parts.next()?.to_string();
+        let url = Url::parse(&format!("http://127.0.0.1:{}{}", port, parts.next()?)).ok()?;
+
+        let mut length = 0;
+        let mut chunked = false;
+        loop {
+            let mut header = String::new();
+            reader.read_line(&mut header).ok()?;
+            let header = header.trim_end();
+            if header.is_empty() {
+                break;
+            }
+            let (name, value) = header.split_once(':')?;
+            match name.to_ascii_lowercase().as_str() {
+                "content-length" => length = value.trim().parse().ok()?,
+                "transfer-encoding" => chunked = value.trim() == "chunked",
+                _ => {}
+            }
+        }
+
+        let mut body = vec![0u8; length];
+        reader.read_exact(&mut body).ok()?;
+        while chunked {
+            let mut size = String::new();
+            reader.read_line(&mut size).ok()?;
+            let size = usize::from_str_radix(size.trim(), 16).ok()?;
+            let mut chunk = vec![0u8; size + 2];
+            reader.read_exact(&mut chunk).ok()?;
+            body.extend_from_slice(&chunk[..size]);
+            chunked = size > 0;
+        }
+        Some(Request { method, url, body })
+    }
+
+    fn handle(request: &Request, port: u16, state: &mut State) -> Response {
+        let params: BTreeMap<String, String> = request.url.query_pairs().into_owned().collect();
+        let param = |name: &str| params.get(name).cloned().unwrap_or_default();
+        let path = match request.url.path().strip_prefix("/webhdfs/v1") {
+            Some("") | Some("/") => "/".to_string(),
+            Some(path) => path.trim_end_matches('/').to_string(),
+            None => return Response::empty(404),
+        };
+        let parent = match path.rfind('/') {
+            Some(0) => "/".to_string(),
+            Some(idx) => path[..idx].to_string(),
+            None => return Response::empty(400),
+        };
+        let user = param("user.name");
+        state.users.push(user.clone());
+        let op = param("op");
+        let exists = state.nodes.contains_key(&path);
+        let not_found = || Response::error(404, "FileNotFoundException", "File does not exist");
+
+        // data requests are redirected to the "data node"
+        if ["CREATE", "APPEND", "OPEN"].contains(&op.as_str()) && param("datanode").is_empty() {
+            let mut location = request.url.clone();
+            location.query_pairs_mut().append_pair("datanode", "true");
+            assert_eq!(port, location.port().unwrap());
+            let mut response = Response::empty(307);
+            response
+                .headers
+                .push(("Location".to_string(), location.to_string()));
+            if op == "CREATE" && exists && param("overwrite") != "true" {
+                return Response::error(403, "FileAlreadyExistsException", "File exists");
+            }
+            return response;
+        }
+
+        match (request.method.as_str(), op.as_str()) {
+            ("GET", "GETHOMEDIRECTORY") => Response::json(
+                200,
+                serde_json::json!({ "Path": format!("/user/{}", user) }).to_string(),
+            ),
+            ("GET", "GETSTATUS") => {
+                let used: usize = state
+                    .nodes
+                    .values()
+                    .filter_map(|node| node.data.as_ref().map(|data| data.len()))
+                    .sum();
+                Response::json(
+                    200,
+                    serde_json::json!({ "FsStatus": { "capacity": 1000, "used": used } })
+                        .to_string(),
+                )
+            }
+            ("GET", "GETSERVERDEFAULTS") => Response::json(
+                200,
+                serde_json::json!({ "FsServerDefaults": { "blockSize": 134217728 } }).to_string(),
+            ),
+            ("GET", "GETFILEBLOCKLOCATIONS") => match state.nodes.get(&path) {
+                Some(node) if node.data.is_some() => {
+                    let len = node.data.as_ref().unwrap().len() as u64;
+                    let offset: u64 = param("offset").parse().unwrap_or(0);
+                    let end = offset + param("length").parse::<u64>().unwrap_or(len);
+                    let blocks: Vec<_> = (0..len)
+                        .step_by(node.block_size as usize)
+                        .filter(|start| *start < end && start + node.block_size > offset)
+                        .map(|start| {
+                            serde_json::json!({
+                                "offset": start,
+                                "length": node.block_size.min(len - start),
+                                "hosts": ["dn1", "dn2"],
+                                "names": ["10.0.0.1:9866", "10.0.0.2:9866"],
+                                "topologyPaths": ["/rack/10.0.0.1:9866", "/rack/10.0.0.2:9866"],
+                                "corrupt": false,
+                            })
+                        })
+                        .collect();
+                    Response::json(
+                        200,
+                        serde_json::json!({ "BlockLocations": { "BlockLocation": blocks } })
+                            .to_string(),
+                    )
+                }
+                Some(_) => Response::error(404, "FileNotFoundException", "Path is not a file"),
+                None => not_found(),
+            },
+            ("GET", "GETFILESTATUS") => match state.nodes.get(&path) {
+                Some(node) => Response::json(
+                    200,
+                    serde_json::json!({ "FileStatus": file_status(node, "") }).to_string(),
+                ),
+                None => not_found(),
+            },
+            ("GET", "LISTSTATUS") | ("GET", "LISTSTATUS_BATCH") => {
+                if !exists {
+                    return not_found();
+                }
+                let prefix = if path == "/" {
+                    "/".to_string()
+                } else {
+                    format!("{}/", path)
+                };
+                let statuses: Vec<_> = state
+                    .nodes
+                    .iter()
+                    .filter(|(name, _)| {
+                        name.starts_with(&prefix)
+                            && !name[prefix.len()..].contains('/')
+                            && name.len() > prefix.len()
+                    })
+                    .map(|(name, node)| file_status(node, &name[prefix.len()..]))
+                    .collect();
+                if op == "LISTSTATUS" {
+                    return Response::json(
+                        200,
+                        serde_json::json!({ "FileStatuses": { "FileStatus": statuses } })
+                            .to_string(),
+                    );
+                }
+
+                // pages of 2 entries, sorted by name
+                let start_after = param("startAfter");
+                state.batches.push(start_after.clone());
+                let mut remaining: Vec<_> = statuses
+                    .into_iter()
+                    .filter(|status| status["pathSuffix"].as_str().unwrap() > start_after.as_str())
+                    .collect();
+                let page: Vec<_> = remaining.drain(..remaining.len().min(2)).collect();
+                Response::json(
+                    200,
+                    serde_json::json!({ "DirectoryListing": {
+                        "partialListing": { "FileStatuses": { "FileStatus": page } },
+                        "remainingEntries": remaining.len(),
+                    } })
+                    .to_string(),
+                )
+            }
+            ("GET", "OPEN") => match state.nodes.get(&path).and_then(|node| node.data.clone()) {
+                Some(data) => {
+                    let offset: usize = param("offset").parse().unwrap_or(0).min(data.len());
+                    let end = match param("length").parse::<usize>() {
+                        Ok(length) => (offset + length).min(data.len()),
+                        Err(_) => data.len(),
+                    };
+                    state.data_node.push(format!("OPEN {}", user));
+                    Response {
+                        status: 200,
+                        headers: vec![],
+                        body: data[offset..end].to_vec(),
+                    }
+                }
+                None => not_found(),
+            },
+            ("PUT", "CREATE") => {
+                if !state.nodes.contains_key(&parent) {
+                    return not_found();
+                }
+                state.data_node.push(format!("CREATE {}", user));
+                let mut node = directory(&user);
+                node.data = Some(request.body.clone());
+                node.permission = 0o644;
+                node.replication = 3;
+                node.block_size = param("blocksize").parse().unwrap_or(134217728);
+                state.nodes.insert(path, node);
+                Response::empty(201)
+            }
+            ("POST", "APPEND") => match state
+                .nodes
+                .get_mut(&path)
+                .and_then(|node| node.data.as_mut())
+            {
+                Some(data) => {
+                    data.extend_from_slice(&request.body);
+                    state.data_node.push(format!("APPEND {}", user));
+                    Response::empty(200)
+                }
+                None => not_found(),
+            },
+            ("PUT", "MKDIRS") => {
+                let mut current = String::new();
+                for segment in path.split('/').filter(|segment| !segment.is_empty()) {
+                    current = format!("{}/{}", current, segment);
+                    state
+                        .nodes
+                        .entry(current.clone())
+                        .or_insert_with(|| directory(&user));
+                }
+                Response::boolean(true)
+            }
+            ("PUT", "RENAME") => {
+                let destination = param("destination");
+                if !exists || state.nodes.contains_key(&destination) {
+                    return Response::boolean(false);
+                }
+                let moved: Vec<String> = state
+                    .nodes
+                    .keys()
+                    .filter(|name| **name == path || name.starts_with(&format!("{}/", path)))
+                    .cloned()
+                    .collect();
+                for name in moved {
+                    let node = state.nodes.remove(&name).unwrap();
+                    state
+                        .nodes
+                        .insert(format!("{}{}", destination, &name[path.len()..]), node);
+                }
+                Response::boolean(true)
+            }
+            ("DELETE", "DELETE") => {
+                if !exists {
+                    return Response::boolean(false);
+                }
+                let children = state
+                    .nodes
+                    .keys()
+                    .any(|name| name.starts_with(&format!("{}/", path)));
+                if children && param("recursive") != "true" {
+                    return Response::error(
+                        403,
+                        "PathIsNotEmptyDirectoryException",
+                        "is non empty",
+                    );
+                }
+                state
+                    .nodes
+                    .retain(|name, _| *name != path && !name.starts_with(&format!("{}/", path)));
+                Response::boolean(true)
+            }
+            ("PUT", "SETPERMISSION") => match state.nodes.get_mut(&path) {
+                Some(node) => match u16::from_str_radix(&param("permission"), 8) {
+                    Ok(permission) => {
+                        node.permission = permission;
+                        Response::empty(200)
+                    }
+                    Err(_) => Response::error(400, "IllegalArgumentException", "bad permission"),
+                },
+                None => not_found(),
+            },
+            ("PUT", "SETOWNER") => match state.nodes.get_mut(&path) {
+                Some(node) => {
+                    if let Some(owner) = params.get("owner") {
+                        node.owner = owner.clone();
+                    }
+                    if let Some(group) = params.get("group") {
+                        node.group = group.clone();
+                    }
+                    Response::empty(200)
+                }
+                None => not_found(),
+            },
+            ("PUT", "SETTIMES") => match state.nodes.get_mut(&path) {
+                Some(node) => {
+                    let time = |name: &str, current: i64| match param(name).parse() {
+                        Ok(-1) | Err(_) => current,
+                        Ok(time) => time,
+                    };
+                    node.times = (
+                        time("modificationtime", node.times.0),
+                        time("accesstime", node.times.1),
+                    );
+                    Response::empty(200)
+                }
+                None => not_found(),
+            },
+            ("PUT", "SETREPLICATION") => match state.nodes.get_mut(&path) {
+                Some(node) if node.data.is_some() => {
+                    node.replication = param("replication").parse().unwrap_or(0);
+                    Response::boolean(true)
+                }
+                Some(_) => Response::boolean(false),
+                None => not_found(),
+            },
+            _ => Response::error(400, "IllegalArgumentException", "Invalid operation"),
+        }
+    }
+
+    fn file_status(node: &Node, suffix: &str) -> serde_json::Value {
+        serde_json::json!({
+            "accessTime": node.times.1,
+            "blockSize": node.block_size,
+            "group": node.group,
+            "length": node.data.as_ref().map(|data| data.len()).unwrap_or(0),
+            "modificationTime": node.times.0,
+            "owner": node.owner,
+            "pathSuffix": suffix,
+            "permission": format!("{:o}", node.permission),
+            "replication": node.replication,
+            "type": if node.data.is_some() { "FILE" } else { "DIRECTORY" },
+        })
+    }
+
+    #[test]
+    fn should_write_and_read_files() {
+        let server = MockWebHdfs::start();
+        let fs = HdfsRegistry::new()
+            .get(&format!("webhdfs://alice@127.0.0.1:{}", server.port))
+            .expect("client created");
+        assert_eq!(server.url(), fs.url());
+
+        assert!(fs.mkdir("/data/in").expect("directory created"));
+        let mut file = fs.create("/data/in/file.txt").expect("file created");
+        file.write_all(b"hello ").expect("data written");
+        file.write_all(b"webhdfs").expect("data written");
+        file.close().expect("file closed");
+        let err = fs.create("/data/in/file.txt").unwrap_err();
+        assert_eq!(ErrorKind::AlreadyExists, err.kind());
+        let mut file = fs
+            .create_with_overwrite("/data/in/file.txt", true)
+            .expect("file overwritten");
+        file.write_all(b"hello").expect("data written");
+        drop(file);
+        let mut file = fs.append("/data/in/file.txt").expect("file opened");
+        assert!(file.is_writable());
+        assert_eq!(5, file.pos().unwrap());
+        file.write_all(b" webhdfs world").expect("data appended");
+        assert!(file.hflush());
+        file.close().expect("file closed");
+        assert_eq!(
+            vec![
+                "CREATE alice",
+                "APPEND alice",
+                "CREATE alice",
+                "APPEND alice",
+                "APPEND alice"
+            ],
+            server.state.lock().unwrap().data_node
+        );
+
+        let mut file = fs.open("/data/in/file.txt").expect("file opened");
+        assert!(file.is_readable());
+        let mut content = String::new();
+        file.read_to_string(&mut content).expect("data read");
+        assert_eq!("hello webhdfs world", content);
+        let mut buf = [0u8; 7];
+        assert_eq!(7, file.read_with_pos(6, &mut buf).expect("data read"));
+        assert_eq!(b"webhdfs", &buf);
+        assert_eq!(13, file.pos().unwrap());
+        assert!(file.seek(14));
+        content.clear();
+        file.read_to_string(&mut content).expect("data read");
+        assert_eq!("world", content);
+        assert!(!file.seek(100));
+        let err = file.try_seek(100).expect_err("seek past end");
+        assert_eq!(ErrorKind::InvalidInput, err.kind());
+        assert!(file.write(b"data").is_err());
+
+        let status = fs.get_file_status("/data/in/file.txt").expect("status");
+        assert!(status.is_file());
+        assert_eq!("/data/in/file.txt", status.name());
+        assert_eq!(19, status.len());
+        assert_eq!("alice", status.owner());
+        assert_eq!(0o644, status.fs_permission().mode());
+        assert_eq!(3, status.replica_count());
+        assert_eq!(1_600_000_001, status.last_modified());
+
+        let listing = fs.list_status("/data").expect("listed");
+        assert_eq!(1, listing.len());
+        assert_eq!("/data/in", listing[0].name());
+        assert!(listing[0].is_directory());
+        assert_eq!(1, fs.list_status("/").unwrap().len());
+
+        let err = fs.open("/data/missing").expect_err("open failed");
+        assert_eq!(ErrorKind::NotFound, err.kind());
+        assert_eq!("File does not exist", err.to_string());
+        assert!(fs.open("/data").is_err());
+        assert!(server
+            .state
+            .lock()
+            .unwrap()
+            .users
+            .iter()
+            .all(|user| user == "alice"));
+    }
+
+    #[test]
+    fn should_send_large_writes_in_chunks() {
+        let server = MockWebHdfs::start();
+        let fs = HdfsRegistry::new()
+            .get(&format!("webhdfs://alice@127.0.0.1:{}", server.port))
+            .expect("client created");
+
+        // larger than the write buffer of 8 MiB
+        let data = vec![7u8; 8 * 1024 * 1024 + 3];
+        let mut file = fs.create("/large").expect("file created");
+        assert_eq!(data.len(), file.write(&data).expect("data written"));
+        assert_eq!(data.len() as u64, file.pos().unwrap());
+        assert_eq!(
+            vec!["CREATE alice", "APPEND alice"],
+            server.state.lock().unwrap().data_node
+        );
+        Write::flush(&mut file).expect("data flushed");
+        assert_eq!(3, server.state.lock().unwrap().data_node.len());
+        file.close().expect("file closed");
+
+        assert_eq!(data.len(), fs.get_file_status("/large").unwrap().len());
+    }
+
+    #[test]
+    fn should_list_directories_in_pages() {
+        let server = MockWebHdfs::start();
+        let fs = HdfsRegistry::new()
+            .get(&format!("webhdfs://alice@127.0.0.1:{}", server.port))
+            .expect("client created");
+        for name in ["a", "b", "c", "d", "e"] {
+            assert!(fs.mkdir(&format!("/dir/{}", name)).unwrap());
+        }
+
+        // pages are only fetched when the iterator reaches them
+        let mut iter = fs.list_status_iter("/dir");
+        assert_eq!("/dir/a", iter.next().unwrap().unwrap().name());
+        assert_eq!((1, None), iter.size_hint());
+        assert_eq!(vec![""], server.state.lock().unwrap().batches);
+
+        let names: Vec<String> = iter
+            .map(|status| status.unwrap().name().to_string())
+            .collect();
+        assert_eq!(vec!["/dir/b", "/dir/c", "/dir/d", "/dir/e"], names);
+        assert_eq!(vec!["", "b", "d"], server.state.lock().unwrap().batches);
+
+        let sizes: Vec<usize> = fs
+            .list_status_iter("/dir")
+            .batches(3)
+            .map(|batch| batch.unwrap().len())
+            .collect();
+        assert_eq!(vec![3, 2], sizes);
+        assert!(fs.list_status_iter("/dir/a").next().is_none());
+
+        let mut iter = fs.list_status_iter("/missing");
+        let err = iter.next().unwrap().unwrap_err();
+        assert_eq!(ErrorKind::NotFound, err.kind());
+        assert!(iter.next().is_none());
+    }
+
+    #[test]
+    fn should_update_metadata() {
+        let server = MockWebHdfs::start();
+        let registry = HdfsRegistry::new();
+        let url = format!("webhdfs://alice@127.0.0.1:{}", server.port);
+        let fs = registry.get(&url).expect("client created");
+        assert!(Arc::ptr_eq(&fs, &registry.get(&url).unwrap()));
+        let bob = format!("webhdfs://bob@127.0.0.1:{}", server.port);
+        assert!(!Arc::ptr_eq(&fs, &registry.get(&bob).unwrap()));
+
+        assert!(fs.mkdir("/a/b").unwrap());
+        fs.create("/a/b/file").unwrap().close().unwrap();
+        assert!(fs.exist("/a/b/file"));
+
+        assert!(fs.chmod("/a/b/file", 0o600));
+        assert!(fs.chown("/a/b/file", "bob", ""));
+        // errors of the server are reported, not the OS error of the thread
+        let err = fs.try_chmod("/a/missing", 0o600).unwrap_err();
+        assert_eq!(ErrorKind::NotFound, err.kind());
+        assert_eq!("File does not exist", err.to_string());
+        let err = fs.try_chown("/a/missing", "bob", "").unwrap_err();
+        assert_eq!(ErrorKind::NotFound, err.kind());
+        assert!(fs.set_replication("/a/b/file", 2).unwrap());
+        assert!(fs.set_replication("/a/b", 2).is_err());
+        let modified = UNIX_EPOCH + Duration::from_secs(1000);
+        assert!(fs.set_times("/a/b/file", Some(modified), None).unwrap());
+        let status = fs.get_file_status("/a/b/file").unwrap();
+        assert_eq!(0o600, status.fs_permission().mode());
+        assert_eq!("bob", status.owner());
+        assert_eq!("supergroup", status.group());
+        assert_eq!(2, status.replica_count());
+        assert_eq!(1000, status.last_modified());
+        assert_eq!(1_600_000_000, status.last_accced());
+
+        assert!(fs.rename("/a/b", "/a/c").unwrap());
+        assert!(!fs.exist("/a/b/file"));
+        assert!(fs.exist("/a/c/file"));
+        assert!(fs.rename("/a/missing", "/a/d").is_err());
+
+        assert!(fs.delete("/a", false).is_err());
+        assert!(fs.delete("/a", true).unwrap());
+        assert_eq!(
+            ErrorKind::NotFound,
+            fs.delete("/a", true).unwrap_err().kind()
+        );
+        assert_eq!(
+            ErrorKind::NotFound,
+            fs.list_status("/a").unwrap_err().kind()
+        );
+    }
+
+    #[test]
+    fn should_resolve_relative_paths() {
+        let server = MockWebHdfs::start();
+        let fs = HdfsRegistry::new()
+            .get(&format!("webhdfs://alice@127.0.0.1:{}", server.port))
+            .expect("client created");
+
+        assert_eq!("/user/alice", fs.working_directory().unwrap());
+        assert!(fs.mkdir("logs").unwrap());
+        assert!(fs.exist("/user/alice/logs"));
+        let status = fs.get_file_status("./logs").unwrap();
+        assert_eq!("/user/alice/logs", status.name());
+        let url = format!("{}/user/alice/logs", server.url());
+        assert!(fs.get_file_status(&url).unwrap().is_directory());
+    }
+
+    #[test]
+    fn should_report_blocks_and_status() {
+        let server = MockWebHdfs::start();
+        let fs = HdfsRegistry::new()
+            .get(&server.url())
+            .expect("client created");
+
+        let mut file = fs
+            .create_with_block_size("/blocks", false, 4)
+            .expect("file created");
+        file.write_all(b"0123456789").unwrap();
+        file.close().unwrap();
+        assert_eq!(4, fs.get_file_status("/blocks").unwrap().block_size());
+
+        let locations = fs.get_block_locations("/blocks", 0, 10).unwrap();
+        assert_eq!(3, locations.len());
+        assert_eq!(
+            vec![(0, 4), (4, 4), (8, 2)],
+            locations
+                .iter()
+                .map(|location| (location.offset, location.length))
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(vec!["dn1", "dn2"], locations[0].hosts);
+        assert_eq!(vec!["10.0.0.1:9866", "10.0.0.2:9866"], locations[1].names);
+        assert_eq!(1, fs.get_block_locations("/blocks", 5, 2).unwrap().len());
+        let hosts = fs.get_hosts("/blocks", 0, 10).unwrap();
+        assert_eq!(3, hosts.len());
+        assert_eq!(Some(vec!["dn1", "dn2"]), hosts.get(2));
+
+        let status = fs.status().expect("status");
+        assert_eq!(1000, status.capacity);
+        assert_eq!(10, status.used);
+        assert_eq!(134217728, status.default_block_size);
+    }
+
+    #[test]
+    fn should_reject_invalid_urls() {
+        let registry = HdfsRegistry::new();
+        assert_eq!(
+            ErrorKind::InvalidInput,
+            registry.get("webhdfs://127.0.0.1").unwrap_err().kind()
+        );
+    }
+}